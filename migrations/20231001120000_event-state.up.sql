@@ -0,0 +1,6 @@
+-- persist event state so the cli and server restarts can pick up the current event : )
+-- existing events are treated as ended
+ALTER TABLE events ADD COLUMN state TEXT NOT NULL DEFAULT 'stopped';
+
+-- only one event can be added/started at a time
+CREATE UNIQUE INDEX idx_events_current ON events ((state != 'stopped')) WHERE state != 'stopped';
@@ -0,0 +1,3 @@
+ALTER TABLE events DROP COLUMN max_delta;
+ALTER TABLE events DROP COLUMN max_score;
+ALTER TABLE events DROP COLUMN min_score;
@@ -0,0 +1,4 @@
+-- optional per event score bounds and per update delta cap , NULL is unbounded : )
+ALTER TABLE events ADD COLUMN min_score INTEGER DEFAULT NULL;
+ALTER TABLE events ADD COLUMN max_score INTEGER DEFAULT NULL;
+ALTER TABLE events ADD COLUMN max_delta INTEGER DEFAULT NULL;
@@ -0,0 +1,30 @@
+DROP TRIGGER users_fts_insert;
+DROP TRIGGER users_fts_update;
+DROP TRIGGER users_fts_delete;
+DROP TRIGGER teams_fts_insert;
+DROP TRIGGER teams_fts_update;
+DROP TRIGGER teams_fts_delete;
+
+CREATE TRIGGER users_fts_insert AFTER INSERT ON users BEGIN
+   INSERT INTO users_fts(id,name,score,logo) VALUES(new.id,new.name,new.score,new.logo);
+END;
+
+CREATE TRIGGER teams_fts_insert AFTER INSERT ON teams BEGIN
+   INSERT INTO teams_fts(id,name,score,logo) VALUES(new.id,new.name,new.score,new.logo);
+END;
+
+CREATE TRIGGER users_fts_update AFTER UPDATE OF score ON users BEGIN
+  UPDATE users_fts SET score = new.score WHERE id=old.id;
+END;
+
+CREATE TRIGGER teams_fts_update AFTER UPDATE OF score ON teams BEGIN
+  UPDATE teams_fts SET score = new.score WHERE id=old.id;
+END;
+
+CREATE TRIGGER users_fts_delete AFTER DELETE ON users BEGIN
+  DELETE FROM  users_fts WHERE id=old.id;
+END;
+
+CREATE TRIGGER teams_fts_delete AFTER DELETE ON teams BEGIN
+  DELETE FROM teams_fts WHERE id=old.id;
+END;
@@ -0,0 +1,40 @@
+-- teams_fts/users_fts are external content tables , they read columns back from the
+-- content table by rowid , so every trigger keys the fts row on the content rowid
+-- and updates are a 'delete' of the old values + insert of the new ones : )
+
+DROP TRIGGER users_fts_insert;
+DROP TRIGGER users_fts_update;
+DROP TRIGGER users_fts_delete;
+DROP TRIGGER teams_fts_insert;
+DROP TRIGGER teams_fts_update;
+DROP TRIGGER teams_fts_delete;
+
+CREATE TRIGGER users_fts_insert AFTER INSERT ON users BEGIN
+  INSERT INTO users_fts(rowid,id,name,score,logo) VALUES(new.rowid,new.id,new.name,new.score,new.logo);
+END;
+
+CREATE TRIGGER users_fts_update AFTER UPDATE OF id,name,score,logo ON users BEGIN
+  INSERT INTO users_fts(users_fts,rowid,id,name,score,logo) VALUES('delete',old.rowid,old.id,old.name,old.score,old.logo);
+  INSERT INTO users_fts(rowid,id,name,score,logo) VALUES(new.rowid,new.id,new.name,new.score,new.logo);
+END;
+
+CREATE TRIGGER users_fts_delete AFTER DELETE ON users BEGIN
+  INSERT INTO users_fts(users_fts,rowid,id,name,score,logo) VALUES('delete',old.rowid,old.id,old.name,old.score,old.logo);
+END;
+
+CREATE TRIGGER teams_fts_insert AFTER INSERT ON teams BEGIN
+  INSERT INTO teams_fts(rowid,id,name,score,logo) VALUES(new.rowid,new.id,new.name,new.score,new.logo);
+END;
+
+CREATE TRIGGER teams_fts_update AFTER UPDATE OF id,name,score,logo ON teams BEGIN
+  INSERT INTO teams_fts(teams_fts,rowid,id,name,score,logo) VALUES('delete',old.rowid,old.id,old.name,old.score,old.logo);
+  INSERT INTO teams_fts(rowid,id,name,score,logo) VALUES(new.rowid,new.id,new.name,new.score,new.logo);
+END;
+
+CREATE TRIGGER teams_fts_delete AFTER DELETE ON teams BEGIN
+  INSERT INTO teams_fts(teams_fts,rowid,id,name,score,logo) VALUES('delete',old.rowid,old.id,old.name,old.score,old.logo);
+END;
+
+-- old triggers could leave fts rowids out of step with the content rows
+INSERT INTO users_fts(users_fts) VALUES('rebuild');
+INSERT INTO teams_fts(teams_fts) VALUES('rebuild');
@@ -0,0 +1,11 @@
+DROP TRIGGER events_fts_insert;
+DROP TRIGGER events_fts_update;
+DROP TRIGGER events_fts_delete;
+
+CREATE TRIGGER events_fts_insert AFTER INSERT ON events BEGIN
+   INSERT INTO events_fts(id,name,logo,event_type,team_size) VALUES(new.id,new.name,new.logo,new.event_type,new.team_size);
+END;
+
+CREATE TRIGGER events_fts_delete AFTER DELETE ON events BEGIN
+  DELETE FROM events_fts WHERE id=old.id;
+END;
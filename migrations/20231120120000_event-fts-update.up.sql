@@ -0,0 +1,20 @@
+-- events_fts had no update trigger and keyed rows on id instead of the content rowid ,
+-- same fix as teams/users : )
+
+DROP TRIGGER events_fts_insert;
+DROP TRIGGER events_fts_delete;
+
+CREATE TRIGGER events_fts_insert AFTER INSERT ON events BEGIN
+  INSERT INTO events_fts(rowid,id,name,logo,event_type,team_size) VALUES(new.rowid,new.id,new.name,new.logo,new.event_type,new.team_size);
+END;
+
+CREATE TRIGGER events_fts_update AFTER UPDATE OF id,name,logo,event_type,team_size ON events BEGIN
+  INSERT INTO events_fts(events_fts,rowid,id,name,logo,event_type,team_size) VALUES('delete',old.rowid,old.id,old.name,old.logo,old.event_type,old.team_size);
+  INSERT INTO events_fts(rowid,id,name,logo,event_type,team_size) VALUES(new.rowid,new.id,new.name,new.logo,new.event_type,new.team_size);
+END;
+
+CREATE TRIGGER events_fts_delete AFTER DELETE ON events BEGIN
+  INSERT INTO events_fts(events_fts,rowid,id,name,logo,event_type,team_size) VALUES('delete',old.rowid,old.id,old.name,old.logo,old.event_type,old.team_size);
+END;
+
+INSERT INTO events_fts(events_fts) VALUES('rebuild');
@@ -0,0 +1,14 @@
+DROP TRIGGER events_trgm_insert;
+DROP TRIGGER events_trgm_update;
+DROP TRIGGER events_trgm_delete;
+DROP TABLE events_trgm;
+
+DROP TRIGGER teams_trgm_insert;
+DROP TRIGGER teams_trgm_update;
+DROP TRIGGER teams_trgm_delete;
+DROP TABLE teams_trgm;
+
+DROP TRIGGER users_trgm_insert;
+DROP TRIGGER users_trgm_update;
+DROP TRIGGER users_trgm_delete;
+DROP TABLE users_trgm;
@@ -0,0 +1,52 @@
+-- trigram indexes on names for typo tolerant search , kept in sync like the *_fts tables : )
+
+CREATE VIRTUAL TABLE events_trgm USING FTS5(name,content='events',tokenize='trigram');
+
+CREATE TRIGGER events_trgm_insert AFTER INSERT ON events BEGIN
+  INSERT INTO events_trgm(rowid,name) VALUES(new.rowid,new.name);
+END;
+
+CREATE TRIGGER events_trgm_update AFTER UPDATE OF name ON events BEGIN
+  INSERT INTO events_trgm(events_trgm,rowid,name) VALUES('delete',old.rowid,old.name);
+  INSERT INTO events_trgm(rowid,name) VALUES(new.rowid,new.name);
+END;
+
+CREATE TRIGGER events_trgm_delete AFTER DELETE ON events BEGIN
+  INSERT INTO events_trgm(events_trgm,rowid,name) VALUES('delete',old.rowid,old.name);
+END;
+
+INSERT INTO events_trgm(events_trgm) VALUES('rebuild');
+
+CREATE VIRTUAL TABLE teams_trgm USING FTS5(name,content='teams',tokenize='trigram');
+
+CREATE TRIGGER teams_trgm_insert AFTER INSERT ON teams BEGIN
+  INSERT INTO teams_trgm(rowid,name) VALUES(new.rowid,new.name);
+END;
+
+CREATE TRIGGER teams_trgm_update AFTER UPDATE OF name ON teams BEGIN
+  INSERT INTO teams_trgm(teams_trgm,rowid,name) VALUES('delete',old.rowid,old.name);
+  INSERT INTO teams_trgm(rowid,name) VALUES(new.rowid,new.name);
+END;
+
+CREATE TRIGGER teams_trgm_delete AFTER DELETE ON teams BEGIN
+  INSERT INTO teams_trgm(teams_trgm,rowid,name) VALUES('delete',old.rowid,old.name);
+END;
+
+INSERT INTO teams_trgm(teams_trgm) VALUES('rebuild');
+
+CREATE VIRTUAL TABLE users_trgm USING FTS5(name,content='users',tokenize='trigram');
+
+CREATE TRIGGER users_trgm_insert AFTER INSERT ON users BEGIN
+  INSERT INTO users_trgm(rowid,name) VALUES(new.rowid,new.name);
+END;
+
+CREATE TRIGGER users_trgm_update AFTER UPDATE OF name ON users BEGIN
+  INSERT INTO users_trgm(users_trgm,rowid,name) VALUES('delete',old.rowid,old.name);
+  INSERT INTO users_trgm(rowid,name) VALUES(new.rowid,new.name);
+END;
+
+CREATE TRIGGER users_trgm_delete AFTER DELETE ON users BEGIN
+  INSERT INTO users_trgm(users_trgm,rowid,name) VALUES('delete',old.rowid,old.name);
+END;
+
+INSERT INTO users_trgm(users_trgm) VALUES('rebuild');
@@ -0,0 +1,18 @@
+-- who did what through the admin api , one row per mutating request : )
+-- target_ids is a json array of uuids , summary a json object of the payload
+CREATE TABLE audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    created_at TEXT NOT NULL,
+    actor TEXT,
+    request_id TEXT,
+    action TEXT NOT NULL,
+    event_id UUID,
+    target_ids TEXT NOT NULL DEFAULT '[]',
+    summary TEXT NOT NULL DEFAULT '{}',
+    outcome TEXT NOT NULL,
+    status INTEGER NOT NULL,
+    error TEXT
+);
+
+CREATE INDEX idx_audit_log_action ON audit_log (action);
+CREATE INDEX idx_audit_log_event ON audit_log (event_id);
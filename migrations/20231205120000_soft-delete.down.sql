@@ -0,0 +1,12 @@
+-- trashed rows would come back as live ones , drop them first
+DELETE FROM events WHERE deleted_at IS NOT NULL;
+DELETE FROM teams WHERE deleted_at IS NOT NULL;
+DELETE FROM users WHERE deleted_at IS NOT NULL;
+
+DROP INDEX idx_users_deleted;
+DROP INDEX idx_teams_deleted;
+DROP INDEX idx_events_deleted;
+
+ALTER TABLE users DROP COLUMN deleted_at;
+ALTER TABLE teams DROP COLUMN deleted_at;
+ALTER TABLE events DROP COLUMN deleted_at;
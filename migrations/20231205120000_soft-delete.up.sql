@@ -0,0 +1,10 @@
+-- deleting moves events/teams/users to the trash , their event/team links are kept so a
+-- restore brings everything back , `vader-admin trash purge` removes them for good : )
+-- fts/trgm rows stay in sync with the base tables , searches skip deleted rows instead
+ALTER TABLE events ADD COLUMN deleted_at TIMESTAMP DEFAULT NULL;
+ALTER TABLE teams ADD COLUMN deleted_at TIMESTAMP DEFAULT NULL;
+ALTER TABLE users ADD COLUMN deleted_at TIMESTAMP DEFAULT NULL;
+
+CREATE INDEX idx_events_deleted ON events (deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX idx_teams_deleted ON teams (deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX idx_users_deleted ON users (deleted_at) WHERE deleted_at IS NOT NULL;
@@ -0,0 +1,5 @@
+DROP INDEX idx_team_members_captain;
+ALTER TABLE team_members DROP COLUMN role;
+
+DROP INDEX idx_team_waitlist_team;
+DROP TABLE team_waitlist;
@@ -0,0 +1,15 @@
+-- users waiting for a place in a full team , first in first out by id : )
+-- a user waits for atmost one team and only while they have no team
+CREATE TABLE team_waitlist (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    team_id UUID NOT NULL,
+    user_id UUID NOT NULL UNIQUE,
+    created_at TIMESTAMP NOT NULL,
+    FOREIGN KEY (team_id) REFERENCES teams (id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
+CREATE INDEX idx_team_waitlist_team ON team_waitlist (team_id, id);
+
+-- atmost one captain per team , the role leaves with the membership
+ALTER TABLE team_members ADD COLUMN role TEXT NOT NULL DEFAULT 'member' CHECK (role IN ('member', 'captain'));
+CREATE UNIQUE INDEX idx_team_members_captain ON team_members (team_id) WHERE role = 'captain';
@@ -0,0 +1,4 @@
+DROP INDEX idx_registrations_event;
+DROP TABLE registrations;
+
+ALTER TABLE events DROP COLUMN self_registration;
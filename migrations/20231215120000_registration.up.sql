@@ -0,0 +1,16 @@
+-- participants register their own team , it joins event_teams once an admin approves it
+-- teammates join with the invite_code of the registration : )
+ALTER TABLE events ADD COLUMN self_registration BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE registrations (
+    id UUID PRIMARY KEY,
+    event_id UUID NOT NULL,
+    team_id UUID NOT NULL UNIQUE,
+    invite_code TEXT NOT NULL UNIQUE,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'approved', 'rejected')),
+    created_at TIMESTAMP NOT NULL,
+    decided_at TIMESTAMP DEFAULT NULL,
+    FOREIGN KEY (event_id) REFERENCES events (id) ON DELETE CASCADE,
+    FOREIGN KEY (team_id) REFERENCES teams (id) ON DELETE CASCADE
+);
+CREATE INDEX idx_registrations_event ON registrations (event_id, status, created_at);
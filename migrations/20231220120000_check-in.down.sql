@@ -0,0 +1,4 @@
+ALTER TABLE events DROP COLUMN exclude_unchecked;
+
+ALTER TABLE event_users DROP COLUMN checked_in_at;
+ALTER TABLE event_teams DROP COLUMN checked_in_at;
@@ -0,0 +1,6 @@
+-- when a team/user showed up for the event , NULL till they check in : )
+ALTER TABLE event_teams ADD COLUMN checked_in_at TIMESTAMP DEFAULT NULL;
+ALTER TABLE event_users ADD COLUMN checked_in_at TIMESTAMP DEFAULT NULL;
+
+-- drop teams/users that did not check in from the event when it starts
+ALTER TABLE events ADD COLUMN exclude_unchecked BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,150 @@
+-- trashed events/teams/users keep their names , so the names are only unique among
+-- the live rows , a restore checks the name is still free : )
+-- sqlite can't drop a UNIQUE column constraint , the tables are rebuilt instead
+-- (run_migrations turns foreign keys off , so the drops don't cascade)
+-- rowids are copied over , the fts/trgm indexes are keyed by them
+
+-- renames recheck every trigger , this one would point at the dropped events table
+DROP TRIGGER team_members_check;
+
+CREATE TABLE events_new (
+    id UUID PRIMARY KEY,
+    name TEXT,
+    logo TEXT,
+    event_type TEXT,
+    team_size INTEGER DEFAULT NULL,
+    created_at TIMESTAMP DEFAULT TIMESTAMP NOT NULL,
+    state TEXT NOT NULL DEFAULT 'stopped',
+    min_score INTEGER DEFAULT NULL,
+    max_score INTEGER DEFAULT NULL,
+    max_delta INTEGER DEFAULT NULL,
+    deleted_at TIMESTAMP DEFAULT NULL,
+    self_registration BOOLEAN NOT NULL DEFAULT FALSE,
+    exclude_unchecked BOOLEAN NOT NULL DEFAULT FALSE
+);
+INSERT INTO events_new (rowid,id,name,logo,event_type,team_size,created_at,state,min_score,
+    max_score,max_delta,deleted_at,self_registration,exclude_unchecked)
+SELECT rowid,id,name,logo,event_type,team_size,created_at,state,min_score,
+    max_score,max_delta,deleted_at,self_registration,exclude_unchecked FROM events;
+DROP TABLE events;
+ALTER TABLE events_new RENAME TO events;
+
+CREATE TABLE teams_new (
+    id UUID PRIMARY KEY,
+    name TEXT,
+    score INTEGER,
+    logo TEXT,
+    created_at TIMESTAMP DEFAULT TIMESTAMP NOT NULL,
+    deleted_at TIMESTAMP DEFAULT NULL
+);
+INSERT INTO teams_new (rowid,id,name,score,logo,created_at,deleted_at)
+SELECT rowid,id,name,score,logo,created_at,deleted_at FROM teams;
+DROP TABLE teams;
+ALTER TABLE teams_new RENAME TO teams;
+
+CREATE TABLE users_new (
+    id UUID PRIMARY KEY,
+    name TEXT,
+    score INTEGER,
+    logo TEXT,
+    created_at TIMESTAMP DEFAULT TIMESTAMP NOT NULL,
+    deleted_at TIMESTAMP DEFAULT NULL
+);
+INSERT INTO users_new (rowid,id,name,score,logo,created_at,deleted_at)
+SELECT rowid,id,name,score,logo,created_at,deleted_at FROM users;
+DROP TABLE users;
+ALTER TABLE users_new RENAME TO users;
+
+CREATE UNIQUE INDEX idx_events_name ON events (name) WHERE deleted_at IS NULL;
+CREATE UNIQUE INDEX idx_teams_name ON teams (name) WHERE deleted_at IS NULL;
+CREATE UNIQUE INDEX idx_users_name ON users (name) WHERE deleted_at IS NULL;
+
+-- the indexes and triggers went with the old tables
+CREATE INDEX idx_events_timestamp ON events (created_at);
+CREATE UNIQUE INDEX idx_events_current ON events ((state != 'stopped')) WHERE state != 'stopped';
+CREATE INDEX idx_events_deleted ON events (deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX idx_teams_score ON teams (score);
+CREATE INDEX idx_teams_timestamp ON teams (created_at);
+CREATE INDEX idx_teams_deleted ON teams (deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX idx_users_score ON users (score);
+CREATE INDEX idx_users_timestamp ON users (created_at);
+CREATE INDEX idx_users_deleted ON users (deleted_at) WHERE deleted_at IS NOT NULL;
+
+CREATE TRIGGER team_members_check BEFORE INSERT ON team_members FOR EACH ROW
+WHEN ( SELECT COUNT(*) FROM team_members WHERE team_id=NEW.team_id ) >= (
+      SELECT team_size FROM events WHERE id=(SELECT event_id FROM event_teams WHERE team_id=NEW.team_id ) )
+BEGIN
+    SELECT RAISE(ABORT,'Count of team_members exceeds team_size');
+END;
+
+CREATE TRIGGER team_size_check BEFORE INSERT ON events FOR EACH ROW
+WHEN ( NEW.event_type='team_event' AND NEW.team_size IS NULL )
+BEGIN
+  SELECT RAISE(ABORT,'team_size cannot be null for team_event');
+END;
+
+CREATE TRIGGER events_fts_insert AFTER INSERT ON events BEGIN
+  INSERT INTO events_fts(rowid,id,name,logo,event_type,team_size) VALUES(new.rowid,new.id,new.name,new.logo,new.event_type,new.team_size);
+END;
+CREATE TRIGGER events_fts_update AFTER UPDATE OF id,name,logo,event_type,team_size ON events BEGIN
+  INSERT INTO events_fts(events_fts,rowid,id,name,logo,event_type,team_size) VALUES('delete',old.rowid,old.id,old.name,old.logo,old.event_type,old.team_size);
+  INSERT INTO events_fts(rowid,id,name,logo,event_type,team_size) VALUES(new.rowid,new.id,new.name,new.logo,new.event_type,new.team_size);
+END;
+CREATE TRIGGER events_fts_delete AFTER DELETE ON events BEGIN
+  INSERT INTO events_fts(events_fts,rowid,id,name,logo,event_type,team_size) VALUES('delete',old.rowid,old.id,old.name,old.logo,old.event_type,old.team_size);
+END;
+
+CREATE TRIGGER teams_fts_insert AFTER INSERT ON teams BEGIN
+  INSERT INTO teams_fts(rowid,id,name,score,logo) VALUES(new.rowid,new.id,new.name,new.score,new.logo);
+END;
+CREATE TRIGGER teams_fts_update AFTER UPDATE OF id,name,score,logo ON teams BEGIN
+  INSERT INTO teams_fts(teams_fts,rowid,id,name,score,logo) VALUES('delete',old.rowid,old.id,old.name,old.score,old.logo);
+  INSERT INTO teams_fts(rowid,id,name,score,logo) VALUES(new.rowid,new.id,new.name,new.score,new.logo);
+END;
+CREATE TRIGGER teams_fts_delete AFTER DELETE ON teams BEGIN
+  INSERT INTO teams_fts(teams_fts,rowid,id,name,score,logo) VALUES('delete',old.rowid,old.id,old.name,old.score,old.logo);
+END;
+
+CREATE TRIGGER users_fts_insert AFTER INSERT ON users BEGIN
+  INSERT INTO users_fts(rowid,id,name,score,logo) VALUES(new.rowid,new.id,new.name,new.score,new.logo);
+END;
+CREATE TRIGGER users_fts_update AFTER UPDATE OF id,name,score,logo ON users BEGIN
+  INSERT INTO users_fts(users_fts,rowid,id,name,score,logo) VALUES('delete',old.rowid,old.id,old.name,old.score,old.logo);
+  INSERT INTO users_fts(rowid,id,name,score,logo) VALUES(new.rowid,new.id,new.name,new.score,new.logo);
+END;
+CREATE TRIGGER users_fts_delete AFTER DELETE ON users BEGIN
+  INSERT INTO users_fts(users_fts,rowid,id,name,score,logo) VALUES('delete',old.rowid,old.id,old.name,old.score,old.logo);
+END;
+
+CREATE TRIGGER events_trgm_insert AFTER INSERT ON events BEGIN
+  INSERT INTO events_trgm(rowid,name) VALUES(new.rowid,new.name);
+END;
+CREATE TRIGGER events_trgm_update AFTER UPDATE OF name ON events BEGIN
+  INSERT INTO events_trgm(events_trgm,rowid,name) VALUES('delete',old.rowid,old.name);
+  INSERT INTO events_trgm(rowid,name) VALUES(new.rowid,new.name);
+END;
+CREATE TRIGGER events_trgm_delete AFTER DELETE ON events BEGIN
+  INSERT INTO events_trgm(events_trgm,rowid,name) VALUES('delete',old.rowid,old.name);
+END;
+
+CREATE TRIGGER teams_trgm_insert AFTER INSERT ON teams BEGIN
+  INSERT INTO teams_trgm(rowid,name) VALUES(new.rowid,new.name);
+END;
+CREATE TRIGGER teams_trgm_update AFTER UPDATE OF name ON teams BEGIN
+  INSERT INTO teams_trgm(teams_trgm,rowid,name) VALUES('delete',old.rowid,old.name);
+  INSERT INTO teams_trgm(rowid,name) VALUES(new.rowid,new.name);
+END;
+CREATE TRIGGER teams_trgm_delete AFTER DELETE ON teams BEGIN
+  INSERT INTO teams_trgm(teams_trgm,rowid,name) VALUES('delete',old.rowid,old.name);
+END;
+
+CREATE TRIGGER users_trgm_insert AFTER INSERT ON users BEGIN
+  INSERT INTO users_trgm(rowid,name) VALUES(new.rowid,new.name);
+END;
+CREATE TRIGGER users_trgm_update AFTER UPDATE OF name ON users BEGIN
+  INSERT INTO users_trgm(users_trgm,rowid,name) VALUES('delete',old.rowid,old.name);
+  INSERT INTO users_trgm(rowid,name) VALUES(new.rowid,new.name);
+END;
+CREATE TRIGGER users_trgm_delete AFTER DELETE ON users BEGIN
+  INSERT INTO users_trgm(users_trgm,rowid,name) VALUES('delete',old.rowid,old.name);
+END;
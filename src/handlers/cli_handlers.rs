@@ -0,0 +1,829 @@
+use std::fs;
+use std::path::Path;
+
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::models::checkin_models::CheckInToken;
+use crate::models::cli_models::{
+    AdminCmd, CheckinCmd, CliResponse, CliStatus, DbCmd, EventCmd, ExportArgs, ImportArgs,
+    RegistrationCmd, ScoreCmd, TeamCmd, TrashCmd, UserCmd, VaderCommand,
+};
+use crate::models::command_models::{
+    ContestantInfo, EventReq, PlayerPatch, ScoreUpdate, TeamWithMembers,
+};
+use crate::models::config_models::VaderConfig;
+use crate::models::error_models::VaderError;
+use crate::models::export_models::EventExport;
+use crate::models::import_models::{ImportFormat, ImportReport};
+use crate::models::membership_models::JoinRes;
+use crate::models::query_models::{EventInfo, EventType, TeamInfo};
+use crate::models::registration_models::RegistrationStatus;
+use crate::models::trash_models::TrashKind;
+use crate::models::v_models::{AdminInfo, Event, ScoreLimits, Team, User, VaderEvent};
+use crate::models::wrapper_models::EventWrapper;
+use crate::services::backup_services::{backup_db, backup_to, list_backups, stage_restore};
+use crate::services::checkin_services::{checkin_secret, checkin_token, verify_checkin_token};
+use crate::services::fts_services::maintain_fts;
+use crate::services::trash_services::{list_trash, purge_trash, restore_trashed};
+use crate::services::validation_services::validation_message;
+
+type CliRes = Result<CliResponse, String>;
+
+// returns false if the command failed , used as exit code in main : )
+pub async fn run_cli(
+    command: VaderCommand,
+    json: bool,
+    config: &VaderConfig,
+    db_pool: &SqlitePool,
+) -> bool {
+    let res = match command {
+        VaderCommand::Event(cmd) => event_cmd(cmd, db_pool).await,
+        VaderCommand::Team(cmd) => team_cmd(cmd, db_pool).await,
+        VaderCommand::User(cmd) => user_cmd(cmd, db_pool).await,
+        VaderCommand::Score(cmd) => score_cmd(cmd, db_pool).await,
+        VaderCommand::Import(args) => import_cmd(args, db_pool).await,
+        VaderCommand::Export(args) => export_cmd(args, db_pool).await,
+        VaderCommand::Admin(cmd) => admin_cmd(cmd, db_pool).await,
+        VaderCommand::Db(cmd) => db_cmd(cmd, config, db_pool).await,
+        VaderCommand::Trash(cmd) => trash_cmd(cmd, config, db_pool).await,
+        VaderCommand::Registration(cmd) => registration_cmd(cmd, db_pool).await,
+        VaderCommand::Checkin(cmd) => checkin_cmd(cmd, config, db_pool).await,
+        VaderCommand::Serve | VaderCommand::Migrate => Ok(CliResponse::ok("Nothing to do")),
+    };
+    let res = res.unwrap_or_else(CliResponse::error);
+    let ok = matches!(res.status, CliStatus::Ok);
+    if json {
+        match serde_json::to_string_pretty(&res) {
+            Ok(res_str) => println!("{}", res_str),
+            Err(e) => eprintln!("Unable to serialize response : {}", e),
+        }
+    } else if ok {
+        println!("{}", res.message);
+    } else {
+        eprintln!("{}", res.message);
+    }
+    ok
+}
+
+async fn current_event(db_pool: &SqlitePool) -> Result<EventWrapper<'static>, String> {
+    match EventWrapper::get_current(db_pool).await {
+        Ok(Some(event)) => Ok(event),
+        Ok(None) => Err("No event added.Add event to continue".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn read_json<T>(file: &Path) -> Result<T, String>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let content = fs::read_to_string(file)
+        .map_err(|e| format!("Unable to read {} : {}", file.display(), e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Unable to parse {} : {}", file.display(), e))
+}
+
+// waitlisted users that took the freed place
+fn promoted_msg(promoted: &[Uuid]) -> String {
+    match promoted {
+        [] => String::new(),
+        ids => format!(
+            " , promoted from waitlist : {}",
+            ids.iter()
+                .map(Uuid::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+async fn event_cmd(cmd: EventCmd, db_pool: &SqlitePool) -> CliRes {
+    match cmd {
+        EventCmd::Add {
+            name,
+            logo,
+            team_size,
+            min_score,
+            max_score,
+            max_delta,
+            exclude_unchecked,
+        } => {
+            let event_type = match team_size {
+                Some(team_size) => EventType::TeamEvent { team_size },
+                None => EventType::UserEvent,
+            };
+            let score_limits = ScoreLimits {
+                min_score,
+                max_score,
+                max_delta,
+            };
+            let event_req = EventReq::new(
+                name.into(),
+                logo.map(Into::into),
+                event_type,
+                score_limits,
+                exclude_unchecked,
+            );
+            validation_message(&event_req)?;
+            if EventWrapper::get_current(db_pool)
+                .await
+                .map_err(|e| e.to_string())?
+                .is_some()
+            {
+                return Err(
+                    "Another event already Added . Wait till the current Event ends".into(),
+                );
+            }
+            let event_id = match event_req.event_type {
+                EventType::TeamEvent { team_size: _ } => {
+                    let event = Into::<Result<Event<Team>, VaderError>>::into(event_req)
+                        .map_err(|e| e.to_string())?;
+                    event.add_event(db_pool).await.map_err(|e| e.to_string())?;
+                    event.id
+                }
+                EventType::UserEvent => {
+                    let event = Into::<Result<Event<User>, VaderError>>::into(event_req)
+                        .map_err(|e| e.to_string())?;
+                    event.add_event(db_pool).await.map_err(|e| e.to_string())?;
+                    event.id
+                }
+            };
+            log::info!("Successfully added Event [id : {}]", event_id);
+            CliResponse::ok(format!("Successfully added event : {}", event_id)).with_data(&event_id)
+        }
+        EventCmd::Start => {
+            let mut event = current_event(db_pool).await?;
+            //reset score before starting event
+            match event.reset_score(db_pool).await {
+                Ok(_) | Err(VaderError::EventActive(_)) => {}
+                Err(e) => return Err(format!("Error resetting score to start event.\n{}", e)),
+            }
+            let unchecked = event
+                .unchecked(db_pool)
+                .await
+                .map_err(|e| format!("Error reading participants not checked in.\n{}", e))?;
+            event
+                .start_event(db_pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            let event_id = event.get_id();
+            let unchecked = match unchecked.len() {
+                0 => String::new(),
+                n => format!(" , {} not checked in hidden from the VaderBoard", n),
+            };
+            CliResponse::ok(format!(
+                "Event id : [{}] started successfully{} , restart a running server to pick it up",
+                event_id, unchecked
+            ))
+            .with_data(&event_id)
+        }
+        EventCmd::Stop => {
+            let mut event = current_event(db_pool).await?;
+            event.end_event(db_pool).await.map_err(|e| e.to_string())?;
+            let event_id = event.get_id();
+            CliResponse::ok(format!(
+                "Event id : [{}] stopped successfully , restart a running server to pick it up",
+                event_id
+            ))
+            .with_data(&event_id)
+        }
+        EventCmd::Current => {
+            let event = current_event(db_pool).await?;
+            let res = event.get_event();
+            CliResponse::ok(format!("{}  {}  {}", res.id, res.name, res.state.as_str()))
+                .with_data(&res)
+        }
+        EventCmd::List => {
+            let events = EventInfo::get_all_event_info(db_pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            let lines = events
+                .iter()
+                .map(|e| match e.event_type {
+                    EventType::TeamEvent { team_size } => {
+                        format!("{}  {}  team_event({})", e.id, e.name, team_size)
+                    }
+                    EventType::UserEvent => format!("{}  {}  user_event", e.id, e.name),
+                })
+                .collect::<Vec<String>>();
+            CliResponse::ok(lines.join("\n")).with_data(&events)
+        }
+        EventCmd::Delete { id } => {
+            let current = EventWrapper::get_current(db_pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            if current.map(|e| e.get_id()).eq(&Some(id)) {
+                return Err(VaderError::EventActive(
+                    "Unable to remove Event i.e currently Added/Active.Stop the event to remove",
+                )
+                .to_string());
+            }
+            let event = EventInfo::get_event_info(&id, db_pool)
+                .await
+                .map_err(|e| format!("unable to get event info\n.{}", e))?;
+            match event.event_type {
+                EventType::TeamEvent { team_size: _ } => {
+                    Event::<Team>::delete_event(&id, db_pool).await
+                }
+                EventType::UserEvent => Event::<User>::delete_event(&id, db_pool).await,
+            }
+            .map_err(|e| format!("Error Deleting event : {}.\n{}", id, e))?;
+            CliResponse::ok(format!("Successfully deleted event : {}", id)).with_data(&id)
+        }
+        EventCmd::Restore { id } => {
+            restore_trashed(TrashKind::Event, &id, db_pool)
+                .await
+                .map_err(|e| format!("Error restoring event : {}.\n{}", id, e))?;
+            CliResponse::ok(format!("Successfully restored event : {}", id)).with_data(&id)
+        }
+    }
+}
+
+async fn team_cmd(cmd: TeamCmd, db_pool: &SqlitePool) -> CliRes {
+    match cmd {
+        TeamCmd::Add {
+            name,
+            logo,
+            members,
+        } => {
+            let tm = TeamWithMembers {
+                team_info: ContestantInfo::new(name.into(), logo.map(Into::into)),
+                members: members
+                    .into_iter()
+                    .map(|m| ContestantInfo::new(m.into(), None))
+                    .collect(),
+            };
+            validation_message(&tm)?;
+            let event = current_event(db_pool).await?;
+            let team = Into::<Team>::into(tm.team_info);
+            let team_id = team.id;
+            if tm.members.is_empty() {
+                event
+                    .add_team(team, db_pool)
+                    .await
+                    .map_err(|e| e.to_string())?;
+            } else {
+                let members: Vec<User> = tm.members.into_iter().map(Into::<User>::into).collect();
+                event
+                    .add_team_with_members(&team, &members, db_pool)
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+            CliResponse::ok(format!("Team added successfully : {}", team_id)).with_data(&team_id)
+        }
+        TeamCmd::AddBulk { file } => {
+            let teams: Vec<TeamWithMembers> = read_json(&file)?;
+            for (i, tm) in teams.iter().enumerate() {
+                validation_message(tm)
+                    .map_err(|e| format!("Invalid team at index {}.\n{}", i, e))?;
+            }
+            let event = current_event(db_pool).await?;
+            let mut team_ids: Vec<Uuid> = Vec::with_capacity(teams.len());
+            for tm in teams {
+                let team = Into::<Team>::into(tm.team_info);
+                let members: Vec<User> = tm.members.into_iter().map(Into::<User>::into).collect();
+                event
+                    .add_team_with_members(&team, &members, db_pool)
+                    .await
+                    .map_err(|e| {
+                        format!(
+                            "Error adding Team {} , added {} teams before it.\n{}",
+                            team.name,
+                            team_ids.len(),
+                            e
+                        )
+                    })?;
+                team_ids.push(team.id);
+            }
+            CliResponse::ok(format!("{} Teams added successfully", team_ids.len()))
+                .with_data(&team_ids)
+        }
+        TeamCmd::List => {
+            let teams = TeamInfo::get_all_team_info(db_pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            let lines = teams
+                .iter()
+                .map(|t| format!("{}  {}  {}", t.id, t.name, t.score))
+                .collect::<Vec<String>>();
+            CliResponse::ok(lines.join("\n")).with_data(&teams)
+        }
+        TeamCmd::Edit { id, name, logo } => {
+            let patch = PlayerPatch {
+                name: name.map(Into::into),
+                logo: logo.map(Into::into),
+            };
+            validation_message(&patch)?;
+            Team::update_team(&id, &patch, db_pool)
+                .await
+                .map_err(|e| format!("Error updating team : {}.\n{}", id, e))?;
+            CliResponse::ok(format!("Successfully updated team : {}", id)).with_data(&id)
+        }
+        TeamCmd::RemoveMember { id, user } => {
+            let promoted = Team::remove_member(&id, &user, db_pool)
+                .await
+                .map_err(|e| {
+                    format!("Error removing user : {} from team : {}.\n{}", user, id, e)
+                })?;
+            CliResponse::ok(format!(
+                "Removed user : {} from team : {}{}",
+                user,
+                id,
+                promoted_msg(&promoted)
+            ))
+            .with_data(&user)
+        }
+        TeamCmd::Join { id, user, waitlist } => {
+            let res = Team::join(&id, &user, waitlist, db_pool)
+                .await
+                .map_err(|e| format!("Error adding user : {} to team : {}.\n{}", user, id, e))?;
+            let msg = match res {
+                JoinRes::Member => format!("Added user : {} to team : {}", user, id),
+                JoinRes::Waitlisted { position } => format!(
+                    "Team : {} is full , user : {} is #{} on its waitlist",
+                    id, user, position
+                ),
+            };
+            CliResponse::ok(msg).with_data(&res)
+        }
+        TeamCmd::Waitlist { id } => {
+            let waitlist = Team::get_waitlist(&id, db_pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            let lines = waitlist
+                .iter()
+                .map(|w| {
+                    format!(
+                        "{}  {}  {}  {}",
+                        w.position, w.user_id, w.name, w.created_at
+                    )
+                })
+                .collect::<Vec<String>>();
+            CliResponse::ok(lines.join("\n")).with_data(&waitlist)
+        }
+        TeamCmd::LeaveWaitlist { id, user } => {
+            Team::leave_waitlist(&id, &user, db_pool)
+                .await
+                .map_err(|e| {
+                    format!(
+                        "Error removing user : {} from waitlist of team : {}.\n{}",
+                        user, id, e
+                    )
+                })?;
+            CliResponse::ok(format!(
+                "Removed user : {} from waitlist of team : {}",
+                user, id
+            ))
+            .with_data(&user)
+        }
+        TeamCmd::Captain {
+            id,
+            user: Some(user),
+        } => {
+            Team::set_captain(&id, &user, db_pool)
+                .await
+                .map_err(|e| format!("Error setting captain of team : {}.\n{}", id, e))?;
+            CliResponse::ok(format!("User : {} is now captain of team : {}", user, id))
+                .with_data(&user)
+        }
+        TeamCmd::Captain { id, user: None } => {
+            Team::clear_captain(&id, db_pool)
+                .await
+                .map_err(|e| format!("Error clearing captain of team : {}.\n{}", id, e))?;
+            CliResponse::ok(format!("Cleared captain of team : {}", id)).with_data(&id)
+        }
+        TeamCmd::Delete { id } => {
+            Team::delete_team(&id, db_pool)
+                .await
+                .map_err(|e| format!("Error Deleting team : {}.\n{}", id, e))?;
+            CliResponse::ok(format!("Successfully deleted team : {}", id)).with_data(&id)
+        }
+        TeamCmd::Restore { id } => {
+            restore_trashed(TrashKind::Team, &id, db_pool)
+                .await
+                .map_err(|e| format!("Error restoring team : {}.\n{}", id, e))?;
+            CliResponse::ok(format!("Successfully restored team : {}", id)).with_data(&id)
+        }
+    }
+}
+
+async fn user_cmd(cmd: UserCmd, db_pool: &SqlitePool) -> CliRes {
+    let users: Vec<ContestantInfo> = match cmd {
+        UserCmd::Add { names } => names
+            .into_iter()
+            .map(|name| ContestantInfo::new(name.into(), None))
+            .collect(),
+        UserCmd::AddBulk { file } => read_json(&file)?,
+        UserCmd::List => {
+            let users = User::get_all_user(db_pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            let lines = users
+                .iter()
+                .map(|u| format!("{}  {}  {}", u.id, u.name, u.score))
+                .collect::<Vec<String>>();
+            return CliResponse::ok(lines.join("\n")).with_data(&users);
+        }
+        UserCmd::Edit { id, name, logo } => {
+            let patch = PlayerPatch {
+                name: name.map(Into::into),
+                logo: logo.map(Into::into),
+            };
+            validation_message(&patch)?;
+            User::update_user(&id, &patch, db_pool)
+                .await
+                .map_err(|e| format!("Error updating user : {}.\n{}", id, e))?;
+            return CliResponse::ok(format!("Successfully updated user : {}", id)).with_data(&id);
+        }
+        UserCmd::Move { id, team } => {
+            let promoted = User::move_to_team(&id, &team, db_pool)
+                .await
+                .map_err(|e| format!("Error moving user : {} to team : {}.\n{}", id, team, e))?;
+            return CliResponse::ok(format!(
+                "Moved user : {} to team : {}{}",
+                id,
+                team,
+                promoted_msg(&promoted)
+            ))
+            .with_data(&id);
+        }
+        UserCmd::Delete { id } => {
+            User::delete_user(&id, db_pool)
+                .await
+                .map_err(|e| format!("Error Deleting user : {}.\n{}", id, e))?;
+            return CliResponse::ok(format!("Successfully deleted user : {}", id)).with_data(&id);
+        }
+        UserCmd::Restore { id } => {
+            restore_trashed(TrashKind::User, &id, db_pool)
+                .await
+                .map_err(|e| format!("Error restoring user : {}.\n{}", id, e))?;
+            return CliResponse::ok(format!("Successfully restored user : {}", id)).with_data(&id);
+        }
+    };
+    for (i, user) in users.iter().enumerate() {
+        validation_message(user).map_err(|e| format!("Invalid user at index {}.\n{}", i, e))?;
+    }
+    let users: Vec<User> = users.into_iter().map(Into::<User>::into).collect();
+    let event = current_event(db_pool).await?;
+    let mut user_ids: Vec<Uuid> = Vec::with_capacity(users.len());
+    for user in &users {
+        event.add_user(user, db_pool).await.map_err(|e| {
+            format!(
+                "Error adding User {} , added {} users before it.\n{}",
+                user.name,
+                user_ids.len(),
+                e
+            )
+        })?;
+        user_ids.push(user.id);
+    }
+    CliResponse::ok(format!("{} Users added successfully", user_ids.len())).with_data(&user_ids)
+}
+
+async fn score_cmd(cmd: ScoreCmd, db_pool: &SqlitePool) -> CliRes {
+    let event = current_event(db_pool).await?;
+    match cmd {
+        ScoreCmd::Update { id, score } => {
+            validation_message(&ScoreUpdate { id, score })?;
+            event
+                .update_score_by_id(&id, score, db_pool)
+                .await
+                .map_err(|e| format!("Error updating Score : \n{}", e))?;
+            CliResponse::ok("Score Updated").with_data(&id)
+        }
+        ScoreCmd::Reset => {
+            event
+                .reset_score(db_pool)
+                .await
+                .map_err(|e| format!("Error resetting Score : \n{}", e))?;
+            Ok(CliResponse::ok("Score reset successful"))
+        }
+    }
+}
+
+async fn import_cmd(args: ImportArgs, db_pool: &SqlitePool) -> CliRes {
+    let ImportArgs {
+        file,
+        format,
+        dry_run,
+    } = args;
+    let format = format.unwrap_or_else(|| match file.extension() {
+        Some(ext) if ext.eq_ignore_ascii_case("csv") => ImportFormat::Csv,
+        _ => ImportFormat::Json,
+    });
+    let data = fs::read(&file).map_err(|e| format!("Unable to read {} : {}", file.display(), e))?;
+    let event = current_event(db_pool).await?;
+    let mut report = ImportReport {
+        dry_run,
+        ..Default::default()
+    };
+    let rows = format
+        .parse_rows(&data, &mut report)
+        .map_err(|e| e.to_string())?;
+    let report = event
+        .import_rows(rows, report, db_pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let summary = format!(
+        "{} teams , {} users , {} members",
+        report.teams.len(),
+        report.users.len(),
+        report.members
+    );
+    if !report.errors.is_empty() {
+        let errors = report
+            .errors
+            .iter()
+            .map(|e| format!("row {} : {}", e.row, e.error))
+            .collect::<Vec<String>>();
+        return CliResponse::error(format!(
+            "Import rolled back , {} rows failed\n{}",
+            errors.len(),
+            errors.join("\n")
+        ))
+        .with_data(&report);
+    }
+    let msg = if report.dry_run {
+        format!("Dry run ok , would import {}", summary)
+    } else {
+        format!("Imported {}", summary)
+    };
+    CliResponse::ok(msg).with_data(&report)
+}
+
+// full ranking , the leaderboard without the vb_count limit : )
+async fn export_cmd(args: ExportArgs, db_pool: &SqlitePool) -> CliRes {
+    let ExportArgs {
+        event,
+        format,
+        output,
+    } = args;
+    let id = match event {
+        Some(id) => id,
+        None => current_event(db_pool).await?.get_id(),
+    };
+    let export = EventExport::get_export(&id, db_pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let body = export.render(format).map_err(|e| e.to_string())?;
+    match output {
+        Some(path) => {
+            fs::write(&path, body)
+                .map_err(|e| format!("Unable to write {} : {}", path.display(), e))?;
+            CliResponse::ok(format!(
+                "Exported {} entries of {} to {}",
+                export.ranking.len(),
+                export.event.name,
+                path.display()
+            ))
+            .with_data(&export)
+        }
+        None => CliResponse::ok(body).with_data(&export),
+    }
+}
+
+async fn admin_cmd(cmd: AdminCmd, db_pool: &SqlitePool) -> CliRes {
+    match cmd {
+        AdminCmd::Add { username, password } => {
+            let admin = AdminInfo {
+                username: username.clone(),
+                password,
+            };
+            if !admin.add_admin(db_pool).await.map_err(|e| e.to_string())? {
+                return Err(format!("Admin {} already exists", username));
+            }
+            CliResponse::ok(format!("Successfully added admin : {}", username)).with_data(&username)
+        }
+        AdminCmd::Passwd { username, password } => {
+            let admin = AdminInfo {
+                username: username.clone(),
+                password,
+            };
+            admin
+                .update_passwd(db_pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            CliResponse::ok(format!("Password updated for admin : {}", username))
+                .with_data(&username)
+        }
+        AdminCmd::Remove { username } => {
+            AdminInfo::delete_admin(&username, db_pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            CliResponse::ok(format!("Successfully removed admin : {}", username))
+                .with_data(&username)
+        }
+        AdminCmd::List => {
+            let admins = AdminInfo::get_all_admins(db_pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            CliResponse::ok(admins.join("\n")).with_data(&admins)
+        }
+    }
+}
+
+async fn db_cmd(cmd: DbCmd, config: &VaderConfig, db_pool: &SqlitePool) -> CliRes {
+    match cmd {
+        DbCmd::Backup { output } => {
+            let info = match output {
+                Some(path) => backup_to(db_pool, &path).await,
+                None => backup_db(db_pool, &config.backup).await,
+            }
+            .map_err(|e| e.to_string())?;
+            CliResponse::ok(format!(
+                "Backup successful : {} ({} bytes)",
+                info.name, info.size
+            ))
+            .with_data(&info)
+        }
+        DbCmd::List => {
+            let backups = list_backups(&config.backup).map_err(|e| e.to_string())?;
+            let lines = backups
+                .iter()
+                .map(|b| format!("{}  {}  {}", b.name, b.size, b.created_at.to_rfc3339()))
+                .collect::<Vec<String>>();
+            CliResponse::ok(lines.join("\n")).with_data(&backups)
+        }
+        DbCmd::Restore { file } => {
+            let version = stage_restore(config, &file)
+                .await
+                .map_err(|e| e.to_string())?;
+            CliResponse::ok(format!(
+                "Restore of {} staged [schema version : {}] , stop the server and run `serve` or `migrate` to apply",
+                file.display(),
+                version
+            ))
+            .with_data(&version)
+        }
+        DbCmd::Fts { op } => {
+            let reports = maintain_fts(op, db_pool).await.map_err(|e| e.to_string())?;
+            let lines = reports
+                .iter()
+                .map(|r| {
+                    let status = r.details.as_deref().unwrap_or("ok");
+                    format!("{}  {}/{}  {}", r.table, r.indexed, r.rows, status)
+                })
+                .collect::<Vec<String>>();
+            let res = match reports.iter().all(|r| r.ok) {
+                true => CliResponse::ok(lines.join("\n")),
+                false => CliResponse::error(format!(
+                    "Search index out of sync , run `db fts rebuild`\n{}",
+                    lines.join("\n")
+                )),
+            };
+            res.with_data(&reports)
+        }
+    }
+}
+
+async fn trash_cmd(cmd: TrashCmd, config: &VaderConfig, db_pool: &SqlitePool) -> CliRes {
+    match cmd {
+        TrashCmd::List { kind } => {
+            let items = list_trash(kind, config.trash.retention_days, db_pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            let lines = items
+                .iter()
+                .map(|i| {
+                    format!(
+                        "{}  {}  {}  deleted {}  purge after {}",
+                        i.kind.as_str(),
+                        i.id,
+                        i.name,
+                        i.deleted_at.to_rfc3339(),
+                        i.purge_after.to_rfc3339()
+                    )
+                })
+                .collect::<Vec<String>>();
+            CliResponse::ok(lines.join("\n")).with_data(&items)
+        }
+        TrashCmd::Purge { older_than_days } => {
+            let days = older_than_days.unwrap_or(config.trash.retention_days);
+            let report = purge_trash(days, db_pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            CliResponse::ok(format!(
+                "Purged {} events , {} teams and {} users deleted before {}",
+                report.events,
+                report.teams,
+                report.users,
+                report.cutoff.to_rfc3339()
+            ))
+            .with_data(&report)
+        }
+    }
+}
+
+async fn registration_cmd(cmd: RegistrationCmd, db_pool: &SqlitePool) -> CliRes {
+    let event = current_event(db_pool).await?;
+    let (id, status) = match cmd {
+        RegistrationCmd::Open | RegistrationCmd::Close => {
+            let open = matches!(cmd, RegistrationCmd::Open);
+            event
+                .set_registration(open, db_pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            let state = event
+                .registration_state(db_pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            let msg = match (open, state.open) {
+                (true, true) => "Registration open",
+                (true, false) => "Registration set open , but the event already started",
+                _ => "Registration closed",
+            };
+            return CliResponse::ok(msg).with_data(&state);
+        }
+        RegistrationCmd::List { status } => {
+            let registrations = event
+                .get_registrations(status, db_pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            let lines = registrations
+                .iter()
+                .map(|r| {
+                    format!(
+                        "{}  {}  {}  {} members  {}  {}",
+                        r.id,
+                        r.team_name,
+                        r.status.as_str(),
+                        r.members,
+                        r.invite_code,
+                        r.created_at.to_rfc3339()
+                    )
+                })
+                .collect::<Vec<String>>();
+            return CliResponse::ok(lines.join("\n")).with_data(&registrations);
+        }
+        RegistrationCmd::Approve { id } => (id, RegistrationStatus::Approved),
+        RegistrationCmd::Reject { id } => (id, RegistrationStatus::Rejected),
+    };
+    let team_id = event
+        .decide_registration(&id, status, db_pool)
+        .await
+        .map_err(|e| format!("Error deciding registration : {}.\n{}", id, e))?;
+    CliResponse::ok(format!(
+        "Registration : {} {} , team : {}",
+        id,
+        status.as_str(),
+        team_id
+    ))
+    .with_data(&team_id)
+}
+
+async fn checkin_cmd(cmd: CheckinCmd, config: &VaderConfig, db_pool: &SqlitePool) -> CliRes {
+    let event = current_event(db_pool).await?;
+    let ids = match cmd {
+        CheckinCmd::List => {
+            let check_ins = event
+                .get_check_ins(db_pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            let lines = check_ins
+                .iter()
+                .map(|c| {
+                    format!(
+                        "{}  {}  {}",
+                        c.id,
+                        c.name,
+                        c.checked_in_at
+                            .map(|t| t.to_rfc3339())
+                            .unwrap_or_else(|| "-".to_string())
+                    )
+                })
+                .collect::<Vec<String>>();
+            return CliResponse::ok(lines.join("\n")).with_data(&check_ins);
+        }
+        CheckinCmd::Remove { id } => {
+            event
+                .undo_check_in(&id, db_pool)
+                .await
+                .map_err(|e| format!("Error undoing check-in : {}.\n{}", id, e))?;
+            return CliResponse::ok(format!("Check-in undone : {}", id)).with_data(&id);
+        }
+        CheckinCmd::Token { id } => {
+            let secret = checkin_secret(&config.checkin).map_err(|e| e.to_string())?;
+            event
+                .checked_in_at(&id, db_pool)
+                .await
+                .map_err(|e| format!("Error making token : {}.\n{}", id, e))?;
+            let token = checkin_token(secret, &event.get_id(), &id);
+            return CliResponse::ok(token.clone()).with_data(&CheckInToken { id, token });
+        }
+        CheckinCmd::Add { ids } => ids,
+        CheckinCmd::Scan { token } => {
+            let secret = checkin_secret(&config.checkin).map_err(|e| e.to_string())?;
+            vec![verify_checkin_token(secret, &event.get_id(), &token).map_err(|e| e.to_string())?]
+        }
+    };
+    let report = event
+        .check_in(&ids, db_pool)
+        .await
+        .map_err(|e| format!("Error checking in.\n{}", e))?;
+    CliResponse::ok(format!(
+        "Checked in : {} , already checked in : {}",
+        report.checked_in, report.already_checked_in
+    ))
+    .with_data(&report)
+}
@@ -1,362 +1,615 @@
 use actix::Addr;
 use actix_session::Session;
-use actix_web::{post, web, Either, HttpResponse, Responder};
+use actix_web::http::header::CONTENT_TYPE;
+use actix_web::{delete, patch, post, web, HttpRequest, HttpResponse};
 use log::{error, info};
 use sqlx::SqlitePool;
+use uuid::Uuid;
+use validator::Validate;
 
+use crate::models::audit_models::Audit;
+use crate::models::backup_models::RestoreReq;
+use crate::models::checkin_models::{CheckInReq, TokenCheckIn};
 use crate::models::command_models::{
-    CommandResponse, ContestantInfo, EventReq, MemberInfo, ScoreUpdate, TeamWithMembers,
+    CommandResponse, ContestantInfo, EventReq, MemberInfo, MoveMember, PlayerPatch, ScoreUpdate,
+    TeamWithMembers,
 };
+use crate::models::config_models::VaderConfig;
 use crate::models::error_models::VaderError;
+use crate::models::fts_models::FtsOp;
+use crate::models::import_models::{ImportFormat, ImportQuery, ImportReport};
+use crate::models::membership_models::{CaptainReq, JoinReq, JoinRes};
 use crate::models::query_models::{
-    CurFtsServer, CurFtsStop, EventInfo, EventType, IdQuery, TransferType, VboardGet, VboardSrv,
+    CurFtsServer, CurFtsStop, EventInfo, EventType, IdQuery, Lifecycle, TransferType, VboardGet,
+    VboardLifecycle, VboardSrv,
 };
+use crate::models::registration_models::{
+    JoinRegistration, RegistrationGate, RegistrationStatus, RegistrationToggle,
+};
+use crate::models::trash_models::{PurgeQuery, TrashKind};
 use crate::models::v_models::{AdminInfo, AppState, Event, Team, User, VaderEvent};
+use crate::models::validation_models::FieldError;
 use crate::models::wrapper_models::{EventStateWrapper, EventWrapper};
+use crate::services::backup_services::{backup_db, find_backup, stage_restore};
+use crate::services::checkin_services::{checkin_secret, verify_checkin_token};
+use crate::services::fts_services::maintain_fts;
+use crate::services::metrics_services::metrics;
+use crate::services::trash_services::{purge_trash, restore_trashed};
 
 #[post("/event/add")]
 pub async fn add_event<'a>(
     event_data: web::Json<EventReq<'a>>,
+    audit: Audit,
     app_state: web::Data<AppState>,
     db_pool: web::Data<SqlitePool>,
-) -> Either<impl Responder, impl Responder>
+) -> Result<HttpResponse, VaderError<'static>>
 where
     'a: 'static,
 {
     let event_data = event_data.into_inner();
+    audit.action("event.add");
+    event_data.validate()?;
     match event_data.event_type {
         EventType::TeamEvent { team_size: _ } => {
-            Either::Left(add_team_event(event_data, app_state, db_pool).await)
+            add_team_event(event_data, audit, app_state, db_pool).await
         }
-        EventType::UserEvent => Either::Right(add_user_event(event_data, app_state, db_pool).await),
+        EventType::UserEvent => add_user_event(event_data, audit, app_state, db_pool).await,
     }
 }
 
 pub async fn add_team_event<'a>(
     event_info: EventReq<'a>,
+    audit: Audit,
     app_state: web::Data<AppState>,
     db_pool: web::Data<SqlitePool>,
-) -> impl Responder
+) -> Result<HttpResponse, VaderError<'static>>
 where
     'a: 'static,
 {
     let mut event_state = app_state.current_event.lock().await;
     if event_state.is_some() {
         error!("Request delined.Another Event already added.");
-        return HttpResponse::BadRequest()
-            .body("Another event already Added . Wait till the current Event ends");
-    }
-    match Into::<Result<Event<Team>, VaderError>>::into(event_info) {
-        Ok(event) => {
-            let event_id = event.id;
-            match event.add_event(&db_pool).await {
-                Ok(_) => {
-                    info!("Successfully added team Event [id : {}]", event_id);
-                    *event_state = Some(EventWrapper::TeamEvent(EventStateWrapper::New(event)));
-                    HttpResponse::Ok().json(web::Json(CommandResponse::new(
-                        "Successfully added team event",
-                        event_id,
-                    )))
-                }
-                Err(err) => {
-                    error!("Error adding Team event : {}", err.to_string());
-                    HttpResponse::InternalServerError().body(err.to_string())
-                }
-            }
-        }
-        Err(e) => {
-            error!("Error adding Team event : {}", e.to_string());
-            HttpResponse::InternalServerError().body(e.to_string())
-        }
+        return Err(another_event());
     }
+    let event = Into::<Result<Event<Team>, VaderError>>::into(event_info)?;
+    let event_id = event.id;
+    audit
+        .event(event_id)
+        .summary("name", &event.name)
+        .summary("team_size", event.team_size);
+    event.add_event(&db_pool).await?;
+    info!(event_id:% = event_id; "Successfully added team event");
+    *event_state = Some(EventWrapper::TeamEvent(EventStateWrapper::New(event)));
+    Ok(HttpResponse::Ok().json(web::Json(CommandResponse::new(
+        "Successfully added team event",
+        event_id,
+    ))))
 }
 pub async fn add_user_event<'a>(
     event_info: EventReq<'a>,
+    audit: Audit,
     app_state: web::Data<AppState>,
     db_pool: web::Data<SqlitePool>,
-) -> impl Responder
+) -> Result<HttpResponse, VaderError<'static>>
 where
     'a: 'static,
 {
     let mut event_state = app_state.current_event.lock().await;
     if event_state.is_some() {
         error!("Request delined.Another Event already added.");
-        return HttpResponse::BadRequest()
-            .body("Another event already Added . Wait till the current Event ends");
-    }
-    match Into::<Result<Event<User>, VaderError>>::into(event_info) {
-        Ok(event) => {
-            let event_id = event.id;
-            match event.add_event(&db_pool).await {
-                Ok(_) => {
-                    info!("Successfully added user Event [id : {}]", event_id);
-                    *event_state = Some(EventWrapper::UserEvent(EventStateWrapper::New(event)));
-                    HttpResponse::Ok().json(web::Json(CommandResponse::new(
-                        "Successfully added user Event",
-                        event_id,
-                    )))
-                }
-                Err(err) => {
-                    error!("Error adding User event : {}", err.to_string());
-                    HttpResponse::InternalServerError().body(err.to_string())
-                }
-            }
-        }
-        Err(err) => {
-            error!("Error adding User event : {}", err.to_string());
-            HttpResponse::InternalServerError().body(err.to_string())
-        }
+        return Err(another_event());
     }
+    let event = Into::<Result<Event<User>, VaderError>>::into(event_info)?;
+    let event_id = event.id;
+    audit
+        .event(event_id)
+        .summary("name", &event.name)
+        .summary("team_size", event.team_size);
+    event.add_event(&db_pool).await?;
+    info!(event_id:% = event_id; "Successfully added user event");
+    *event_state = Some(EventWrapper::UserEvent(EventStateWrapper::New(event)));
+    Ok(HttpResponse::Ok().json(web::Json(CommandResponse::new(
+        "Successfully added user Event",
+        event_id,
+    ))))
 }
 
 #[post("/event/start")]
 pub async fn start_event(
+    audit: Audit,
     app_state: web::Data<AppState>,
     vb_srv: web::Data<Addr<VboardSrv>>,
     db_pool: web::Data<SqlitePool>,
-) -> impl Responder {
+) -> Result<HttpResponse, VaderError<'static>> {
+    audit.action("event.start");
     let mut event_state = app_state.current_event.lock().await;
-    if event_state.is_none() {
-        error!("Request delined.No event added");
-        HttpResponse::BadRequest().body("No event added.Add event to start event")
-    } else {
-        //reset score before starting event
-        let reset_res = event_state.as_ref().unwrap().reset_score(&db_pool).await;
-        if let Err(e) = reset_res {
-            match e {
-                VaderError::EventActive(_) => {}
-                _ => {
-                    error!("Error reseting score to start event");
-                    return HttpResponse::BadRequest().body(format!(
-                        "Error resetting score to start event.\n{}",
-                        e.to_string()
-                    ));
-                }
-            }
-        };
-        let res = event_state.as_mut().unwrap().start_event();
-        match res {
-            Ok(_) => {
-                vb_srv.do_send(VboardGet(TransferType::Broadcast));
-                let body = format!(
-                    "Event id : [{}] started successfully",
-                    event_state.as_ref().unwrap().get_id()
-                );
-                info!("{}", body);
-                HttpResponse::Ok().body(body)
-            }
-            Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+    let event = event_state.as_mut().ok_or_else(no_event)?;
+    audit.event(event.get_id());
+    //reset score before starting event
+    match event.reset_score(&db_pool).await {
+        Ok(_) | Err(VaderError::EventActive(_)) => {}
+        Err(e) => {
+            error!(event_id:% = event.get_id(), error:% = e; "Error reseting score to start event");
+            return Err(e);
         }
     }
+    // teams/users not checked in are hidden from the VaderBoard till they check in : )
+    let unchecked = event.unchecked(&db_pool).await?;
+    if !unchecked.is_empty() {
+        audit
+            .targets(&unchecked)
+            .summary("unchecked", unchecked.len());
+        info!(event_id:% = event.get_id(), unchecked = unchecked.len(); "Participants not checked in hidden from the VaderBoard");
+    }
+    event.start_event(&db_pool).await?;
+    vb_srv.do_send(VboardLifecycle(Lifecycle::Started, event.get_id()));
+    vb_srv.do_send(VboardGet(TransferType::Broadcast));
+    let body = format!("Event id : [{}] started successfully", event.get_id());
+    info!(event_id:% = event.get_id(); "Event started");
+    Ok(HttpResponse::Ok().body(body))
 }
 #[post("/event/stop")]
 pub async fn end_event(
+    audit: Audit,
     app_state: web::Data<AppState>,
     srv_addr: web::Data<Addr<CurFtsServer<'static>>>,
-) -> impl Responder {
+    vb_srv: web::Data<Addr<VboardSrv>>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    audit.action("event.stop");
     let mut event_state = app_state.current_event.lock().await;
-    if event_state.is_none() {
-        error!("Request delined.No event added");
-        HttpResponse::BadRequest().body("No event added.Add event to start event")
-    } else {
-        let res = event_state.as_mut().unwrap().end_event();
-        match res {
-            Ok(_) => {
-                let body = format!(
-                    "Event id : [{}] stopped successfully",
-                    event_state.as_ref().unwrap().get_id()
-                );
-                info!("{}", body);
-                *event_state = None;
-                srv_addr.do_send(CurFtsStop);
-                HttpResponse::Ok().body(body)
-            }
-            Err(e) => HttpResponse::BadRequest().body(e.to_string()),
-        }
-    }
+    let event = event_state.as_mut().ok_or_else(no_event)?;
+    audit.event(event.get_id());
+    event.end_event(&db_pool).await?;
+    let body = format!("Event id : [{}] stopped successfully", event.get_id());
+    info!(event_id:% = event.get_id(); "Event stopped");
+    vb_srv.do_send(VboardLifecycle(Lifecycle::Ended, event.get_id()));
+    *event_state = None;
+    srv_addr.do_send(CurFtsStop);
+    Ok(HttpResponse::Ok().body(body))
 }
 
 #[post("/score/update")]
 pub async fn update_score(
     score_req: web::Json<ScoreUpdate>,
+    audit: Audit,
     app_state: web::Data<AppState>,
     vb_srv: web::Data<Addr<VboardSrv>>,
     db_pool: web::Data<SqlitePool>,
-) -> impl Responder {
+) -> Result<HttpResponse, VaderError<'static>> {
+    audit.action("score.update");
     let event_state = app_state.current_event.lock().await;
-    if event_state.is_none() {
-        error!("Request delined.No event added");
-        HttpResponse::BadRequest().body("No event added.Add event to start event")
-    } else {
-        let sr = score_req.into_inner();
-        let score_res = event_state
-            .as_ref()
-            .unwrap()
-            .update_score_by_id(&sr.id, sr.score, &db_pool)
-            .await;
-        match score_res {
-            Ok(_) => {
-                info!("Score updated successfully.");
-                vb_srv.do_send(VboardGet(TransferType::Broadcast));
-                HttpResponse::Ok().body("Score Updated")
-            }
-            Err(err) => {
-                error!("Error updating score :\n[error] : {}", err);
-                HttpResponse::BadRequest().body(format!("Error updating Score : \n{}", err))
-            }
-        }
-    }
+    let event = event_state.as_ref().ok_or_else(no_event)?;
+    let sr = score_req.into_inner();
+    audit
+        .event(event.get_id())
+        .target(sr.id)
+        .summary("score", sr.score);
+    sr.validate()?;
+    event.update_score_by_id(&sr.id, sr.score, &db_pool).await?;
+    metrics()
+        .score_updates
+        .with_label_values(&[&event.get_id().to_string()])
+        .inc();
+    info!(
+        event_id:% = event.get_id(),
+        participant_id:% = sr.id,
+        score = sr.score;
+        "Score updated successfully"
+    );
+    vb_srv.do_send(VboardGet(TransferType::Broadcast));
+    Ok(HttpResponse::Ok().body("Score Updated"))
 }
 #[post("/score/reset")]
 pub async fn reset_score(
+    audit: Audit,
     app_state: web::Data<AppState>,
+    vb_srv: web::Data<Addr<VboardSrv>>,
     db_pool: web::Data<SqlitePool>,
-) -> impl Responder {
+) -> Result<HttpResponse, VaderError<'static>> {
+    audit.action("score.reset");
     let event_state = app_state.current_event.lock().await;
-    if event_state.is_none() {
-        error!("Request delined.No event added");
-        HttpResponse::BadRequest().body("No event added.Add event to start event")
-    } else {
-        let res = event_state.as_ref().unwrap().reset_score(&db_pool).await;
-        match res {
-            Ok(_) => {
-                info!("Score Reset successful");
-                HttpResponse::Ok().body("Score reset successful")
-            }
-            Err(err) => {
-                error!("Error resetting score :\n[error] : {}", err);
-                HttpResponse::BadRequest().body(format!("Error resetting Score : \n{}", err))
-            }
-        }
-    }
+    let event = event_state.as_ref().ok_or_else(no_event)?;
+    audit.event(event.get_id());
+    event.reset_score(&db_pool).await?;
+    info!(event_id:% = event.get_id(); "Score reset successful");
+    vb_srv.do_send(VboardGet(TransferType::Broadcast));
+    Ok(HttpResponse::Ok().body("Score reset successful"))
 }
 #[post("/event/team/add")]
 pub async fn add_team(
     c_info: web::Json<ContestantInfo<'_>>,
+    audit: Audit,
     app_state: web::Data<AppState>,
     db_pool: web::Data<SqlitePool>,
-) -> impl Responder {
+) -> Result<HttpResponse, VaderError<'static>> {
+    audit.action("team.add");
     let event_state = app_state.current_event.lock().await;
-    if event_state.is_none() {
-        error!("Request delined.No event added");
-        HttpResponse::BadRequest().body("No event added.Add event to start event")
-    } else {
-        let team = Into::<Team>::into(c_info.into_inner());
-        let team_id = team.id;
-        let res = event_state.as_ref().unwrap().add_team(team, &db_pool).await;
-        match res {
-            Ok(_) => {
-                info!("Team  added successfully : {}", team_id);
-                HttpResponse::Ok().json(web::Json(CommandResponse::new(
-                    "Team added successfully",
-                    team_id,
-                )))
-            }
-            Err(err) => {
-                error!("Error adding Team :\n[error] : {}", err);
-                HttpResponse::BadRequest().body(err.to_string())
-            }
-        }
-    }
+    let event = event_state.as_ref().ok_or_else(no_event)?;
+    let c_info = c_info.into_inner();
+    c_info.validate()?;
+    let team = Into::<Team>::into(c_info);
+    let team_id = team.id;
+    audit
+        .event(event.get_id())
+        .target(team_id)
+        .summary("name", &team.name);
+    event.add_team(team, &db_pool).await?;
+    info!(event_id:% = event.get_id(), participant_id:% = team_id; "Team added successfully");
+    Ok(HttpResponse::Ok().json(web::Json(CommandResponse::new(
+        "Team added successfully",
+        team_id,
+    ))))
 }
 
 #[post("/event/team/add/with_members")]
 pub async fn add_team_with_members(
     tm_info: web::Json<TeamWithMembers<'_>>,
+    audit: Audit,
     app_state: web::Data<AppState>,
     db_pool: web::Data<SqlitePool>,
-) -> impl Responder {
+) -> Result<HttpResponse, VaderError<'static>> {
+    audit.action("team.add");
     let event_state = app_state.current_event.lock().await;
-    if event_state.is_none() {
-        error!("Request delined.No event added");
-        HttpResponse::BadRequest().body("No event added.Add event to start event")
-    } else {
-        let tm = tm_info.into_inner();
-        let team = Into::<Team>::into(tm.team_info);
-        let members: Vec<User> = tm.members.into_iter().map(Into::<User>::into).collect();
-        let team_id = team.id;
-        let res = event_state
-            .as_ref()
-            .unwrap()
-            .add_team_with_members(&team, &members, &db_pool)
-            .await;
-        match res {
-            Ok(_) => {
-                info!("Team  added successfully : {}", team_id);
-                HttpResponse::Ok().json(web::Json(CommandResponse::new(
-                    "Team added successfully",
-                    team_id,
-                )))
-            }
-            Err(err) => {
-                error!("Error adding Team :\n[error] : {}", err);
-                HttpResponse::BadRequest().body(err.to_string())
-            }
-        }
-    }
+    let event = event_state.as_ref().ok_or_else(no_event)?;
+    let tm = tm_info.into_inner();
+    tm.validate()?;
+    let team = Into::<Team>::into(tm.team_info);
+    let members: Vec<User> = tm.members.into_iter().map(Into::<User>::into).collect();
+    let team_id = team.id;
+    audit
+        .event(event.get_id())
+        .target(team_id)
+        .targets(&members.iter().map(|m| m.id).collect::<Vec<Uuid>>())
+        .summary("name", &team.name)
+        .summary(
+            "members",
+            members.iter().map(|m| &m.name).collect::<Vec<_>>(),
+        );
+    event
+        .add_team_with_members(&team, &members, &db_pool)
+        .await?;
+    info!(
+        event_id:% = event.get_id(),
+        participant_id:% = team_id,
+        members = members.len();
+        "Team added successfully"
+    );
+    Ok(HttpResponse::Ok().json(web::Json(CommandResponse::new(
+        "Team added successfully",
+        team_id,
+    ))))
 }
 
 #[post("/event/user/add")]
 pub async fn add_user(
     c_info: web::Json<ContestantInfo<'_>>,
+    audit: Audit,
     app_state: web::Data<AppState>,
     db_pool: web::Data<SqlitePool>,
-) -> impl Responder {
+) -> Result<HttpResponse, VaderError<'static>> {
+    audit.action("user.add");
     let event_state = app_state.current_event.lock().await;
-    if event_state.is_none() {
-        error!("Request delined.No event added");
-        HttpResponse::BadRequest().body("No event added.Add event to start event")
-    } else {
-        let user = Into::<User>::into(c_info.into_inner());
-        let user_id = user.id;
-        let res = event_state
-            .as_ref()
-            .unwrap()
-            .add_user(&user, &db_pool)
-            .await;
-        match res {
-            Ok(_) => {
-                info!("User  added successfully : {}", user_id);
-                HttpResponse::Ok().json(web::Json(CommandResponse::new(
-                    "User added successfully",
-                    user_id,
-                )))
-            }
-            Err(err) => {
-                error!("Error adding User :\n[error] : {}", err);
-                HttpResponse::BadRequest().body(err.to_string())
-            }
-        }
-    }
+    let event = event_state.as_ref().ok_or_else(no_event)?;
+    let c_info = c_info.into_inner();
+    c_info.validate()?;
+    let user = Into::<User>::into(c_info);
+    let user_id = user.id;
+    audit
+        .event(event.get_id())
+        .target(user_id)
+        .summary("name", &user.name);
+    event.add_user(&user, &db_pool).await?;
+    info!(event_id:% = event.get_id(), participant_id:% = user_id; "User added successfully");
+    Ok(HttpResponse::Ok().json(web::Json(CommandResponse::new(
+        "User added successfully",
+        user_id,
+    ))))
 }
 
 #[post("/event/team/add/members")]
 pub async fn add_team_members(
     m_info: web::Json<MemberInfo>,
+    audit: Audit,
     app_state: web::Data<AppState>,
     db_pool: web::Data<SqlitePool>,
-) -> impl Responder {
+) -> Result<HttpResponse, VaderError<'static>> {
+    audit.action("team.members.add");
     let event_state = app_state.current_event.lock().await;
-    if event_state.is_none() {
-        error!("Request delined.No event added");
-        HttpResponse::BadRequest().body("No event added.Add event to start event")
-    } else {
-        let mi = m_info.into_inner();
-        let res = event_state
-            .as_ref()
-            .unwrap()
-            .add_team_members(&mi, &db_pool)
-            .await;
-        match res {
-            Ok(_) => {
-                info!("Team Members added successfully : {:?}", mi.members);
-                HttpResponse::Ok().body("Team Members added successfully")
-            }
-            Err(err) => {
-                error!("Error adding Team Members:\n[error] : {}", err);
-                HttpResponse::BadRequest().body(format!("Error adding Team Members : {}", err))
-            }
+    let event = event_state.as_ref().ok_or_else(no_event)?;
+    let mi = m_info.into_inner();
+    audit
+        .event(event.get_id())
+        .target(mi.team_id)
+        .targets(&mi.members);
+    mi.validate()?;
+    event.add_team_members(&mi, &db_pool).await?;
+    info!(
+        event_id:% = event.get_id(),
+        participant_id:% = mi.team_id,
+        members = mi.members.len();
+        "Team members added successfully"
+    );
+    Ok(HttpResponse::Ok().body("Team Members added successfully"))
+}
+
+// public , the team waits for an admin to approve it
+#[post("/event/register")]
+pub async fn register_team(
+    req: HttpRequest,
+    tm_info: web::Json<TeamWithMembers<'_>>,
+    audit: Audit,
+    gate: web::Data<RegistrationGate>,
+    app_state: web::Data<AppState>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    audit.action("registration.team");
+    gate.admit(&req)?;
+    let event_state = app_state.current_event.lock().await;
+    let event = event_state.as_ref().ok_or_else(no_event)?;
+    let tm = tm_info.into_inner();
+    tm.validate()?;
+    // the registering teammate is a member too , the rest join with the invite code
+    if tm.members.is_empty() {
+        return Err(VaderError::ValidationError(vec![FieldError {
+            field: "members".to_string(),
+            code: "length".to_string(),
+            message: "must have atleast one member".to_string(),
+        }]));
+    }
+    let team = Into::<Team>::into(tm.team_info);
+    let members: Vec<User> = tm.members.into_iter().map(Into::<User>::into).collect();
+    audit
+        .event(event.get_id())
+        .target(team.id)
+        .targets(&members.iter().map(|m| m.id).collect::<Vec<Uuid>>())
+        .summary("name", &team.name);
+    let res = event.register_team(&team, &members, &db_pool).await?;
+    info!(
+        event_id:% = event.get_id(),
+        participant_id:% = team.id,
+        members = members.len();
+        "Team registered"
+    );
+    Ok(HttpResponse::Ok().json(web::Json(res)))
+}
+
+// public , joins the registered team of the invite code
+#[post("/event/register/join")]
+pub async fn join_registration(
+    req: HttpRequest,
+    join_req: web::Json<JoinRegistration<'_>>,
+    audit: Audit,
+    gate: web::Data<RegistrationGate>,
+    app_state: web::Data<AppState>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    audit.action("registration.join");
+    gate.admit(&req)?;
+    let event_state = app_state.current_event.lock().await;
+    let event = event_state.as_ref().ok_or_else(no_event)?;
+    let join_req = join_req.into_inner();
+    join_req.validate()?;
+    let user = Into::<User>::into(join_req.member);
+    audit
+        .event(event.get_id())
+        .target(user.id)
+        .summary("name", &user.name);
+    let team_id = event
+        .join_registration(&join_req.invite_code, &user, &db_pool)
+        .await?;
+    audit.target(team_id);
+    info!(event_id:% = event.get_id(), participant_id:% = user.id, team_id:% = team_id; "User joined registered team");
+    Ok(HttpResponse::Ok().json(web::Json(CommandResponse::new(
+        "Joined team successfully",
+        team_id,
+    ))))
+}
+
+#[post("/event/registration")]
+pub async fn toggle_registration(
+    toggle: web::Json<RegistrationToggle>,
+    audit: Audit,
+    app_state: web::Data<AppState>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    audit.action("registration.toggle");
+    let event_state = app_state.current_event.lock().await;
+    let event = event_state.as_ref().ok_or_else(no_event)?;
+    audit.event(event.get_id()).summary("open", toggle.open);
+    event.set_registration(toggle.open, &db_pool).await?;
+    info!(event_id:% = event.get_id(), open = toggle.open; "Registration toggled");
+    Ok(HttpResponse::Ok().json(web::Json(event.registration_state(&db_pool).await?)))
+}
+
+#[post("/event/registrations/{id}/approve")]
+pub async fn approve_registration(
+    id: web::Path<Uuid>,
+    audit: Audit,
+    app_state: web::Data<AppState>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    decide_registration(
+        id.into_inner(),
+        RegistrationStatus::Approved,
+        audit,
+        app_state,
+        db_pool,
+    )
+    .await
+}
+
+#[post("/event/registrations/{id}/reject")]
+pub async fn reject_registration(
+    id: web::Path<Uuid>,
+    audit: Audit,
+    app_state: web::Data<AppState>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    decide_registration(
+        id.into_inner(),
+        RegistrationStatus::Rejected,
+        audit,
+        app_state,
+        db_pool,
+    )
+    .await
+}
+
+async fn decide_registration(
+    id: Uuid,
+    status: RegistrationStatus,
+    audit: Audit,
+    app_state: web::Data<AppState>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    audit.action(match status {
+        RegistrationStatus::Approved => "registration.approve",
+        _ => "registration.reject",
+    });
+    let event_state = app_state.current_event.lock().await;
+    let event = event_state.as_ref().ok_or_else(no_event)?;
+    audit.event(event.get_id()).target(id);
+    let team_id = event.decide_registration(&id, status, &db_pool).await?;
+    audit.target(team_id);
+    info!(
+        event_id:% = event.get_id(),
+        participant_id:% = team_id,
+        status = status.as_str();
+        "Registration decided"
+    );
+    Ok(HttpResponse::Ok().json(web::Json(CommandResponse::new(
+        match status {
+            RegistrationStatus::Approved => "Registration approved",
+            _ => "Registration rejected",
+        },
+        team_id,
+    ))))
+}
+
+// bulk or one by one , the same request either way
+#[post("/event/checkin")]
+pub async fn check_in(
+    req: web::Json<CheckInReq>,
+    audit: Audit,
+    app_state: web::Data<AppState>,
+    vb_srv: web::Data<Addr<VboardSrv>>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    audit.action("checkin.add");
+    req.validate()?;
+    let event_state = app_state.current_event.lock().await;
+    let event = event_state.as_ref().ok_or_else(no_event)?;
+    audit.event(event.get_id()).targets(&req.ids);
+    let report = event.check_in(&req.ids, &db_pool).await?;
+    audit.summary("checked_in", report.checked_in);
+    info!(event_id:% = event.get_id(), checked_in = report.checked_in; "Participants checked in");
+    // with exclude_unchecked a late check-in joins the VaderBoard
+    vb_srv.do_send(VboardGet(TransferType::Broadcast));
+    Ok(HttpResponse::Ok().json(web::Json(report)))
+}
+
+// the QR code scanned at the door
+#[post("/event/checkin/token")]
+pub async fn check_in_token(
+    req: web::Json<TokenCheckIn>,
+    audit: Audit,
+    config: web::Data<VaderConfig>,
+    app_state: web::Data<AppState>,
+    vb_srv: web::Data<Addr<VboardSrv>>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    audit.action("checkin.token");
+    let event_state = app_state.current_event.lock().await;
+    let event = event_state.as_ref().ok_or_else(no_event)?;
+    audit.event(event.get_id());
+    let secret = checkin_secret(&config.checkin)?;
+    let id = verify_checkin_token(secret, &event.get_id(), &req.token)?;
+    audit.target(id);
+    let report = event.check_in(std::slice::from_ref(&id), &db_pool).await?;
+    info!(event_id:% = event.get_id(), participant_id:% = id; "Participant checked in with token");
+    vb_srv.do_send(VboardGet(TransferType::Broadcast));
+    Ok(HttpResponse::Ok().json(web::Json(report)))
+}
+
+#[delete("/event/checkin/{id}")]
+pub async fn undo_check_in(
+    id: web::Path<Uuid>,
+    audit: Audit,
+    app_state: web::Data<AppState>,
+    vb_srv: web::Data<Addr<VboardSrv>>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    audit.action("checkin.remove");
+    let id = id.into_inner();
+    let event_state = app_state.current_event.lock().await;
+    let event = event_state.as_ref().ok_or_else(no_event)?;
+    audit.event(event.get_id()).target(id);
+    event.undo_check_in(&id, &db_pool).await?;
+    info!(event_id:% = event.get_id(), participant_id:% = id; "Check-in undone");
+    vb_srv.do_send(VboardGet(TransferType::Broadcast));
+    Ok(HttpResponse::Ok().json(web::Json(CommandResponse::new("Check-in undone", id))))
+}
+
+// bulk add teams/users/members from csv or json rows , all or nothing : )
+#[post("/event/import")]
+pub async fn import_rows(
+    query: web::Query<ImportQuery>,
+    req: HttpRequest,
+    body: web::Bytes,
+    audit: Audit,
+    app_state: web::Data<AppState>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    audit.action("event.import");
+    let event_state = app_state.current_event.lock().await;
+    let event = event_state.as_ref().ok_or_else(no_event)?;
+    let ImportQuery { format, dry_run } = query.into_inner();
+    let format = format.unwrap_or_else(|| {
+        let is_csv = req
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|ct| ct.to_str().ok())
+            .is_some_and(|ct| ct.starts_with("text/csv"));
+        if is_csv {
+            ImportFormat::Csv
+        } else {
+            ImportFormat::Json
         }
+    });
+    let mut report = ImportReport {
+        dry_run,
+        ..Default::default()
+    };
+    audit
+        .event(event.get_id())
+        .summary("format", format)
+        .summary("dry_run", dry_run)
+        .summary("bytes", body.len());
+    let rows = format.parse_rows(&body, &mut report)?;
+    let report = event.import_rows(rows, report, &db_pool).await?;
+    audit
+        .targets(&report.teams.iter().map(|t| t.id).collect::<Vec<Uuid>>())
+        .targets(&report.users.iter().map(|u| u.id).collect::<Vec<Uuid>>())
+        .summary("committed", report.committed)
+        .summary("errors", report.errors.len());
+    if report.errors.is_empty() {
+        info!(
+            event_id:% = event.get_id(),
+            dry_run = report.dry_run,
+            teams = report.teams.len(),
+            users = report.users.len();
+            "Import successful"
+        );
+        Ok(HttpResponse::Ok().json(web::Json(report)))
+    } else {
+        // per row errors are part of the report , not a single error : )
+        error!(
+            event_id:% = event.get_id(),
+            failed_rows = report.errors.len();
+            "Import rolled back"
+        );
+        Ok(HttpResponse::BadRequest().json(web::Json(report)))
     }
 }
 
@@ -365,95 +618,369 @@ pub async fn delete_event(
     db_pool: web::Data<SqlitePool>,
     app_state: web::Data<AppState>,
     id_info: web::Json<IdQuery>,
-) -> impl Responder {
+    audit: Audit,
+) -> Result<HttpResponse, VaderError<'static>> {
     let id = id_info.into_inner().id;
+    audit.action("event.delete").event(id).target(id);
     let event_state = app_state.current_event.lock().await;
-    if let Some(e) = event_state.as_ref() {
-        let event_id = e.get_id();
-        if event_id.eq(&id) {
-            return HttpResponse::BadRequest().body(
-                VaderError::EventActive(
-                    "Unable to remove Event i.e currently Added/Active.Stop the event to remove",
-                )
-                .to_string(),
-            );
-        }
+    if event_state.as_ref().is_some_and(|e| e.get_id().eq(&id)) {
+        return Err(VaderError::EventActive(
+            "Unable to remove Event i.e currently Added/Active.Stop the event to remove",
+        ));
     }
-    let info_res: Result<EventInfo, VaderError> = EventInfo::get_event_info(&id, &db_pool).await;
-    let res = match info_res {
-        Ok(event) => match event.event_type {
-            EventType::TeamEvent { team_size: _ } => {
-                Event::<Team>::delete_event(&id, &db_pool).await
-            }
-            EventType::UserEvent => Event::<User>::delete_event(&id, &db_pool).await,
-        },
-        Err(e) => {
-            let err = format!("unable to get event info\n.{}", e);
-            error!("{}", err);
-            return HttpResponse::BadRequest().body(err);
-        }
-    };
-    match res {
-        Ok(_) => {
-            info!("Successfully deleted event : {}", id);
-            HttpResponse::Ok().json(web::Json(CommandResponse::new(
-                "Successfully deleted event",
-                id,
-            )))
-        }
-        Err(e) => {
-            let err = format!("Error Deleting event : {}.\n{}", id, e.to_string());
-            error!("{}", err);
-            HttpResponse::BadRequest().body(err)
-        }
+    let event: EventInfo = EventInfo::get_event_info(&id, &db_pool).await?;
+    match event.event_type {
+        EventType::TeamEvent { team_size: _ } => Event::<Team>::delete_event(&id, &db_pool).await?,
+        EventType::UserEvent => Event::<User>::delete_event(&id, &db_pool).await?,
     }
+    info!(event_id:% = id; "Successfully deleted event");
+    Ok(HttpResponse::Ok().json(web::Json(CommandResponse::new(
+        "Successfully deleted event",
+        id,
+    ))))
 }
 
 #[post("/team/delete")]
 pub async fn delete_team(
     db_pool: web::Data<SqlitePool>,
+    vb_srv: web::Data<Addr<VboardSrv>>,
     id_info: web::Json<IdQuery>,
-) -> impl Responder {
+    audit: Audit,
+) -> Result<HttpResponse, VaderError<'static>> {
     let id = id_info.into_inner().id;
-    let res = Team::delete_team(&id, &db_pool).await;
-    match res {
-        Ok(_) => {
-            info!("Successfully deleted team : {}", id);
-            HttpResponse::Ok().json(web::Json(CommandResponse::new(
-                "Successfully deleted team ",
-                id,
-            )))
-        }
-
-        Err(e) => {
-            let err = format!("Error Deleting team : {}.\n{}", id, e.to_string());
-            error!("{}", err);
-            HttpResponse::BadRequest().body(err)
-        }
-    }
+    audit.action("team.delete").target(id);
+    Team::delete_team(&id, &db_pool).await?;
+    info!(participant_id:% = id; "Successfully deleted team");
+    vb_srv.do_send(VboardGet(TransferType::Broadcast));
+    Ok(HttpResponse::Ok().json(web::Json(CommandResponse::new(
+        "Successfully deleted team ",
+        id,
+    ))))
 }
 
 #[post("/user/delete")]
 pub async fn delete_user(
     db_pool: web::Data<SqlitePool>,
+    vb_srv: web::Data<Addr<VboardSrv>>,
     id_info: web::Json<IdQuery>,
-) -> impl Responder {
+    audit: Audit,
+) -> Result<HttpResponse, VaderError<'static>> {
     let id = id_info.into_inner().id;
-    let res = User::delete_user(&id, &db_pool).await;
+    audit.action("user.delete").target(id);
+    User::delete_user(&id, &db_pool).await?;
+    info!(participant_id:% = id; "Successfully deleted user");
+    vb_srv.do_send(VboardGet(TransferType::Broadcast));
+    Ok(HttpResponse::Ok().json(web::Json(CommandResponse::new(
+        "Successfully deleted user",
+        id,
+    ))))
+}
+
+// deleted rows wait in the trash , see GET /admin/trash : )
+#[post("/event/restore")]
+pub async fn restore_event(
+    db_pool: web::Data<SqlitePool>,
+    id_info: web::Json<IdQuery>,
+    audit: Audit,
+) -> Result<HttpResponse, VaderError<'static>> {
+    let id = id_info.into_inner().id;
+    audit.action("event.restore").event(id).target(id);
+    restore_trashed(TrashKind::Event, &id, &db_pool).await?;
+    info!(event_id:% = id; "Successfully restored event");
+    Ok(HttpResponse::Ok().json(web::Json(CommandResponse::new(
+        "Successfully restored event",
+        id,
+    ))))
+}
+
+#[post("/team/restore")]
+pub async fn restore_team(
+    db_pool: web::Data<SqlitePool>,
+    vb_srv: web::Data<Addr<VboardSrv>>,
+    id_info: web::Json<IdQuery>,
+    audit: Audit,
+) -> Result<HttpResponse, VaderError<'static>> {
+    let id = id_info.into_inner().id;
+    audit.action("team.restore").target(id);
+    restore_trashed(TrashKind::Team, &id, &db_pool).await?;
+    info!(participant_id:% = id; "Successfully restored team");
+    vb_srv.do_send(VboardGet(TransferType::Broadcast));
+    Ok(HttpResponse::Ok().json(web::Json(CommandResponse::new(
+        "Successfully restored team",
+        id,
+    ))))
+}
+
+#[post("/user/restore")]
+pub async fn restore_user(
+    db_pool: web::Data<SqlitePool>,
+    vb_srv: web::Data<Addr<VboardSrv>>,
+    id_info: web::Json<IdQuery>,
+    audit: Audit,
+) -> Result<HttpResponse, VaderError<'static>> {
+    let id = id_info.into_inner().id;
+    audit.action("user.restore").target(id);
+    restore_trashed(TrashKind::User, &id, &db_pool).await?;
+    info!(participant_id:% = id; "Successfully restored user");
+    vb_srv.do_send(VboardGet(TransferType::Broadcast));
+    Ok(HttpResponse::Ok().json(web::Json(CommandResponse::new(
+        "Successfully restored user",
+        id,
+    ))))
+}
+
+// permanently removes rows trashed more than ?older_than_days ago
+#[post("/trash/purge")]
+pub async fn purge(
+    query: web::Query<PurgeQuery>,
+    config: web::Data<VaderConfig>,
+    db_pool: web::Data<SqlitePool>,
+    audit: Audit,
+) -> Result<HttpResponse, VaderError<'static>> {
+    let days = query
+        .into_inner()
+        .older_than_days
+        .unwrap_or(config.trash.retention_days);
+    audit.action("trash.purge").summary("older_than_days", days);
+    let report = purge_trash(days, &db_pool).await?;
+    audit
+        .summary("events", report.events)
+        .summary("teams", report.teams)
+        .summary("users", report.users);
+    info!(events = report.events, teams = report.teams, users = report.users; "Trash purged");
+    Ok(HttpResponse::Ok().json(web::Json(report)))
+}
+
+#[patch("/team/{id}")]
+pub async fn update_team(
+    db_pool: web::Data<SqlitePool>,
+    vb_srv: web::Data<Addr<VboardSrv>>,
+    id: web::Path<Uuid>,
+    patch: web::Json<PlayerPatch<'_>>,
+    audit: Audit,
+) -> Result<HttpResponse, VaderError<'static>> {
+    let id = id.into_inner();
+    audit
+        .action("team.update")
+        .target(id)
+        .summary("name", &patch.name)
+        .summary("logo", &patch.logo);
+    patch.validate()?;
+    Team::update_team(&id, &patch, &db_pool).await?;
+    info!(participant_id:% = id; "Successfully updated team");
+    vb_srv.do_send(VboardGet(TransferType::Broadcast));
+    Ok(HttpResponse::Ok().json(web::Json(CommandResponse::new(
+        "Successfully updated team",
+        id,
+    ))))
+}
+
+#[delete("/team/{id}/members/{user_id}")]
+pub async fn remove_team_member(
+    db_pool: web::Data<SqlitePool>,
+    vb_srv: web::Data<Addr<VboardSrv>>,
+    ids: web::Path<(Uuid, Uuid)>,
+    audit: Audit,
+) -> Result<HttpResponse, VaderError<'static>> {
+    let (team_id, user_id) = ids.into_inner();
+    audit
+        .action("team.members.remove")
+        .target(team_id)
+        .target(user_id);
+    let promoted = Team::remove_member(&team_id, &user_id, &db_pool).await?;
+    audit.summary("promoted", &promoted);
+    info!(
+        participant_id:% = user_id,
+        team_id:% = team_id,
+        promoted = promoted.len();
+        "Removed user from team"
+    );
+    vb_srv.do_send(VboardGet(TransferType::Broadcast));
+    Ok(HttpResponse::Ok().json(web::Json(CommandResponse::new(
+        "Successfully removed member",
+        user_id,
+    ))))
+}
+
+#[post("/team/{id}/join")]
+pub async fn join_team(
+    db_pool: web::Data<SqlitePool>,
+    vb_srv: web::Data<Addr<VboardSrv>>,
+    id: web::Path<Uuid>,
+    join_req: web::Json<JoinReq>,
+    audit: Audit,
+) -> Result<HttpResponse, VaderError<'static>> {
+    let team_id = id.into_inner();
+    audit
+        .action("team.members.join")
+        .target(team_id)
+        .target(join_req.user_id);
+    let res = Team::join(&team_id, &join_req.user_id, join_req.waitlist, &db_pool).await?;
+    audit.summary("result", &res);
     match res {
-        Ok(_) => {
-            info!("Successfully deleted user : {}", id);
-            HttpResponse::Ok().json(web::Json(CommandResponse::new(
-                "Successfully deleted user",
-                id,
-            )))
+        JoinRes::Member => {
+            info!(participant_id:% = join_req.user_id, team_id:% = team_id; "User joined team");
+            vb_srv.do_send(VboardGet(TransferType::Broadcast));
         }
-        Err(e) => {
-            let err = format!("Error Deleting user : {}.\n{}", id, e.to_string());
-            error!("{}", err);
-            HttpResponse::BadRequest().body(err)
+        JoinRes::Waitlisted { position } => {
+            info!(participant_id:% = join_req.user_id, team_id:% = team_id, position; "User waitlisted");
         }
     }
+    Ok(HttpResponse::Ok().json(web::Json(res)))
+}
+
+#[delete("/team/{id}/waitlist/{user_id}")]
+pub async fn leave_waitlist(
+    db_pool: web::Data<SqlitePool>,
+    ids: web::Path<(Uuid, Uuid)>,
+    audit: Audit,
+) -> Result<HttpResponse, VaderError<'static>> {
+    let (team_id, user_id) = ids.into_inner();
+    audit
+        .action("team.waitlist.remove")
+        .target(team_id)
+        .target(user_id);
+    Team::leave_waitlist(&team_id, &user_id, &db_pool).await?;
+    info!(participant_id:% = user_id, team_id:% = team_id; "Removed user from waitlist");
+    Ok(HttpResponse::Ok().json(web::Json(CommandResponse::new(
+        "Successfully removed from waitlist",
+        user_id,
+    ))))
+}
+
+#[post("/team/{id}/captain")]
+pub async fn set_captain(
+    db_pool: web::Data<SqlitePool>,
+    id: web::Path<Uuid>,
+    captain: web::Json<CaptainReq>,
+    audit: Audit,
+) -> Result<HttpResponse, VaderError<'static>> {
+    let team_id = id.into_inner();
+    audit
+        .action("team.captain.set")
+        .target(team_id)
+        .target(captain.user_id);
+    Team::set_captain(&team_id, &captain.user_id, &db_pool).await?;
+    info!(participant_id:% = captain.user_id, team_id:% = team_id; "Team captain set");
+    Ok(HttpResponse::Ok().json(web::Json(CommandResponse::new(
+        "Successfully set team captain",
+        captain.user_id,
+    ))))
+}
+
+#[delete("/team/{id}/captain")]
+pub async fn clear_captain(
+    db_pool: web::Data<SqlitePool>,
+    id: web::Path<Uuid>,
+    audit: Audit,
+) -> Result<HttpResponse, VaderError<'static>> {
+    let team_id = id.into_inner();
+    audit.action("team.captain.clear").target(team_id);
+    Team::clear_captain(&team_id, &db_pool).await?;
+    info!(team_id:% = team_id; "Team captain cleared");
+    Ok(HttpResponse::Ok().json(web::Json(CommandResponse::new(
+        "Successfully cleared team captain",
+        team_id,
+    ))))
+}
+
+#[patch("/user/{id}")]
+pub async fn update_user(
+    db_pool: web::Data<SqlitePool>,
+    vb_srv: web::Data<Addr<VboardSrv>>,
+    id: web::Path<Uuid>,
+    patch: web::Json<PlayerPatch<'_>>,
+    audit: Audit,
+) -> Result<HttpResponse, VaderError<'static>> {
+    let id = id.into_inner();
+    audit
+        .action("user.update")
+        .target(id)
+        .summary("name", &patch.name)
+        .summary("logo", &patch.logo);
+    patch.validate()?;
+    User::update_user(&id, &patch, &db_pool).await?;
+    info!(participant_id:% = id; "Successfully updated user");
+    vb_srv.do_send(VboardGet(TransferType::Broadcast));
+    Ok(HttpResponse::Ok().json(web::Json(CommandResponse::new(
+        "Successfully updated user",
+        id,
+    ))))
+}
+
+#[patch("/user/{id}/team")]
+pub async fn move_user(
+    db_pool: web::Data<SqlitePool>,
+    vb_srv: web::Data<Addr<VboardSrv>>,
+    id: web::Path<Uuid>,
+    move_req: web::Json<MoveMember>,
+    audit: Audit,
+) -> Result<HttpResponse, VaderError<'static>> {
+    let id = id.into_inner();
+    audit
+        .action("user.move")
+        .target(id)
+        .target(move_req.team_id);
+    let promoted = User::move_to_team(&id, &move_req.team_id, &db_pool).await?;
+    audit.summary("promoted", &promoted);
+    info!(
+        participant_id:% = id,
+        team_id:% = move_req.team_id,
+        promoted = promoted.len();
+        "Moved user to team"
+    );
+    vb_srv.do_send(VboardGet(TransferType::Broadcast));
+    Ok(HttpResponse::Ok().json(web::Json(CommandResponse::new(
+        "Successfully moved user",
+        id,
+    ))))
+}
+
+#[post("/db/backup")]
+pub async fn backup(
+    db_pool: web::Data<SqlitePool>,
+    config: web::Data<VaderConfig>,
+    audit: Audit,
+) -> Result<HttpResponse, VaderError<'static>> {
+    audit.action("db.backup");
+    let info = backup_db(&db_pool, &config.backup).await?;
+    audit.summary("name", &info.name);
+    info!(backup = info.name.as_str(); "Backup successful");
+    Ok(HttpResponse::Ok().json(web::Json(info)))
+}
+
+// rebuild/optimize/check the search index , reports every fts table : )
+#[post("/db/fts/{op}")]
+pub async fn fts_maintenance(
+    op: web::Path<FtsOp>,
+    db_pool: web::Data<SqlitePool>,
+    audit: Audit,
+) -> Result<HttpResponse, VaderError<'static>> {
+    let op = op.into_inner();
+    audit.action("db.fts").summary("op", op);
+    let reports = maintain_fts(op, &db_pool).await?;
+    let in_sync = reports.iter().all(|r| r.ok);
+    audit.summary("in_sync", in_sync);
+    info!(op:? = op, in_sync = in_sync; "Search index maintenance done");
+    Ok(HttpResponse::Ok().json(web::Json(reports)))
+}
+
+// validated now , swapped in when the server restarts : )
+#[post("/db/restore")]
+pub async fn restore(
+    restore_req: web::Json<RestoreReq>,
+    config: web::Data<VaderConfig>,
+    audit: Audit,
+) -> Result<HttpResponse, VaderError<'static>> {
+    let name = restore_req.into_inner().name;
+    audit.action("db.restore").summary("name", &name);
+    let path = find_backup(&config.backup, &name)?;
+    let version = stage_restore(&config, &path).await?;
+    info!(backup = name.as_str(), version = version; "Restore staged");
+    Ok(HttpResponse::Ok().body(format!(
+        "Restore of {} staged [schema version : {}] , restart the server to apply",
+        name, version
+    )))
 }
 
 #[post("/login")]
@@ -461,25 +988,35 @@ pub async fn login(
     session: Session,
     login_info: web::Json<AdminInfo>,
     db_pool: web::Data<SqlitePool>,
-) -> impl Responder {
+    audit: Audit,
+) -> Result<HttpResponse, VaderError<'static>> {
     let login = login_info.into_inner();
-    match login.verify_passwd(&db_pool).await {
-        Ok(true) => {
-            if session.insert("admin", true).is_ok() {
-                log::debug!("Login Successful : )");
-                HttpResponse::Ok().body("Login Successful")
-            } else {
-                log::debug!("Unable to get Admin Session");
-                HttpResponse::InternalServerError().finish()
-            }
-        }
-        Ok(false) => {
-            log::debug!("Invalid UserName/Password");
-            HttpResponse::Unauthorized().body("Invalid UserName/Password")
-        }
-        Err(e) => {
-            log::debug!("Admin Auth error : {}", e.to_string());
-            HttpResponse::InternalServerError().finish()
-        }
+    let username = login.username.clone();
+    // the claimed name is only the actor once the password checks out
+    audit.action("admin.login").summary("username", &username);
+    let verified = login.verify_passwd(&db_pool).await;
+    if !matches!(verified, Ok(true)) {
+        metrics().login_failures.inc();
     }
+    if !verified? {
+        log::warn!(admin = username.as_str(); "Invalid UserName/Password");
+        return Err(VaderError::Unauthorized("Invalid UserName/Password"));
+    }
+    session
+        .insert("admin", true)
+        .map_err(|e| VaderError::SessionError(e.to_string()))?;
+    session
+        .insert("admin_user", &username)
+        .map_err(|e| VaderError::SessionError(e.to_string()))?;
+    audit.actor(&username);
+    info!(admin = username.as_str(); "Login Successful : )");
+    Ok(HttpResponse::Ok().body("Login Successful"))
+}
+
+fn no_event() -> VaderError<'static> {
+    VaderError::EventNotActive("No event added.Add event to start event")
+}
+
+fn another_event() -> VaderError<'static> {
+    VaderError::EventActive("Another event already Added . Wait till the current Event ends")
 }
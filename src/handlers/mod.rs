@@ -1,2 +1,3 @@
+pub mod cli_handlers;
 pub mod command_handlers;
 pub mod query_handlers;
@@ -1,61 +1,138 @@
-
+use std::sync::atomic::Ordering;
+use std::time::Instant;
 
 use actix::Addr;
-use actix_web::{get, web, HttpRequest, HttpResponse, Responder};
-use actix_web_actors::ws;
+use actix_web::http::header::{CacheControl, CacheDirective, EntityTag};
+use actix_web::http::StatusCode;
+use actix_web::{get, web, HttpRequest, HttpResponse};
 use log::debug;
 use sqlx::SqlitePool;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+use validator::Validate;
 
+use crate::models::audit_models::{
+    AuditClient, AuditEntry, AuditExportQuery, AuditQuery, AuditSrv,
+};
+use crate::models::checkin_models::CheckInToken;
+use crate::models::config_models::VaderConfig;
 use crate::models::error_models::VaderError;
+use crate::models::export_models::{EventExport, ExportQuery};
+use crate::models::health_models::HealthReport;
+use crate::models::page_models::{Page, PageQuery, SearchQuery, PAGE_LIMIT_DEFAULT};
 use crate::models::query_models::{
-    CurFtsBuilder, CurFtsServer, EventInfo, EventQuery, FtsQuery, IdQuery, TeamInfo, VboardClient,
-    VboardSrv,
+    CurFtsBuilder, CurFtsCount, CurFtsServer, EventInfo, EventQuery, FtsQuery, IdQuery, SseConnect,
+    TeamInfo, VbCount, VboardClient, VboardSrv, SSE_BUFFER,
 };
+use crate::models::registration_models::RegistrationQuery;
+use crate::models::trash_models::TrashQuery;
 use crate::models::v_models::{AppState, Team, User};
+use crate::models::wrapper_models::EventWrapper;
+use crate::models::ws_models::{WsGate, WsLimit, WsStats};
+use crate::services::backup_services::list_backups;
+use crate::services::cache_services::{cached_json, is_fresh, not_modified, search_response};
+use crate::services::checkin_services::{checkin_secret, checkin_token};
+use crate::services::health_services::{check_actor, check_db, check_dist, check_migrations};
+use crate::services::metrics_services::metrics;
+use crate::services::page_services::search_page;
+use crate::services::query_services::Queriable;
+use crate::services::trash_services::list_trash;
+use crate::services::ws_services::start_ws;
 
 #[get("/event/info")]
-pub async fn get_current_event(app_state: web::Data<AppState>) -> impl Responder {
+pub async fn get_current_event(
+    app_state: web::Data<AppState>,
+) -> Result<HttpResponse, VaderError<'static>> {
     let event_state = app_state.current_event.lock().await;
-    if event_state.is_none() {
-        debug!("Request delined.No event added");
-        HttpResponse::BadRequest().body("No event added.Add event to Fetch details")
-    } else {
-        let res: EventQuery = event_state.as_ref().unwrap().get_event();
-        HttpResponse::Ok().json(web::Json(res))
-    }
+    let res: EventQuery = event_state.as_ref().ok_or_else(no_event)?.get_event();
+    Ok(HttpResponse::Ok().json(web::Json(res)))
+}
+// public , whether the current event takes team registrations
+#[get("/event/registration")]
+pub async fn get_registration_state(
+    app_state: web::Data<AppState>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    let event_state = app_state.current_event.lock().await;
+    let event = event_state.as_ref().ok_or_else(no_event)?;
+    let res = event.registration_state(&db_pool).await?;
+    Ok(HttpResponse::Ok().json(web::Json(res)))
+}
+
+// registrations of the current event , oldest first
+#[get("/event/registrations")]
+pub async fn get_registrations(
+    query: web::Query<RegistrationQuery>,
+    app_state: web::Data<AppState>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    let event_state = app_state.current_event.lock().await;
+    let event = event_state.as_ref().ok_or_else(no_event)?;
+    let res = event.get_registrations(query.status, &db_pool).await?;
+    Ok(HttpResponse::Ok().json(web::Json(res)))
+}
+// everyone in the current event with their check-in time
+#[get("/event/checkin")]
+pub async fn get_check_ins(
+    app_state: web::Data<AppState>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    let event_state = app_state.current_event.lock().await;
+    let event = event_state.as_ref().ok_or_else(no_event)?;
+    let res = event.get_check_ins(&db_pool).await?;
+    Ok(HttpResponse::Ok().json(web::Json(res)))
+}
+
+// the signed token to print as a QR code , for teams/users of the current event
+#[get("/event/checkin/{id}/token")]
+pub async fn get_checkin_token(
+    id: web::Path<Uuid>,
+    config: web::Data<VaderConfig>,
+    app_state: web::Data<AppState>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    let id = id.into_inner();
+    let event_state = app_state.current_event.lock().await;
+    let event = event_state.as_ref().ok_or_else(no_event)?;
+    let secret = checkin_secret(&config.checkin)?;
+    event.checked_in_at(&id, &db_pool).await?;
+    let token = checkin_token(secret, &event.get_id(), &id);
+    Ok(HttpResponse::Ok().json(web::Json(CheckInToken { id, token })))
 }
 #[get("event/info/team/{count}")]
 pub async fn get_event_teams(
     app_state: web::Data<AppState>,
     srv_addr: web::Data<Addr<CurFtsServer<'static>>>,
     db_pool: web::Data<SqlitePool>,
+    gate: web::Data<WsGate>,
     count: web::Path<u32>,
     req: HttpRequest,
     stream: web::Payload,
-) -> impl Responder {
+) -> Result<HttpResponse, actix_web::Error> {
     let event_state = app_state.current_event.lock().await;
     if event_state.is_none() {
         debug!("Request delined.No event added");
-        Ok(HttpResponse::BadRequest().body("No event added.Add event to Fetch details"))
+        Err(no_event().into())
     } else {
         let event = event_state.as_ref().unwrap();
         match event {
             crate::models::wrapper_models::EventWrapper::TeamEvent(_) => {
                 let event_id = event.get_id();
-                let cur_fts = CurFtsBuilder::<Team>::new(
-                    event_id,
-                    srv_addr.into_inner(),
-                    count.into_inner(),
-                    db_pool.into_inner(),
-                )
-                .team_fts()
-                .build();
-                ws::start(cur_fts, &req, stream)
+                let count = count.into_inner();
+                start_ws(&gate, &req, stream, Some(count), |conn| {
+                    CurFtsBuilder::<Team>::new(
+                        event_id,
+                        srv_addr.into_inner(),
+                        count,
+                        db_pool.into_inner(),
+                        conn,
+                    )
+                    .team_fts()
+                    .build()
+                })
             }
             crate::models::wrapper_models::EventWrapper::UserEvent(_) => {
-                Ok(HttpResponse::BadRequest().body(
-                    VaderError::EventTypeMismatch("Cannot get Team Info in user event").to_string(),
-                ))
+                Err(VaderError::EventTypeMismatch("Cannot get Team Info in user event").into())
             }
         }
     }
@@ -65,33 +142,35 @@ pub async fn get_event_rem_members(
     app_state: web::Data<AppState>,
     srv_addr: web::Data<Addr<CurFtsServer<'static>>>,
     db_pool: web::Data<SqlitePool>,
+    gate: web::Data<WsGate>,
     count: web::Path<u32>,
     req: HttpRequest,
     stream: web::Payload,
-) -> impl Responder {
+) -> Result<HttpResponse, actix_web::Error> {
     let event_state = app_state.current_event.lock().await;
     if event_state.is_none() {
         debug!("Request delined.No event added");
-        Ok(HttpResponse::BadRequest().body("No event added.Add event to Fetch details"))
+        Err(no_event().into())
     } else {
         let event = event_state.as_ref().unwrap();
         match event {
             crate::models::wrapper_models::EventWrapper::TeamEvent(_) => {
                 let event_id = event.get_id();
-                let cur_fts = CurFtsBuilder::<Team>::new(
-                    event_id,
-                    srv_addr.into_inner(),
-                    count.into_inner(),
-                    db_pool.into_inner(),
-                )
-                .rem_user_fts()
-                .build();
-                ws::start(cur_fts, &req, stream)
+                let count = count.into_inner();
+                start_ws(&gate, &req, stream, Some(count), |conn| {
+                    CurFtsBuilder::<Team>::new(
+                        event_id,
+                        srv_addr.into_inner(),
+                        count,
+                        db_pool.into_inner(),
+                        conn,
+                    )
+                    .rem_user_fts()
+                    .build()
+                })
             }
             crate::models::wrapper_models::EventWrapper::UserEvent(_) => {
-                Ok(HttpResponse::BadRequest().body(
-                    VaderError::EventTypeMismatch("Cannot get Team Info in user event").to_string(),
-                ))
+                Err(VaderError::EventTypeMismatch("Cannot get Team Info in user event").into())
             }
         }
     }
@@ -102,32 +181,34 @@ pub async fn get_event_users(
     app_state: web::Data<AppState>,
     srv_addr: web::Data<Addr<CurFtsServer<'static>>>,
     db_pool: web::Data<SqlitePool>,
+    gate: web::Data<WsGate>,
     count: web::Path<u32>,
     req: HttpRequest,
     stream: web::Payload,
-) -> impl Responder {
+) -> Result<HttpResponse, actix_web::Error> {
     let event_state = app_state.current_event.lock().await;
     if event_state.is_none() {
         debug!("Request delined.No event added");
-        Ok(HttpResponse::BadRequest().body("No event added.Add event to Fetch details"))
+        Err(no_event().into())
     } else {
         let event = event_state.as_ref().unwrap();
         match event {
             crate::models::wrapper_models::EventWrapper::TeamEvent(_) => {
-                Ok(HttpResponse::BadRequest().body(
-                    VaderError::EventTypeMismatch("Cannot get User Info in team event").to_string(),
-                ))
+                Err(VaderError::EventTypeMismatch("Cannot get User Info in team event").into())
             }
             crate::models::wrapper_models::EventWrapper::UserEvent(_) => {
                 let event_id = event.get_id();
-                let cur_fts = CurFtsBuilder::<User>::new(
-                    event_id,
-                    srv_addr.into_inner(),
-                    count.into_inner(),
-                    db_pool.into_inner(),
-                )
-                .build();
-                ws::start(cur_fts, &req, stream)
+                let count = count.into_inner();
+                start_ws(&gate, &req, stream, Some(count), |conn| {
+                    CurFtsBuilder::<User>::new(
+                        event_id,
+                        srv_addr.into_inner(),
+                        count,
+                        db_pool.into_inner(),
+                        conn,
+                    )
+                    .build()
+                })
             }
         }
     }
@@ -137,105 +218,258 @@ pub async fn get_event_users(
 pub async fn get_event_info(
     id_info: web::Json<IdQuery>,
     db_pool: web::Data<SqlitePool>,
-) -> impl Responder {
+) -> Result<HttpResponse, VaderError<'static>> {
     let id = id_info.into_inner().id;
-    let res: Result<EventInfo, VaderError> = EventInfo::get_event_info(&id, &db_pool).await;
-    match res {
-        Ok(event) => HttpResponse::Ok().json(web::Json(event)),
-        Err(e) => HttpResponse::BadRequest().body(e.to_string()),
-    }
+    let event: EventInfo = EventInfo::get_event_info(&id, &db_pool).await?;
+    Ok(HttpResponse::Ok().json(web::Json(event)))
 }
 
+// one page per request , pass `next_cursor` back as `cursor` for the next : )
 #[get("/event/info/all")]
-pub async fn get_all_event(db_pool: web::Data<SqlitePool>) -> impl Responder {
-    let res: Result<Vec<EventInfo>, VaderError> = EventInfo::get_all_event_info(&db_pool).await;
-    match res {
-        Ok(event) => HttpResponse::Ok().json(web::Json(event)),
-        Err(e) => HttpResponse::BadRequest().body(e.to_string()),
-    }
+pub async fn get_all_event(
+    page: web::Query<PageQuery>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    page.validate()?;
+    let res: Page<EventInfo> = EventInfo::get_event_page(&page, &db_pool).await?;
+    Ok(HttpResponse::Ok().json(web::Json(res)))
+}
+
+// full ranking with members , as csv/json/printable html : )
+#[get("/event/export/{id}")]
+pub async fn export_event(
+    id: web::Path<Uuid>,
+    query: web::Query<ExportQuery>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    let id = id.into_inner();
+    let format = query.into_inner().format;
+    let body = EventExport::get_export(&id, &db_pool)
+        .await?
+        .render(format)?;
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header((
+            "Content-Disposition",
+            format!("inline; filename=\"{}.{}\"", id, format.extension()),
+        ))
+        .body(body))
+}
+
+#[get("/db/backups")]
+pub async fn get_backups(
+    config: web::Data<VaderConfig>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    let backups = list_backups(&config.backup)?;
+    Ok(HttpResponse::Ok().json(web::Json(backups)))
+}
+
+// deleted events/teams/users , newest first with the date a purge may remove them
+#[get("/trash")]
+pub async fn get_trash(
+    query: web::Query<TrashQuery>,
+    config: web::Data<VaderConfig>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    let items = list_trash(query.kind, config.trash.retention_days, &db_pool).await?;
+    Ok(HttpResponse::Ok().json(web::Json(items)))
+}
+
+// users waiting for a place in the team , next in line first
+#[get("/team/{id}/waitlist")]
+pub async fn get_team_waitlist(
+    id: web::Path<Uuid>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    let waitlist = Team::get_waitlist(&id, &db_pool).await?;
+    Ok(HttpResponse::Ok().json(web::Json(waitlist)))
+}
+
+// audit entries newest first , filtered by action/actor/event_id/target_id/outcome
+#[get("/audit")]
+pub async fn get_audit_log(
+    query: web::Query<AuditQuery>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    query.validate()?;
+    let res: Page<AuditEntry> = AuditEntry::get_page(&query, &db_pool).await?;
+    Ok(HttpResponse::Ok().json(web::Json(res)))
+}
+
+// every matching entry oldest first , same filters as /audit
+#[get("/audit/export")]
+pub async fn export_audit_log(
+    query: web::Query<AuditQuery>,
+    export: web::Query<AuditExportQuery>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    let format = export.into_inner().format;
+    let body = AuditEntry::export(&query, format, &db_pool).await?;
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header((
+            "Content-Disposition",
+            format!("attachment; filename=\"audit-log.{}\"", format.extension()),
+        ))
+        .body(body))
+}
+
+// new audit entries as they are written , one json entry per message
+#[get("/audit/ws")]
+pub async fn audit_ws(
+    req: HttpRequest,
+    srv_addr: web::Data<Addr<AuditSrv>>,
+    gate: web::Data<WsGate>,
+    stream: web::Payload,
+) -> Result<HttpResponse, actix_web::Error> {
+    start_ws(&gate, &req, stream, None, |conn| {
+        AuditClient::new(srv_addr, conn)
+    })
+}
+
+// open websockets , dead ones are dropped after the websocket timeout : )
+#[get("/ws/stats")]
+pub async fn ws_stats(
+    gate: web::Data<WsGate>,
+    vb_srv: web::Data<Addr<VboardSrv>>,
+    cur_fts: web::Data<Addr<CurFtsServer<'static>>>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    let (vb_count, sse_count) = vb_srv.send(VbCount).await?;
+    let cur_fts_count = cur_fts.send(CurFtsCount).await?;
+    Ok(HttpResponse::Ok().json(web::Json(WsStats {
+        total: gate.live(),
+        vaderboard: vb_count,
+        current_event: cur_fts_count,
+        event_streams: sse_count,
+    })))
+}
+
+// liveness , answers as long as the workers do
+#[get("/healthz")]
+pub async fn healthz() -> HttpResponse {
+    HttpResponse::Ok()
+        .insert_header(CacheControl(vec![CacheDirective::NoStore]))
+        .json(web::Json(HealthReport::new(Vec::new())))
+}
+
+// readiness , 503 with the failing checks until everything is up : )
+#[get("/readyz")]
+pub async fn readyz(
+    config: web::Data<VaderConfig>,
+    vb_srv: web::Data<Addr<VboardSrv>>,
+    cur_fts: web::Data<Addr<CurFtsServer<'static>>>,
+    db_pool: web::Data<SqlitePool>,
+) -> HttpResponse {
+    let (db, migrations, vb_actor, cur_fts_actor) = futures::join!(
+        check_db(&db_pool),
+        check_migrations(&db_pool),
+        check_actor("vaderboard_server", &vb_srv),
+        check_actor("current_event_server", &cur_fts),
+    );
+    let dist = check_dist(&config.server.dist_dir);
+    let report = HealthReport::new(vec![db, migrations, vb_actor, cur_fts_actor, dist]);
+    let mut res = if report.is_ok() {
+        HttpResponse::Ok()
+    } else {
+        HttpResponse::ServiceUnavailable()
+    };
+    res.insert_header(CacheControl(vec![CacheDirective::NoStore]))
+        .json(web::Json(report))
+}
+
+// prometheus text format , the pool and stream gauges are read on scrape : )
+#[get("/metrics")]
+pub async fn get_metrics(
+    vb_srv: web::Data<Addr<VboardSrv>>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    let (_, sse_count) = vb_srv.send(VbCount).await?;
+    metrics().sse_connections.set(sse_count as i64);
+    let body = metrics().scrape(&db_pool).await?;
+    Ok(HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4; charset=utf-8")
+        .body(body))
 }
 
 #[get("/team/info")]
 pub async fn get_team_info(
     id_info: web::Json<IdQuery>,
     db_pool: web::Data<SqlitePool>,
-) -> impl Responder {
+) -> Result<HttpResponse, VaderError<'static>> {
     let id = id_info.into_inner().id;
-    match Team::get_team(&id, &db_pool).await {
-        Ok(team) => HttpResponse::Ok().json(web::Json(team)),
-        Err(e) => HttpResponse::BadRequest().body(e.to_string()),
-    }
+    let team = Team::get_team(&id, &db_pool).await?;
+    Ok(HttpResponse::Ok().json(web::Json(team)))
 }
 
 #[get("/team/info/all")]
-pub async fn get_all_team(db_pool: web::Data<SqlitePool>) -> impl Responder {
-    let res: Result<Vec<TeamInfo>, VaderError> = TeamInfo::get_all_team_info(&db_pool).await;
-    match res {
-        Ok(event) => HttpResponse::Ok().json(web::Json(event)),
-        Err(e) => HttpResponse::BadRequest().body(e.to_string()),
-    }
+pub async fn get_all_team(
+    page: web::Query<PageQuery>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    page.validate()?;
+    let res: Page<TeamInfo> = TeamInfo::get_team_page(&page, &db_pool).await?;
+    Ok(HttpResponse::Ok().json(web::Json(res)))
 }
 
 #[get("/user/info")]
 pub async fn get_user_info(
     id_info: web::Json<IdQuery>,
     db_pool: web::Data<SqlitePool>,
-) -> impl Responder {
+) -> Result<HttpResponse, VaderError<'static>> {
     let id = id_info.into_inner().id;
-    match User::get_user(&id, &db_pool).await {
-        Ok(user) => HttpResponse::Ok().json(web::Json(user)),
-        Err(e) => HttpResponse::BadRequest().body(e.to_string()),
-    }
+    let user = User::get_user(&id, &db_pool).await?;
+    Ok(HttpResponse::Ok().json(web::Json(user)))
 }
 
 #[get("/user/info/all")]
-pub async fn get_all_user(db_pool: web::Data<SqlitePool>) -> impl Responder {
-    let res: Result<Vec<User>, VaderError> = User::get_all_user(&db_pool).await;
-    match res {
-        Ok(event) => HttpResponse::Ok().json(web::Json(event)),
-        Err(e) => HttpResponse::BadRequest().body(e.to_string()),
-    }
+pub async fn get_all_user(
+    page: web::Query<PageQuery>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    page.validate()?;
+    let res: Page<User> = User::get_user_page(&page, &db_pool).await?;
+    Ok(HttpResponse::Ok().json(web::Json(res)))
 }
 
 #[get("/event/fts/{count}")]
 pub async fn event_fts(
     req: HttpRequest,
     db_pool: web::Data<SqlitePool>,
+    gate: web::Data<WsGate>,
     count: web::Path<u32>,
     stream: web::Payload,
-) -> impl Responder {
-    ws::start(
-        FtsQuery::<EventInfo>::new(count.into_inner(), db_pool.into_inner()),
-        &req,
-        stream,
-    )
+) -> Result<HttpResponse, actix_web::Error> {
+    let count = count.into_inner();
+    start_ws(&gate, &req, stream, Some(count), |conn| {
+        FtsQuery::<EventInfo>::new(count, db_pool.into_inner(), conn)
+    })
 }
 
 #[get("/team/fts/{count}")]
-pub async fn team_fts<'a>(
+pub async fn team_fts(
     req: HttpRequest,
     db_pool: web::Data<SqlitePool>,
+    gate: web::Data<WsGate>,
     count: web::Path<u32>,
     stream: web::Payload,
-) -> impl Responder {
-    ws::start(
-        FtsQuery::<TeamInfo>::new(count.into_inner(), db_pool.into_inner()),
-        &req,
-        stream,
-    )
+) -> Result<HttpResponse, actix_web::Error> {
+    let count = count.into_inner();
+    start_ws(&gate, &req, stream, Some(count), |conn| {
+        FtsQuery::<TeamInfo>::new(count, db_pool.into_inner(), conn)
+    })
 }
 #[get("/user/fts/{count}")]
 pub async fn user_fts(
     req: HttpRequest,
     db_pool: web::Data<SqlitePool>,
+    gate: web::Data<WsGate>,
     count: web::Path<u32>,
     stream: web::Payload,
-) -> impl Responder {
-    ws::start(
-        FtsQuery::<User>::new(count.into_inner(), db_pool.into_inner()),
-        &req,
-        stream,
-    )
+) -> Result<HttpResponse, actix_web::Error> {
+    let count = count.into_inner();
+    start_ws(&gate, &req, stream, Some(count), |conn| {
+        FtsQuery::<User>::new(count, db_pool.into_inner(), conn)
+    })
 }
 
 #[get("/vaderboard")]
@@ -243,13 +477,212 @@ pub async fn vaderboard(
     req: HttpRequest,
     app_state: web::Data<AppState>,
     srv_addr: web::Data<Addr<VboardSrv>>,
+    gate: web::Data<WsGate>,
     stream: web::Payload,
-) -> impl Responder {
+) -> Result<HttpResponse, actix_web::Error> {
     let event_state = app_state.current_event.lock().await;
     if event_state.is_none() {
         debug!("Request delined.No event added");
-        Ok(HttpResponse::BadRequest().body("No event added.Add event to Fetch details"))
+        Err(no_event().into())
     } else {
-        ws::start(VboardClient::new(srv_addr), &req, stream)
+        start_ws(&gate, &req, stream, None, |conn| {
+            VboardClient::new(srv_addr, conn)
+        })
+    }
+}
+
+// the VaderBoard as server sent events , `vaderboard` with every update and
+// `started`/`ended` with the event , resumes from Last-Event-ID : )
+#[get("/vaderboard/events")]
+pub async fn vaderboard_events(
+    req: HttpRequest,
+    srv_addr: web::Data<Addr<VboardSrv>>,
+    gate: web::Data<WsGate>,
+) -> HttpResponse {
+    // streams share the websocket caps , the slot is freed with the stream
+    let conn = match gate.admit(&req, None) {
+        Ok(conn) => conn,
+        Err(limit) => {
+            let status = match limit {
+                WsLimit::Connections => StatusCode::SERVICE_UNAVAILABLE,
+                _ => StatusCode::TOO_MANY_REQUESTS,
+            };
+            log::debug!(
+                "Event stream refused : {:?}",
+                limit.close_reason().description
+            );
+            return HttpResponse::build(status)
+                .body(limit.close_reason().description.unwrap_or_default());
+        }
+    };
+    let last_event_id = req
+        .headers()
+        .get("Last-Event-ID")
+        .and_then(|id| id.to_str().ok())
+        .and_then(|id| id.trim().parse().ok());
+    let (tx, rx) = mpsc::channel(SSE_BUFFER);
+    srv_addr.do_send(SseConnect { tx, last_event_id });
+    let stream = futures::stream::unfold((rx, conn), |(mut rx, conn)| async move {
+        rx.recv()
+            .await
+            .map(|frame| (Ok::<_, actix_web::Error>(frame), (rx, conn)))
+    });
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(CacheControl(vec![CacheDirective::NoCache]))
+        //don't let nginx buffer the stream
+        .insert_header(("X-Accel-Buffering", "no"))
+        .streaming(stream)
+}
+
+// plain http VaderBoard , the etag only changes when the scores do : )
+#[get("/leaderboard")]
+pub async fn get_leaderboard(
+    req: HttpRequest,
+    app_state: web::Data<AppState>,
+    config: web::Data<VaderConfig>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    //read the version before the scores , a racing update only costs a refetch
+    let version = app_state.vb_version.load(Ordering::SeqCst);
+    let event_state = app_state.current_event.lock().await;
+    let event = event_state.as_ref().ok_or_else(no_event)?;
+    let etag = EntityTag::new_weak(format!("{}-{}", event.get_id(), version));
+    let max_age = config.vaderboard.cache_secs;
+    if is_fresh(&req, &etag) {
+        return Ok(not_modified(&etag, max_age));
+    }
+    let start = Instant::now();
+    let vb_str = event.get_vboard(&db_pool, app_state.vb_count).await?;
+    metrics().observe_query("vaderboard", start);
+    Ok(cached_json(&etag, max_age, vb_str.into_owned()))
+}
+
+// http twins of the search websockets , one page per request : )
+#[get("/event/search")]
+pub async fn event_search(
+    req: HttpRequest,
+    query: web::Query<SearchQuery>,
+    config: web::Data<VaderConfig>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    let body = search_all::<EventInfo>(&query, &db_pool).await?;
+    Ok(search_response(&req, config.vaderboard.cache_secs, body))
+}
+
+#[get("/team/search")]
+pub async fn team_search(
+    req: HttpRequest,
+    query: web::Query<SearchQuery>,
+    config: web::Data<VaderConfig>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    let body = search_all::<TeamInfo>(&query, &db_pool).await?;
+    Ok(search_response(&req, config.vaderboard.cache_secs, body))
+}
+
+#[get("/user/search")]
+pub async fn user_search(
+    req: HttpRequest,
+    query: web::Query<SearchQuery>,
+    config: web::Data<VaderConfig>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    let body = search_all::<User>(&query, &db_pool).await?;
+    Ok(search_response(&req, config.vaderboard.cache_secs, body))
+}
+
+#[get("/event/info/team/search")]
+pub async fn event_team_search(
+    req: HttpRequest,
+    query: web::Query<SearchQuery>,
+    app_state: web::Data<AppState>,
+    config: web::Data<VaderConfig>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    query.validate()?;
+    let event_id = team_event_id(&app_state).await?;
+    let (q, offset, count) = (&query.q, query.offset, search_count(&query));
+    let body = TeamInfo::event_team_fts(&event_id, q, offset, count + 1, &db_pool)
+        .await
+        .and_then(|teams| search_page(q, offset, count, teams))?;
+    Ok(search_response(&req, config.vaderboard.cache_secs, body))
+}
+
+#[get("/event/info/team/rem_members/search")]
+pub async fn event_rem_members_search(
+    req: HttpRequest,
+    query: web::Query<SearchQuery>,
+    app_state: web::Data<AppState>,
+    config: web::Data<VaderConfig>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    query.validate()?;
+    // only team events have members to place
+    team_event_id(&app_state).await?;
+    let (q, offset, count) = (&query.q, query.offset, search_count(&query));
+    let body = TeamInfo::event_rem_users_fts(q, offset, count + 1, &db_pool)
+        .await
+        .and_then(|users| search_page(q, offset, count, users))?;
+    Ok(search_response(&req, config.vaderboard.cache_secs, body))
+}
+
+#[get("/event/info/user/search")]
+pub async fn event_user_search(
+    req: HttpRequest,
+    query: web::Query<SearchQuery>,
+    app_state: web::Data<AppState>,
+    config: web::Data<VaderConfig>,
+    db_pool: web::Data<SqlitePool>,
+) -> Result<HttpResponse, VaderError<'static>> {
+    query.validate()?;
+    let event_id = {
+        let event_state = app_state.current_event.lock().await;
+        match event_state.as_ref().ok_or_else(no_event)? {
+            EventWrapper::TeamEvent(_) => {
+                return Err(VaderError::EventTypeMismatch(
+                    "Cannot get User Info in team event",
+                ))
+            }
+            event => event.get_id(),
+        }
+    };
+    let (q, offset, count) = (&query.q, query.offset, search_count(&query));
+    let body = User::event_user_fts(&event_id, q, offset, count + 1, &db_pool)
+        .await
+        .and_then(|users| search_page(q, offset, count, users))?;
+    Ok(search_response(&req, config.vaderboard.cache_secs, body))
+}
+
+async fn search_all<T>(
+    query: &SearchQuery,
+    db_pool: &SqlitePool,
+) -> Result<String, VaderError<'static>>
+where
+    T: Queriable,
+    T::QueryRes: serde::Serialize,
+{
+    query.validate()?;
+    let count = search_count(query);
+    let items = T::fts_query(&query.q, query.offset, count + 1, db_pool).await?;
+    search_page(&query.q, query.offset, count, items)
+}
+
+fn search_count(query: &SearchQuery) -> u32 {
+    query.count.unwrap_or(PAGE_LIMIT_DEFAULT)
+}
+
+// the lock is dropped before searching , searches don't need the event
+async fn team_event_id(app_state: &AppState) -> Result<Uuid, VaderError<'static>> {
+    let event_state = app_state.current_event.lock().await;
+    match event_state.as_ref().ok_or_else(no_event)? {
+        EventWrapper::UserEvent(_) => Err(VaderError::EventTypeMismatch(
+            "Cannot get Team Info in user event",
+        )),
+        event => Ok(event.get_id()),
     }
 }
+
+fn no_event() -> VaderError<'static> {
+    VaderError::EventNotActive("No event added.Add event to Fetch details")
+}
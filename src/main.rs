@@ -1,35 +1,50 @@
-use std::env;
-
 use actix::Actor;
-use actix_cors::Cors;
 use actix_session::config::BrowserSession;
 use actix_session::storage::CookieSessionStore;
 use actix_session::SessionMiddleware;
-use actix_web::cookie::Key;
-use actix_web::middleware::Logger;
 use actix_web::web::{self, Data};
 use actix_web::{App, HttpServer};
+use clap::Parser;
 use dotenvy::dotenv;
 use mimalloc::MiMalloc;
-use sqlx::SqlitePool;
 mod handlers;
 mod models;
 mod services;
 
 use actix_files::Files;
 
+use crate::handlers::cli_handlers::run_cli;
 use crate::handlers::command_handlers::{
-    add_event, add_team, add_team_members, add_team_with_members, add_user, delete_event,
-    delete_team, delete_user, end_event, login, reset_score, start_event, update_score,
+    add_event, add_team, add_team_members, add_team_with_members, add_user, approve_registration,
+    backup, check_in, check_in_token, clear_captain, delete_event, delete_team, delete_user,
+    end_event, fts_maintenance, import_rows, join_registration, join_team, leave_waitlist, login,
+    move_user, purge, register_team, reject_registration, remove_team_member, reset_score, restore,
+    restore_event, restore_team, restore_user, set_captain, start_event, toggle_registration,
+    undo_check_in, update_score, update_team, update_user,
 };
 use crate::handlers::query_handlers::{
-    event_fts, get_all_event, get_all_team, get_all_user, get_current_event, get_event_info,
-    get_event_rem_members, get_event_teams, get_event_users, get_team_info, get_user_info,
-    team_fts, user_fts, vaderboard,
+    audit_ws, event_fts, event_rem_members_search, event_search, event_team_search,
+    event_user_search, export_audit_log, export_event, get_all_event, get_all_team, get_all_user,
+    get_audit_log, get_backups, get_check_ins, get_checkin_token, get_current_event,
+    get_event_info, get_event_rem_members, get_event_teams, get_event_users, get_leaderboard,
+    get_metrics, get_registration_state, get_registrations, get_team_info, get_team_waitlist,
+    get_trash, get_user_info, healthz, readyz, team_fts, team_search, user_fts, user_search,
+    vaderboard, vaderboard_events, ws_stats,
 };
+use crate::models::audit_models::AuditSrv;
+use crate::models::cli_models::VaderCommand;
+use crate::models::config_models::{VaderArgs, VaderConfig};
+use crate::models::error_models::invalid_request;
 use crate::models::query_models::{CurFtsServer, VboardSrv};
+use crate::models::registration_models::RegistrationGate;
 use crate::models::v_models::AppState;
-use crate::services::v_middlewares::AdminOnlyGuard;
+use crate::models::wrapper_models::EventWrapper;
+use crate::models::ws_models::WsGate;
+use crate::services::audit_services::schedule_audit_prune;
+use crate::services::backup_services::{apply_staged_restore, schedule_backups};
+use crate::services::db_services::{connect_db, run_migrations, seed_admin};
+use crate::services::log_services::init_logger;
+use crate::services::v_middlewares::{AdminOnlyGuard, AuditLog, RequestLogger, RequestMetrics};
 
 #[global_allocator]
 static GLOBAL: MiMalloc = MiMalloc;
@@ -37,47 +52,109 @@ static GLOBAL: MiMalloc = MiMalloc;
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     dotenv().ok();
-    env::set_var("RUST_LOG", "actix_web=debug");
-    env_logger::builder()
-        .filter_level(log::LevelFilter::Debug)
-        .init();
-    let host = env::var("HOST").unwrap_or_else(|_| "0.0.0.0".to_string());
-    let port = env::var("PORT").unwrap_or_else(|_| "8080".to_string());
-    let db_url = env::var("DATABASE_URL").expect("Error Reading DATABASE_URL Env Variable");
-    let vb_count: u32 = env::var("VADERBOARD_COUNT").map_or(10, |count| {
-        count
-            .parse::<u32>()
-            .expect("Unable to parse VADERBOARD_COUNT,please replace with a positive integer")
-    });
-    let session_key = Key::generate();
-    let host_port = format!("{}:{}", host, port);
-    let db_pool = SqlitePool::connect(&db_url)
+    let args = VaderArgs::parse();
+    let config = match VaderConfig::load(&args) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    if args.print_config {
+        match config.to_toml() {
+            Ok(config_str) => println!("{}", config_str),
+            Err(e) => eprintln!("{}", e),
+        }
+        return Ok(());
+    }
+    init_logger(&config);
+    let host_port = config.host_port();
+    let workers = config.workers();
+    let command = args.command.unwrap_or(VaderCommand::Serve);
+    //swap in a staged restore before the pool opens the database
+    if matches!(command, VaderCommand::Serve | VaderCommand::Migrate) {
+        apply_staged_restore(&config)
+            .await
+            .expect("Unable to apply staged restore");
+    }
+    let db_pool = connect_db(&config)
         .await
         .expect("Error connecting to Database");
-    let app_state = web::Data::new(AppState::new(vb_count));
+    if config.database.auto_migrate || matches!(command, VaderCommand::Migrate) {
+        run_migrations(&db_pool)
+            .await
+            .expect("Unable to run Db migrations");
+    }
+    seed_admin(&config, &db_pool)
+        .await
+        .expect("Error adding admin cred to admin_login");
+    match command {
+        VaderCommand::Serve => {}
+        VaderCommand::Migrate => {
+            log::info!("Migrations completed successfully");
+            return Ok(());
+        }
+        command => {
+            if !run_cli(command, args.json, &config, &db_pool).await {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+    }
+    let session_key = config.session_key();
+    let app_state = web::Data::new(AppState::new(config.vaderboard.count));
+    //restore the current event , may have been added/started by the cli
+    match EventWrapper::get_current(&db_pool).await {
+        Ok(Some(event)) => {
+            log::info!("Restored current event [id : {}]", event.get_id());
+            *app_state.current_event.lock().await = Some(event);
+        }
+        Ok(None) => {}
+        Err(e) => log::error!("Unable to restore current event : {}", e),
+    }
+    schedule_backups(db_pool.clone(), config.backup.clone());
+    schedule_audit_prune(db_pool.clone(), config.audit.clone());
+    let config = web::Data::new(config);
     //VaderBoard server Actor
     let vb_srv = VboardSrv::new(app_state.clone(), db_pool.clone()).start();
     //Current Event Fts Actor
     let cur_fts = CurFtsServer::new().start();
-    let cpus = num_cpus::get();
+    let ws_gate = Data::new(WsGate::new(&config.websocket));
+    let registration_gate = Data::new(RegistrationGate::new(
+        &config.registration,
+        &config.websocket,
+    ));
+    //Audit log Actor , streams new entries to admin websockets
+    let audit_srv = AuditSrv::default().start();
     log::info!("Database connection successful");
     log::info!("Server Starting on :  {}", host_port);
     HttpServer::new(move || {
         App::new()
-            .wrap(Logger::default())
-            //warning : Never use Cors::permissive in production : )
-            .wrap(Cors::permissive())
+            .wrap(AuditLog)
+            .wrap(RequestMetrics)
+            .wrap(RequestLogger)
+            .wrap(config.cors())
             .wrap(
                 SessionMiddleware::builder(CookieSessionStore::default(), session_key.clone())
-                    .cookie_secure(false)
+                    .cookie_secure(config.session.cookie_secure)
                     .session_lifecycle(BrowserSession::default())
                     .build(),
             )
+            .app_data(config.clone())
             .app_data(app_state.clone())
             .app_data(Data::new(vb_srv.clone()))
             .app_data(Data::new(cur_fts.clone()))
+            .app_data(Data::new(audit_srv.clone()))
+            .app_data(ws_gate.clone())
+            .app_data(registration_gate.clone())
             .app_data(Data::new(db_pool.clone()))
+            .app_data(web::JsonConfig::default().error_handler(invalid_request))
+            .app_data(web::QueryConfig::default().error_handler(invalid_request))
+            .app_data(web::PathConfig::default().error_handler(invalid_request))
             .service(login)
+            .service(register_team)
+            .service(join_registration)
+            .service(get_registration_state)
             .service(
                 web::scope("/admin")
                     .wrap(AdminOnlyGuard)
@@ -86,15 +163,59 @@ async fn main() -> std::io::Result<()> {
                     .service(add_team)
                     .service(add_team_members)
                     .service(add_team_with_members)
+                    .service(toggle_registration)
+                    .service(get_registrations)
+                    .service(approve_registration)
+                    .service(reject_registration)
+                    .service(get_check_ins)
+                    .service(check_in)
+                    .service(check_in_token)
+                    .service(undo_check_in)
+                    .service(get_checkin_token)
+                    .service(import_rows)
                     .service(start_event)
                     .service(update_score)
                     .service(reset_score)
                     .service(delete_event)
                     .service(delete_team)
                     .service(delete_user)
+                    .service(restore_event)
+                    .service(restore_team)
+                    .service(restore_user)
+                    .service(get_trash)
+                    .service(purge)
+                    .service(update_team)
+                    .service(update_user)
+                    .service(move_user)
+                    .service(remove_team_member)
+                    .service(join_team)
+                    .service(get_team_waitlist)
+                    .service(leave_waitlist)
+                    .service(set_captain)
+                    .service(clear_captain)
+                    .service(export_event)
+                    .service(backup)
+                    .service(restore)
+                    .service(fts_maintenance)
+                    .service(get_backups)
+                    .service(ws_stats)
+                    .service(get_audit_log)
+                    .service(export_audit_log)
+                    .service(audit_ws)
                     .service(end_event),
             )
             .service(get_current_event)
+            //before the websockets , `search` would match their {count}
+            .service(event_team_search)
+            .service(event_rem_members_search)
+            .service(event_user_search)
+            .service(event_search)
+            .service(team_search)
+            .service(user_search)
+            .service(get_leaderboard)
+            .service(get_metrics)
+            .service(healthz)
+            .service(readyz)
             .service(get_event_teams)
             .service(get_event_rem_members)
             .service(get_event_users)
@@ -108,10 +229,11 @@ async fn main() -> std::io::Result<()> {
             .service(team_fts)
             .service(user_fts)
             .service(vaderboard)
-            .service(Files::new("/", "dist").index_file("index.html"))
+            .service(vaderboard_events)
+            .service(Files::new("/", &config.server.dist_dir).index_file("index.html"))
     })
     .bind(host_port)?
-    .workers(cpus * 2)
+    .workers(workers)
     .run()
     .await
 }
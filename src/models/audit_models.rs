@@ -0,0 +1,143 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use actix::{Actor, Addr, AsyncContext, Message};
+use actix_web::web;
+use actix_web_actors::ws;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::metrics_models::WS_AUDIT_CLIENT;
+use super::validation_models::PAGE_LIMIT_MAX;
+use super::ws_models::WsConn;
+use crate::services::metrics_services::ws_gauge;
+use crate::services::ws_services::start_heartbeat;
+
+// one audited request , written by the AuditLog middleware once it's answered
+#[derive(Serialize, Clone)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub created_at: String,
+    // admin username , None if the request had no (named) admin session
+    pub actor: Option<String>,
+    pub request_id: Option<String>,
+    // ie: team.delete , the route pattern if the handler never ran
+    pub action: String,
+    pub event_id: Option<Uuid>,
+    pub target_ids: Vec<Uuid>,
+    pub summary: serde_json::Value,
+    pub outcome: AuditOutcome,
+    pub status: u16,
+    // error code of a failed request
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditOutcome {
+    Ok,
+    Error,
+}
+
+// filled in by the handler , a request only gets the Audit the middleware put
+// in its extensions : )
+#[derive(Default)]
+pub struct AuditNote {
+    pub action: Option<&'static str>,
+    pub actor: Option<String>,
+    pub event_id: Option<Uuid>,
+    pub target_ids: Vec<Uuid>,
+    pub summary: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Clone, Default)]
+pub struct Audit(pub Rc<RefCell<AuditNote>>);
+
+// ?action=..&actor=..&event_id=..&target_id=..&outcome=..&limit=..&cursor=..
+// newest first , cursor is the `next_cursor` of the previous page
+#[derive(Deserialize, Validate, Default)]
+pub struct AuditQuery {
+    pub action: Option<String>,
+    pub actor: Option<String>,
+    pub event_id: Option<Uuid>,
+    pub target_id: Option<Uuid>,
+    pub outcome: Option<AuditOutcome>,
+    #[validate(range(min = 1, max = "PAGE_LIMIT_MAX", message = "must be between 1 and 500"))]
+    pub limit: Option<u32>,
+    pub cursor: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(Deserialize)]
+pub struct AuditExportQuery {
+    #[serde(default)]
+    pub format: AuditFormat,
+}
+
+// live audit entries for admin websockets
+#[derive(Default)]
+pub struct AuditSrv {
+    pub clients: HashSet<Addr<AuditClient>>,
+}
+impl Actor for AuditSrv {
+    type Context = actix::Context<Self>;
+}
+
+pub struct AuditClient {
+    pub srv_addr: Arc<Addr<AuditSrv>>,
+    pub addr: Option<Addr<AuditClient>>,
+    pub conn: WsConn,
+}
+impl AuditClient {
+    pub fn new(srv_addr: web::Data<Addr<AuditSrv>>, conn: WsConn) -> Self {
+        Self {
+            srv_addr: srv_addr.into_inner(),
+            addr: None,
+            conn,
+        }
+    }
+}
+
+impl Actor for AuditClient {
+    type Context = ws::WebsocketContext<Self>;
+    fn started(&mut self, ctx: &mut Self::Context) {
+        let addr = ctx.address();
+        self.addr = Some(addr.clone());
+        ws_gauge(WS_AUDIT_CLIENT).inc();
+        start_heartbeat(self, ctx);
+        self.srv_addr.do_send(AuditConnect(addr))
+    }
+    fn stopped(&mut self, _ctx: &mut Self::Context) {
+        ws_gauge(WS_AUDIT_CLIENT).dec();
+        if let Some(addr) = &self.addr {
+            self.srv_addr.do_send(AuditDisconnect(addr.clone()))
+        }
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct AuditConnect(pub Addr<AuditClient>);
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct AuditDisconnect(pub Addr<AuditClient>);
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct AuditAppend(pub AuditEntry);
+
+// an entry serialized once for every client
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct AuditRes(pub Arc<str>);
@@ -0,0 +1,15 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize)]
+pub struct BackupInfo {
+    pub name: String,
+    pub size: u64,
+    pub created_at: DateTime<Utc>,
+}
+
+// restore only takes backups from the backup dir , never arbitrary paths : )
+#[derive(Deserialize)]
+pub struct RestoreReq {
+    pub name: String,
+}
@@ -0,0 +1,37 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+// teams of a team event , users of a user event
+#[derive(Deserialize, Validate)]
+pub struct CheckInReq {
+    #[validate(length(min = 1, message = "must have atleast one id"))]
+    pub ids: Vec<Uuid>,
+}
+
+// the signed token of a participant's QR code
+#[derive(Deserialize)]
+pub struct TokenCheckIn {
+    pub token: String,
+}
+
+#[derive(Serialize)]
+pub struct CheckInReport {
+    pub checked_in: u64,
+    // checked in before , keep their first check-in time
+    pub already_checked_in: u64,
+}
+
+#[derive(Serialize)]
+pub struct CheckIn {
+    pub id: Uuid,
+    pub name: String,
+    pub checked_in_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize)]
+pub struct CheckInToken {
+    pub id: Uuid,
+    pub token: String,
+}
@@ -0,0 +1,400 @@
+use std::path::PathBuf;
+
+use clap::{Args, Subcommand};
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::export_models::ExportFormat;
+use super::fts_models::FtsOp;
+use super::import_models::ImportFormat;
+use super::registration_models::RegistrationStatus;
+use super::trash_models::TrashKind;
+
+#[derive(Subcommand)]
+pub enum VaderCommand {
+    /// Run migrations and start the server (default)
+    Serve,
+    /// Run database migrations , seed the admin and exit
+    Migrate,
+    /// Manage events
+    #[command(subcommand)]
+    Event(EventCmd),
+    /// Manage teams of the current event
+    #[command(subcommand)]
+    Team(TeamCmd),
+    /// Manage users of the current event
+    #[command(subcommand)]
+    User(UserCmd),
+    /// Update scores of the current event
+    #[command(subcommand)]
+    Score(ScoreCmd),
+    /// Import teams , users and members of the current event from a csv/json file
+    Import(ImportArgs),
+    /// Export the full ranking of an event as csv/json/html
+    Export(ExportArgs),
+    /// Manage admin logins
+    #[command(subcommand)]
+    Admin(AdminCmd),
+    /// Backup and restore the database
+    #[command(subcommand)]
+    Db(DbCmd),
+    /// List or purge deleted events , teams and users
+    #[command(subcommand)]
+    Trash(TrashCmd),
+    /// Open/close team registration of the current event , approve or reject teams
+    #[command(subcommand)]
+    Registration(RegistrationCmd),
+    /// Check in teams/users of the current event , print or scan their tokens
+    #[command(subcommand)]
+    Checkin(CheckinCmd),
+}
+
+// event add/start/stop change the current event stored in the database ,
+// a running server picks them up on restart : )
+#[derive(Subcommand)]
+pub enum EventCmd {
+    /// Add a new event , becomes the current event
+    Add {
+        #[arg(long)]
+        name: String,
+        #[arg(long)]
+        logo: Option<String>,
+        /// Team size of a team event , user event if not set
+        #[arg(long)]
+        team_size: Option<u32>,
+        /// Lowest score a team/user can reach
+        #[arg(long, allow_hyphen_values = true)]
+        min_score: Option<i64>,
+        /// Highest score a team/user can reach
+        #[arg(long, allow_hyphen_values = true)]
+        max_score: Option<i64>,
+        /// Largest change allowed in a single score update
+        #[arg(long)]
+        max_delta: Option<i64>,
+        /// Keep teams/users that did not check in off the VaderBoard
+        #[arg(long)]
+        exclude_unchecked: bool,
+    },
+    /// Start the current event
+    Start,
+    /// Stop the current event
+    Stop,
+    /// Show the current event
+    Current,
+    /// List all events
+    List,
+    /// Delete an event that is not current , moves it to the trash
+    Delete {
+        #[arg(long)]
+        id: Uuid,
+    },
+    /// Restore a deleted event with its teams/users
+    Restore {
+        #[arg(long)]
+        id: Uuid,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TeamCmd {
+    /// Add a team with members to the current event
+    Add {
+        #[arg(long)]
+        name: String,
+        #[arg(long)]
+        logo: Option<String>,
+        /// Member name , can be repeated
+        #[arg(long = "member")]
+        members: Vec<String>,
+    },
+    /// Bulk add teams with members from a json file : [{"team_info":{..},"members":[..]}]
+    AddBulk {
+        #[arg(long)]
+        file: PathBuf,
+    },
+    /// List all teams
+    List,
+    /// Rename a team and/or change its logo , empty logo clears it
+    Edit {
+        #[arg(long)]
+        id: Uuid,
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long)]
+        logo: Option<String>,
+    },
+    /// Remove a single member from a team
+    RemoveMember {
+        #[arg(long)]
+        id: Uuid,
+        #[arg(long)]
+        user: Uuid,
+    },
+    /// Add a user to a team , or its waitlist with --waitlist when the team is full
+    Join {
+        #[arg(long)]
+        id: Uuid,
+        #[arg(long)]
+        user: Uuid,
+        #[arg(long)]
+        waitlist: bool,
+    },
+    /// List the waitlist of a team , next in line first
+    Waitlist {
+        #[arg(long)]
+        id: Uuid,
+    },
+    /// Take a user off the waitlist of a team
+    LeaveWaitlist {
+        #[arg(long)]
+        id: Uuid,
+        #[arg(long)]
+        user: Uuid,
+    },
+    /// Make a member the team captain , clears the captain if --user is not set
+    Captain {
+        #[arg(long)]
+        id: Uuid,
+        #[arg(long)]
+        user: Option<Uuid>,
+    },
+    /// Delete a team , moves it to the trash
+    Delete {
+        #[arg(long)]
+        id: Uuid,
+    },
+    /// Restore a deleted team with its members
+    Restore {
+        #[arg(long)]
+        id: Uuid,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum UserCmd {
+    /// Add users to the current event
+    Add {
+        /// User name , can be repeated
+        #[arg(long = "name", required = true)]
+        names: Vec<String>,
+    },
+    /// Bulk add users from a json file : [{"name":..,"logo":..}]
+    AddBulk {
+        #[arg(long)]
+        file: PathBuf,
+    },
+    /// List all users
+    List,
+    /// Rename a user and/or change its logo , empty logo clears it
+    Edit {
+        #[arg(long)]
+        id: Uuid,
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long)]
+        logo: Option<String>,
+    },
+    /// Move a user to another team , the team must have room for them
+    Move {
+        #[arg(long)]
+        id: Uuid,
+        #[arg(long)]
+        team: Uuid,
+    },
+    /// Delete a user , moves them to the trash
+    Delete {
+        #[arg(long)]
+        id: Uuid,
+    },
+    /// Restore a deleted user with their team/event
+    Restore {
+        #[arg(long)]
+        id: Uuid,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ScoreCmd {
+    /// Add points to a team/user of the current event , negative to subtract
+    Update {
+        #[arg(long)]
+        id: Uuid,
+        #[arg(long, allow_negative_numbers = true)]
+        score: i64,
+    },
+    /// Reset scores of the current event before it starts
+    Reset,
+}
+
+#[derive(Args)]
+pub struct ImportArgs {
+    /// csv with header team,team_logo,user,user_logo or a json array of such rows
+    #[arg(long)]
+    pub file: PathBuf,
+    /// File format [default : from the file extension]
+    #[arg(long, value_enum)]
+    pub format: Option<ImportFormat>,
+    /// Validate and report without saving anything
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Args)]
+pub struct ExportArgs {
+    /// Event id , defaults to the current event
+    #[arg(long)]
+    pub event: Option<Uuid>,
+    #[arg(long, value_enum, default_value_t = ExportFormat::Json)]
+    pub format: ExportFormat,
+    /// Write the export to a file instead of stdout
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+pub enum AdminCmd {
+    /// Add an admin login
+    Add {
+        #[arg(long)]
+        username: String,
+        #[arg(long, env = "VADER_CLI_PASSWORD", hide_env_values = true)]
+        password: String,
+    },
+    /// Change the password of an admin
+    Passwd {
+        #[arg(long)]
+        username: String,
+        #[arg(long, env = "VADER_CLI_PASSWORD", hide_env_values = true)]
+        password: String,
+    },
+    /// Remove an admin login
+    Remove {
+        #[arg(long)]
+        username: String,
+    },
+    /// List admin usernames
+    List,
+}
+
+#[derive(Subcommand)]
+pub enum DbCmd {
+    /// Take an online backup to the backup dir , older ones are pruned by retention
+    Backup {
+        /// Write the backup to this file instead , not pruned
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// List backups in the backup dir
+    List,
+    /// Validate a backup and stage it , applied on the next `serve`/`migrate`
+    Restore {
+        #[arg(long)]
+        file: PathBuf,
+    },
+    /// Rebuild , optimize or check the search index against the base tables
+    Fts {
+        #[arg(value_enum)]
+        op: FtsOp,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TrashCmd {
+    /// List deleted events , teams and users
+    List {
+        #[arg(long, value_enum)]
+        kind: Option<TrashKind>,
+    },
+    /// Permanently remove everything deleted before the retention period
+    Purge {
+        /// Purge rows deleted more than n days ago [default : trash.retention_days]
+        #[arg(long)]
+        older_than_days: Option<u32>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum RegistrationCmd {
+    /// Let participants register teams for the current event
+    Open,
+    /// Stop taking registrations , registered teams stay as they are
+    Close,
+    /// List registrations of the current event , oldest first
+    List {
+        #[arg(long, value_enum)]
+        status: Option<RegistrationStatus>,
+    },
+    /// Approve a pending registration , its team joins the event
+    Approve {
+        #[arg(long)]
+        id: Uuid,
+    },
+    /// Reject a pending registration , its team moves to the trash
+    Reject {
+        #[arg(long)]
+        id: Uuid,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum CheckinCmd {
+    /// List teams/users of the current event with their check-in time
+    List,
+    /// Check in teams/users of the current event , --id can be repeated
+    Add {
+        #[arg(long = "id", required = true)]
+        ids: Vec<Uuid>,
+    },
+    /// Undo the check-in of a team/user
+    Remove {
+        #[arg(long)]
+        id: Uuid,
+    },
+    /// Print the signed check-in token of a team/user , needs checkin.secret
+    Token {
+        #[arg(long)]
+        id: Uuid,
+    },
+    /// Check in the team/user of a scanned token
+    Scan {
+        #[arg(long)]
+        token: String,
+    },
+}
+
+// printed as is with --json , message only otherwise : )
+#[derive(Serialize)]
+pub struct CliResponse {
+    pub status: CliStatus,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CliStatus {
+    Ok,
+    Error,
+}
+
+impl CliResponse {
+    pub fn ok(message: impl Into<String>) -> Self {
+        Self {
+            status: CliStatus::Ok,
+            message: message.into(),
+            data: None,
+        }
+    }
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            status: CliStatus::Error,
+            message: message.into(),
+            data: None,
+        }
+    }
+    pub fn with_data<T: Serialize>(mut self, data: &T) -> Result<Self, String> {
+        self.data = Some(serde_json::to_value(data).map_err(|e| e.to_string())?);
+        Ok(self)
+    }
+}
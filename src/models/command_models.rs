@@ -2,52 +2,96 @@ use std::borrow::Cow;
 
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+use validator::Validate;
 
 use super::error_models::VaderError;
 use super::query_models::EventType;
-use super::v_models::{Event, Team, User};
+use super::v_models::{Event, ScoreLimits, Team, User};
+use crate::services::validation_services::{
+    validate_event_type, validate_logo, validate_name, validate_patch, validate_score_delta,
+};
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Validate)]
 pub struct EventReq<'a> {
+    #[validate(custom = "validate_name")]
     name: Cow<'a, str>,
     #[serde(default)]
+    #[validate(custom = "validate_logo")]
     logo: Option<Cow<'a, str>>,
+    #[validate(custom = "validate_event_type")]
     pub event_type: EventType,
+    #[serde(default)]
+    #[validate]
+    pub score_limits: ScoreLimits,
+    #[serde(default)]
+    pub exclude_unchecked: bool,
+}
+
+impl<'a> EventReq<'a> {
+    pub fn new(
+        name: Cow<'a, str>,
+        logo: Option<Cow<'a, str>>,
+        event_type: EventType,
+        score_limits: ScoreLimits,
+        exclude_unchecked: bool,
+    ) -> Self {
+        Self {
+            name,
+            logo,
+            event_type,
+            score_limits,
+            exclude_unchecked,
+        }
+    }
 }
 
 impl<'a> From<EventReq<'a>> for Result<Event<'a, Team<'a>>, VaderError<'a>> {
     fn from(req: EventReq<'a>) -> Self {
         match req.event_type {
             EventType::TeamEvent { team_size } => {
-                Ok(Event::<Team>::new(req.name, req.logo, Some(team_size)))
-            }
-            EventType::UserEvent => {
-                Err(VaderError::TeamSizeMismatch("time size was not specified"))
+                Ok(Event::<Team>::new(req.name, req.logo, Some(team_size))
+                    .with_score_limits(req.score_limits)
+                    .with_exclude_unchecked(req.exclude_unchecked))
             }
+            EventType::UserEvent => Err(VaderError::EventTypeMismatch("Team size not specified")),
         }
     }
 }
 impl<'a> From<EventReq<'a>> for Result<Event<'a, User<'a>>, VaderError<'a>> {
     fn from(req: EventReq<'a>) -> Self {
-        Ok(Event::<User>::new(req.name, req.logo, None))
+        Ok(Event::<User>::new(req.name, req.logo, None)
+            .with_score_limits(req.score_limits)
+            .with_exclude_unchecked(req.exclude_unchecked))
     }
 }
-#[derive(Deserialize)]
+// score is added to the current score , not set : )
+#[derive(Deserialize, Validate)]
 pub struct ScoreUpdate {
     pub id: Uuid,
+    #[validate(custom = "validate_score_delta")]
     pub score: i64,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Validate)]
 pub struct ContestantInfo<'a> {
+    #[validate(custom = "validate_name")]
     name: Cow<'a, str>,
     #[serde(default)]
+    #[validate(custom = "validate_logo")]
     logo: Option<Cow<'a, str>>,
 }
 
-#[derive(Deserialize)]
+impl<'a> ContestantInfo<'a> {
+    pub fn new(name: Cow<'a, str>, logo: Option<Cow<'a, str>>) -> Self {
+        Self { name, logo }
+    }
+}
+
+#[derive(Deserialize, Validate)]
 pub struct TeamWithMembers<'a> {
+    #[validate]
     pub team_info: ContestantInfo<'a>,
+    #[validate]
     pub members: Vec<ContestantInfo<'a>>,
 }
 
@@ -72,8 +116,26 @@ impl<'a> CommandResponse<'a> {
         Self { msg, id }
     }
 }
-#[derive(Deserialize)]
+#[derive(Deserialize, Validate)]
 pub struct MemberInfo {
     pub team_id: Uuid,
+    #[validate(length(min = 1, message = "must have atleast one member"))]
     pub members: Vec<Uuid>,
 }
+
+// rename and/or change logo of a team/user , "" clears the logo : )
+#[derive(Deserialize, Validate, Default)]
+#[validate(schema(function = "validate_patch"))]
+pub struct PlayerPatch<'a> {
+    #[serde(default)]
+    #[validate(custom = "validate_name")]
+    pub name: Option<Cow<'a, str>>,
+    #[serde(default)]
+    #[validate(custom = "validate_logo")]
+    pub logo: Option<Cow<'a, str>>,
+}
+
+#[derive(Deserialize)]
+pub struct MoveMember {
+    pub team_id: Uuid,
+}
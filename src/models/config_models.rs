@@ -0,0 +1,320 @@
+use std::path::PathBuf;
+
+use clap::{Args, Parser, ValueEnum};
+use serde::{Deserialize, Serialize};
+
+use super::cli_models::VaderCommand;
+
+// Config is layered : defaults < toml file < env variables < cli flags : )
+#[derive(Parser)]
+#[command(name = "vader-admin", version, about = "VaderBoard Admin server")]
+pub struct VaderArgs {
+    /// Path to the toml config file [default : vaderboard.toml if present]
+    #[arg(short, long, env = "VADERBOARD_CONFIG")]
+    pub config: Option<PathBuf>,
+    /// Print the effective config and exit
+    #[arg(long)]
+    pub print_config: bool,
+    /// Print command results as json , for scripting
+    #[arg(long, global = true)]
+    pub json: bool,
+    #[command(flatten)]
+    pub overrides: ConfigOverrides,
+    #[command(subcommand)]
+    pub command: Option<VaderCommand>,
+}
+
+#[derive(Args, Default)]
+pub struct ConfigOverrides {
+    /// Address to bind the server to
+    #[arg(long, env = "HOST")]
+    pub host: Option<String>,
+    /// Port to bind the server to
+    #[arg(long, env = "PORT")]
+    pub port: Option<u16>,
+    /// No of http workers [default : 2 * cpu count]
+    #[arg(long, env = "VADERBOARD_WORKERS")]
+    pub workers: Option<usize>,
+    /// Directory of the vader-admin-ui build
+    #[arg(long, env = "VADERBOARD_DIST")]
+    pub dist_dir: Option<PathBuf>,
+    /// Sqlite database url
+    #[arg(long, env = "DATABASE_URL")]
+    pub database_url: Option<String>,
+    /// Run pending migrations when the server starts
+    #[arg(long, env = "VADERBOARD_AUTO_MIGRATE")]
+    pub auto_migrate: Option<bool>,
+    /// Username of the initial admin
+    #[arg(long, env = "ADMIN_USERNAME")]
+    pub admin_username: Option<String>,
+    /// Password of the initial admin
+    #[arg(long, env = "ADMIN_PASSWORD", hide_env_values = true)]
+    pub admin_password: Option<String>,
+    /// Session cookie signing key (atleast 64 bytes)
+    #[arg(long, env = "VADERBOARD_SESSION_KEY", hide_env_values = true)]
+    pub session_key: Option<String>,
+    /// Mark the session cookie as secure (https only)
+    #[arg(long, env = "VADERBOARD_COOKIE_SECURE")]
+    pub cookie_secure: Option<bool>,
+    /// Comma separated list of allowed CORS origins, "*" allows any origin
+    #[arg(long, env = "VADERBOARD_CORS_ORIGINS", value_delimiter = ',')]
+    pub cors_origins: Option<Vec<String>>,
+    /// No of entries in the VaderBoard
+    #[arg(long, env = "VADERBOARD_COUNT")]
+    pub vb_count: Option<u32>,
+    /// Seconds the http VaderBoard and search responses may be cached for
+    #[arg(long, env = "VADERBOARD_CACHE_SECS")]
+    pub vb_cache_secs: Option<u32>,
+    /// Seconds between websocket pings
+    #[arg(long, env = "VADERBOARD_WS_HEARTBEAT")]
+    pub ws_heartbeat: Option<u64>,
+    /// Seconds a silent websocket is kept open before it is closed
+    #[arg(long, env = "VADERBOARD_WS_TIMEOUT")]
+    pub ws_timeout: Option<u64>,
+    /// Max no of open websockets
+    #[arg(long, env = "VADERBOARD_WS_MAX_CONNECTIONS")]
+    pub ws_max_connections: Option<usize>,
+    /// Max no of open websockets per client address
+    #[arg(long, env = "VADERBOARD_WS_MAX_PER_IP")]
+    pub ws_max_per_ip: Option<usize>,
+    /// Log level [off,error,warn,info,debug,trace]
+    #[arg(long, env = "VADERBOARD_LOG_LEVEL")]
+    pub log_level: Option<String>,
+    /// Log line format
+    #[arg(long, value_enum, env = "VADERBOARD_LOG_FORMAT")]
+    pub log_format: Option<LogFormat>,
+    /// Directory to keep database backups in
+    #[arg(long, env = "VADERBOARD_BACKUP_DIR")]
+    pub backup_dir: Option<PathBuf>,
+    /// Take a backup every n seconds while serving [default : disabled]
+    #[arg(long, env = "VADERBOARD_BACKUP_INTERVAL")]
+    pub backup_interval: Option<u64>,
+    /// No of backups to keep in the backup directory
+    #[arg(long, env = "VADERBOARD_BACKUP_RETENTION")]
+    pub backup_retention: Option<usize>,
+    /// Days deleted events/teams/users stay in the trash before a purge removes them
+    #[arg(long, env = "VADERBOARD_TRASH_RETENTION_DAYS")]
+    pub trash_retention_days: Option<u32>,
+    /// Days audit log entries are kept before the daily prune removes them [0 : keep forever]
+    #[arg(long, env = "VADERBOARD_AUDIT_RETENTION_DAYS")]
+    pub audit_retention_days: Option<u32>,
+    /// Secret signing the check-in QR tokens [default : tokens disabled]
+    #[arg(long, env = "VADERBOARD_CHECKIN_SECRET", hide_env_values = true)]
+    pub checkin_secret: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct VaderConfig {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub session: SessionConfig,
+    pub cors: CorsConfig,
+    pub vaderboard: VboardConfig,
+    pub websocket: WsConfig,
+    pub log: LogConfig,
+    pub admin: AdminConfig,
+    pub backup: BackupConfig,
+    pub trash: TrashConfig,
+    pub audit: AuditConfig,
+    pub checkin: CheckinConfig,
+    pub registration: RegistrationConfig,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub host: String,
+    pub port: u16,
+    pub workers: Option<usize>,
+    pub dist_dir: PathBuf,
+}
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            host: "0.0.0.0".to_string(),
+            port: 8080,
+            workers: None,
+            dist_dir: PathBuf::from("dist"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    pub url: String,
+    pub auto_migrate: bool,
+}
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            url: "sqlite:vaderboard.db".to_string(),
+            auto_migrate: true,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct SessionConfig {
+    // random key is generated on each start if not set : )
+    pub key: Option<String>,
+    pub cookie_secure: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct CorsConfig {
+    pub allowed_origins: Vec<String>,
+}
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: vec!["*".to_string()],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct VboardConfig {
+    pub count: u32,
+    // max-age of the http VaderBoard/search responses , 0 to always revalidate
+    pub cache_secs: u32,
+}
+impl Default for VboardConfig {
+    fn default() -> Self {
+        Self {
+            count: 10,
+            cache_secs: 2,
+        }
+    }
+}
+
+// server pings every `heartbeat_secs` , clients quiet for `timeout_secs` are dropped : )
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct WsConfig {
+    pub heartbeat_secs: u64,
+    pub timeout_secs: u64,
+    pub max_connections: usize,
+    pub max_per_ip: usize,
+    // largest {count} a websocket can ask for
+    pub max_count: u32,
+    pub queries_per_sec: u32,
+    pub debounce_ms: u64,
+    // take the client address from X-Forwarded-For/Forwarded , only behind a proxy
+    pub trust_forwarded: bool,
+}
+impl Default for WsConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_secs: 5,
+            timeout_secs: 15,
+            max_connections: 1000,
+            max_per_ip: 20,
+            max_count: 100,
+            queries_per_sec: 5,
+            debounce_ms: 150,
+            trust_forwarded: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    pub level: String,
+    pub format: LogFormat,
+}
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            format: LogFormat::Text,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// `[time level target] message key=value ..`
+    Text,
+    /// One json object per line , fields included
+    Json,
+}
+
+// initial admin , seeded on startup if not present : )
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct AdminConfig {
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct BackupConfig {
+    pub dir: PathBuf,
+    // scheduled backups are disabled if not set : )
+    pub interval_secs: Option<u64>,
+    pub retention: usize,
+}
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("backups"),
+            interval_secs: None,
+            retention: 7,
+        }
+    }
+}
+
+// deleted rows are kept this long , `trash purge` removes the older ones : )
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct TrashConfig {
+    pub retention_days: u32,
+}
+impl Default for TrashConfig {
+    fn default() -> Self {
+        Self { retention_days: 30 }
+    }
+}
+
+// audit entries older than this are pruned once a day , 0 keeps them all : )
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuditConfig {
+    pub retention_days: u32,
+}
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self { retention_days: 90 }
+    }
+}
+
+// public sign ups (registering and joining) per client address , in a fixed window : )
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct RegistrationConfig {
+    pub max_per_ip: u32,
+    pub window_secs: u64,
+}
+impl Default for RegistrationConfig {
+    fn default() -> Self {
+        Self {
+            max_per_ip: 20,
+            window_secs: 600,
+        }
+    }
+}
+
+// check-in QR tokens are signed with the secret , disabled if not set : )
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct CheckinConfig {
+    pub secret: Option<String>,
+}
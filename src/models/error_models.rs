@@ -1,8 +1,17 @@
 use std::error::Error;
 use std::fmt::Display;
 
+use actix::MailboxError;
 use actix_web::error::BlockingError;
+use actix_web::http::StatusCode;
+use actix_web::{HttpRequest, HttpResponse};
 use bcrypt::BcryptError;
+use serde::Serialize;
+use sqlx::error::ErrorKind;
+use sqlx::migrate::MigrateError;
+
+use super::membership_models::TeamCapacity;
+use super::validation_models::FieldError;
 
 #[derive(Debug)]
 pub enum VaderError<'a> {
@@ -14,11 +23,43 @@ pub enum VaderError<'a> {
     SqlxFieldError(&'a str),
     EventNotFound(&'a str),
     TeamNotFound(&'a str),
-    TeamSizeMismatch(&'a str),
+    TeamSizeMismatch(TeamCapacity),
     UserNotFound(&'a str),
+    RegistrationNotFound(&'a str),
+    RegistrationClosed(&'a str),
+    NameTaken(&'a str),
+    AlreadyInTeam(&'a str),
+    AdminNotFound(&'a str),
+    AdminRequired(&'a str),
     AdminHashError(BcryptError),
+    Unauthorized(&'a str),
+    TooManyRequests(&'a str),
+    InvalidRequest(String),
+    ValidationError(Vec<FieldError>),
+    SessionError(String),
     BlockingOpError(BlockingError),
+    ActorError(MailboxError),
     SerdeJsonError(serde_json::Error),
+    ConfigError(String),
+    MigrateError(MigrateError),
+    ImportError(String),
+    ExportError(String),
+    BackupError(String),
+    BackupIoError(String),
+    MetricsError(String),
+}
+
+// json body of every error response , frontend branches on `code` : )
+#[derive(Serialize)]
+pub struct ErrorResponse<'a> {
+    pub code: &'a str,
+    pub message: &'a str,
+    pub details: String,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    pub fields: &'a [FieldError],
+    // team_size_mismatch , so the ui can show the counts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<&'a TeamCapacity>,
 }
 
 impl<'a> From<sqlx::Error> for VaderError<'a> {
@@ -32,6 +73,11 @@ impl<'a> From<BcryptError> for VaderError<'a> {
     }
 }
 
+impl<'a> From<MailboxError> for VaderError<'a> {
+    fn from(value: MailboxError) -> Self {
+        Self::ActorError(value)
+    }
+}
 impl<'a> From<BlockingError> for VaderError<'a> {
     fn from(value: BlockingError) -> Self {
         Self::BlockingOpError(value)
@@ -42,58 +88,236 @@ impl<'a> From<serde_json::Error> for VaderError<'a> {
         Self::SerdeJsonError(value)
     }
 }
+impl<'a> From<MigrateError> for VaderError<'a> {
+    fn from(value: MigrateError) -> Self {
+        Self::MigrateError(value)
+    }
+}
 
 impl<'a> Error for VaderError<'a> {}
 
-impl<'a> actix_web::ResponseError for VaderError<'a> {}
+impl<'a> VaderError<'a> {
+    // machine readable , stable across releases
+    pub fn code(&self) -> &'static str {
+        match self {
+            VaderError::EventNotActive(_) => "event_not_active",
+            VaderError::EventEnded(_) => "event_ended",
+            VaderError::EventActive(_) => "event_active",
+            VaderError::EventTypeMismatch(_) => "event_type_mismatch",
+            VaderError::SqlxError(e) => match e {
+                sqlx::Error::RowNotFound => "not_found",
+                sqlx::Error::Database(db_err) => match db_err.kind() {
+                    ErrorKind::UniqueViolation => "unique_violation",
+                    ErrorKind::ForeignKeyViolation => "foreign_key_violation",
+                    ErrorKind::NotNullViolation | ErrorKind::CheckViolation => {
+                        "constraint_violation"
+                    }
+                    // RAISE(ABORT) in triggers , ie: team size exceeded
+                    _ if db_err.code().as_deref() == Some(SQLITE_CONSTRAINT_TRIGGER) => {
+                        "constraint_violation"
+                    }
+                    _ => "database_error",
+                },
+                _ => "database_error",
+            },
+            VaderError::SqlxFieldError(_) => "database_error",
+            VaderError::EventNotFound(_) => "event_not_found",
+            VaderError::TeamNotFound(_) => "team_not_found",
+            VaderError::TeamSizeMismatch(_) => "team_size_mismatch",
+            VaderError::UserNotFound(_) => "user_not_found",
+            VaderError::RegistrationNotFound(_) => "registration_not_found",
+            VaderError::RegistrationClosed(_) => "registration_closed",
+            VaderError::NameTaken(_) => "name_taken",
+            VaderError::AlreadyInTeam(_) => "already_in_team",
+            VaderError::AdminNotFound(_) => "admin_not_found",
+            VaderError::AdminRequired(_) => "admin_required",
+            VaderError::AdminHashError(_) => "admin_hash_error",
+            VaderError::Unauthorized(_) => "unauthorized",
+            VaderError::TooManyRequests(_) => "rate_limited",
+            VaderError::InvalidRequest(_) => "invalid_request",
+            VaderError::ValidationError(_) => "validation_error",
+            VaderError::SessionError(_) => "session_error",
+            VaderError::BlockingOpError(_) => "internal_error",
+            VaderError::ActorError(_) => "internal_error",
+            VaderError::SerdeJsonError(_) => "internal_error",
+            VaderError::ConfigError(_) => "config_error",
+            VaderError::MigrateError(_) => "migrate_error",
+            VaderError::ImportError(_) => "import_error",
+            VaderError::ExportError(_) => "export_error",
+            VaderError::BackupError(_) => "backup_error",
+            VaderError::BackupIoError(_) => "backup_io_error",
+            VaderError::MetricsError(_) => "internal_error",
+        }
+    }
 
-impl<'a> Display for VaderError<'a> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn title(&self) -> &'static str {
         match self {
-            VaderError::EventNotActive(e) => write!(
-                f,
-                "No Event is Active to perform operation.\n[error] :  {}",
-                e
-            ),
-            VaderError::SqlxError(e) => write!(f, "Sqlx Error.\n[error] : {}", e),
-            VaderError::SqlxFieldError(e) => write!(f, "Sqlx Field DecodeError.\n[error] : {}", e),
-            VaderError::EventTypeMismatch(e) => write!(
-                f,
-                "Operation Cannot be performed on this Event Type.\n[error] : {}",
-                e
-            ),
-            VaderError::EventActive(e) => write!(
-                f,
-                "Operation Cannot be performed on Active Event.\n[error] : {}",
-                e
-            ),
-            VaderError::EventEnded(e) => write!(
-                f,
-                "Operation Cannot be performed on Event that Ended.\n[error] : {}",
-                e
+            VaderError::EventNotActive(_) => "No Event is Active to perform operation",
+            VaderError::SqlxError(sqlx::Error::RowNotFound) => "Not Found",
+            VaderError::SqlxError(_) => "Sqlx Error",
+            VaderError::SqlxFieldError(_) => "Sqlx Field DecodeError",
+            VaderError::EventTypeMismatch(_) => "Operation Cannot be performed on this Event Type",
+            VaderError::EventActive(_) => "Operation Cannot be performed on Active Event",
+            VaderError::EventEnded(_) => "Operation Cannot be performed on Event that Ended",
+            VaderError::EventNotFound(_) => "Event not Found",
+            VaderError::TeamNotFound(_) => "Team not Found",
+            VaderError::TeamSizeMismatch(_) => "Team Size mismatch",
+            VaderError::UserNotFound(_) => "User not Found",
+            VaderError::RegistrationNotFound(_) => "Registration not Found",
+            VaderError::RegistrationClosed(_) => "Registration Closed",
+            VaderError::NameTaken(_) => "Name already in use",
+            VaderError::AlreadyInTeam(_) => "User already in a team",
+            VaderError::AdminNotFound(_) => "Admin not Found",
+            VaderError::AdminRequired(_) => "Admin Required",
+            VaderError::AdminHashError(_) => "Admin Hash Error",
+            VaderError::Unauthorized(_) => "Unauthorized",
+            VaderError::TooManyRequests(_) => "Too many requests",
+            VaderError::InvalidRequest(_) => "Invalid Request",
+            VaderError::ValidationError(_) => "Validation failed",
+            VaderError::SessionError(_) => "Unable to get Admin Session",
+            VaderError::BlockingOpError(_) => "Error in performing blocking operation",
+            VaderError::ActorError(_) => "Error in reaching server actor",
+            VaderError::SerdeJsonError(_) => "Error in serializing object",
+            VaderError::ConfigError(_) => "Invalid Configuration",
+            VaderError::MigrateError(_) => "Database Migration Error",
+            VaderError::ImportError(_) => "Import Error",
+            VaderError::ExportError(_) => "Export Error",
+            VaderError::BackupError(_) => "Backup Error",
+            VaderError::BackupIoError(_) => "Unable to read/write backup files",
+            VaderError::MetricsError(_) => "Error in encoding metrics",
+        }
+    }
+
+    fn details(&self) -> String {
+        match self {
+            VaderError::EventNotActive(e)
+            | VaderError::EventEnded(e)
+            | VaderError::EventActive(e)
+            | VaderError::EventTypeMismatch(e)
+            | VaderError::SqlxFieldError(e)
+            | VaderError::EventNotFound(e)
+            | VaderError::TeamNotFound(e)
+            | VaderError::UserNotFound(e)
+            | VaderError::RegistrationNotFound(e)
+            | VaderError::RegistrationClosed(e)
+            | VaderError::NameTaken(e)
+            | VaderError::AlreadyInTeam(e)
+            | VaderError::AdminNotFound(e)
+            | VaderError::AdminRequired(e)
+            | VaderError::Unauthorized(e)
+            | VaderError::TooManyRequests(e) => e.to_string(),
+            VaderError::InvalidRequest(e)
+            | VaderError::SessionError(e)
+            | VaderError::ConfigError(e)
+            | VaderError::ImportError(e)
+            | VaderError::ExportError(e)
+            | VaderError::BackupError(e)
+            | VaderError::BackupIoError(e)
+            | VaderError::MetricsError(e) => e.clone(),
+            VaderError::ValidationError(fields) => fields
+                .iter()
+                .map(|f| match f.field.is_empty() {
+                    true => f.message.clone(),
+                    false => format!("{} : {}", f.field, f.message),
+                })
+                .collect::<Vec<String>>()
+                .join("; "),
+            VaderError::TeamSizeMismatch(c) => format!(
+                "Team has {} of {} members , cannot add {} more",
+                c.current, c.max, c.adding
             ),
-            VaderError::EventNotFound(e) => write!(f, "Event not Found.\n[error] : {}", e),
-            VaderError::TeamNotFound(e) => write!(f, "Team not Found.\n[error] : {}", e),
-            VaderError::TeamSizeMismatch(e) => write!(f, "Team Size mismatch.\n[error] : {}", e),
+            VaderError::SqlxError(e) => e.to_string(),
+            VaderError::AdminHashError(e) => e.to_string(),
+            VaderError::BlockingOpError(e) => e.to_string(),
+            VaderError::ActorError(e) => e.to_string(),
+            VaderError::SerdeJsonError(e) => e.to_string(),
+            VaderError::MigrateError(e) => e.to_string(),
+        }
+    }
+}
 
-            VaderError::UserNotFound(e) => write!(f, "User not Found.\n[error] : {}", e),
-            VaderError::AdminHashError(e) => {
-                write!(f, "Admin Hash Error.\n[error] : {}", e)
-            }
-            VaderError::BlockingOpError(e) => {
-                write!(
-                    f,
-                    "Error in performing blocking operation.\n[error] : {}",
-                    e
-                )
-            }
-            VaderError::SerdeJsonError(e) => {
-                write!(
-                    f,
-                    "Error in serializing object.\n[error] : {}",
-                    e
-                )
-            }
+// extended result code of a failed trigger RAISE(ABORT,..)
+const SQLITE_CONSTRAINT_TRIGGER: &str = "1811";
+
+impl<'a> actix_web::ResponseError for VaderError<'a> {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            VaderError::EventNotFound(_)
+            | VaderError::TeamNotFound(_)
+            | VaderError::UserNotFound(_)
+            | VaderError::AdminNotFound(_)
+            | VaderError::RegistrationNotFound(_) => StatusCode::NOT_FOUND,
+            VaderError::EventNotActive(_)
+            | VaderError::EventEnded(_)
+            | VaderError::EventActive(_)
+            | VaderError::TeamSizeMismatch(_)
+            | VaderError::RegistrationClosed(_)
+            | VaderError::NameTaken(_)
+            | VaderError::AlreadyInTeam(_)
+            | VaderError::AdminRequired(_) => StatusCode::CONFLICT,
+            VaderError::SqlxError(sqlx::Error::RowNotFound) => StatusCode::NOT_FOUND,
+            VaderError::SqlxError(sqlx::Error::Database(db_err)) => match db_err.kind() {
+                ErrorKind::UniqueViolation
+                | ErrorKind::ForeignKeyViolation
+                | ErrorKind::NotNullViolation
+                | ErrorKind::CheckViolation => StatusCode::CONFLICT,
+                _ if db_err.code().as_deref() == Some(SQLITE_CONSTRAINT_TRIGGER) => {
+                    StatusCode::CONFLICT
+                }
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            },
+            VaderError::SqlxError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            VaderError::EventTypeMismatch(_)
+            | VaderError::InvalidRequest(_)
+            | VaderError::ImportError(_)
+            | VaderError::BackupError(_) => StatusCode::BAD_REQUEST,
+            VaderError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            VaderError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            VaderError::ValidationError(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            VaderError::SqlxFieldError(_)
+            | VaderError::AdminHashError(_)
+            | VaderError::SessionError(_)
+            | VaderError::BlockingOpError(_)
+            | VaderError::ActorError(_)
+            | VaderError::SerdeJsonError(_)
+            | VaderError::ConfigError(_)
+            | VaderError::MigrateError(_)
+            | VaderError::ExportError(_)
+            | VaderError::BackupIoError(_)
+            | VaderError::MetricsError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        if status.is_server_error() {
+            log::error!("{}", self);
+        } else {
+            log::debug!("{}", self);
+        }
+        HttpResponse::build(status).json(ErrorResponse {
+            code: self.code(),
+            message: self.title(),
+            details: self.details(),
+            fields: match self {
+                VaderError::ValidationError(fields) => fields,
+                _ => &[],
+            },
+            capacity: match self {
+                VaderError::TeamSizeMismatch(capacity) => Some(capacity),
+                _ => None,
+            },
+        })
+    }
+}
+
+// malformed json/query/path extractors get the same json error body : )
+pub fn invalid_request<E: Display>(err: E, _req: &HttpRequest) -> actix_web::Error {
+    VaderError::InvalidRequest(err.to_string()).into()
+}
+
+impl<'a> Display for VaderError<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.\n[error] : {}", self.title(), self.details())
+    }
 }
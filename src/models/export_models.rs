@@ -0,0 +1,46 @@
+use std::borrow::Cow;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::query_models::EventInfo;
+
+#[derive(Deserialize, Clone, Copy, Default, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    #[default]
+    Json,
+    Html,
+}
+
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+// full ranking of an event , not limited by vb_count : )
+#[derive(Serialize)]
+pub struct EventExport<'a> {
+    pub event: EventInfo<'a>,
+    pub ranking: Vec<ExportEntry<'a>>,
+}
+
+// tied scores share a rank (1,1,3) , members empty for user events
+#[derive(Serialize)]
+pub struct ExportEntry<'a> {
+    pub rank: usize,
+    pub id: Uuid,
+    pub name: Cow<'a, str>,
+    pub score: i64,
+    pub logo: Option<Cow<'a, str>>,
+    pub members: Vec<ExportMember<'a>>,
+}
+
+#[derive(Serialize)]
+pub struct ExportMember<'a> {
+    pub id: Uuid,
+    pub name: Cow<'a, str>,
+}
@@ -0,0 +1,25 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum FtsOp {
+    /// Rebuild the fts tables from the base tables
+    Rebuild,
+    /// Merge the fts index segments , faster searches after lots of updates
+    Optimize,
+    /// Only check the fts tables against the base tables
+    Check,
+}
+
+// consistency of one fts table after the op , `rows` are base table rows and
+// `indexed` the rows in the fts index : )
+#[derive(Serialize)]
+pub struct FtsReport {
+    pub table: &'static str,
+    pub rows: i64,
+    pub indexed: i64,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+}
@@ -0,0 +1,35 @@
+use std::time::Duration;
+
+use actix::Message;
+use serde::Serialize;
+
+// how long /readyz waits on a server actor before calling it stuck
+pub const ACTOR_PING_TIMEOUT: Duration = Duration::from_secs(2);
+
+// answered straight from the actor's mailbox , a reply means it's alive : )
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct HealthPing;
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Fail,
+}
+
+#[derive(Serialize)]
+pub struct HealthCheck {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+}
+
+// `status` is fail if any of the checks failed
+#[derive(Serialize)]
+pub struct HealthReport {
+    pub status: CheckStatus,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub checks: Vec<HealthCheck>,
+}
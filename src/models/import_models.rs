@@ -0,0 +1,63 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::services::validation_services::{validate_logo, validate_name};
+
+#[derive(Deserialize, Serialize, Clone, Copy, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportFormat {
+    Csv,
+    Json,
+}
+
+// one row per team member , csv header : team,team_logo,user,user_logo
+// team only rows add an empty team , user only rows add a user : )
+#[derive(Deserialize, Serialize, Default, Validate)]
+pub struct ImportRow {
+    #[serde(default)]
+    #[validate(custom = "validate_name")]
+    pub team: Option<String>,
+    #[serde(default)]
+    #[validate(custom = "validate_logo")]
+    pub team_logo: Option<String>,
+    #[serde(default, alias = "member")]
+    #[validate(custom = "validate_name")]
+    pub user: Option<String>,
+    #[serde(default, alias = "member_logo")]
+    #[validate(custom = "validate_logo")]
+    pub user_logo: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ImportQuery {
+    #[serde(default)]
+    pub format: Option<ImportFormat>,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Serialize)]
+pub struct ImportEntry {
+    pub row: usize,
+    pub id: Uuid,
+    pub name: String,
+}
+
+#[derive(Serialize)]
+pub struct ImportRowError {
+    pub row: usize,
+    pub error: String,
+}
+
+// rows are numbered from 1 , excluding the csv header : )
+#[derive(Serialize, Default)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub committed: bool,
+    pub teams: Vec<ImportEntry>,
+    pub users: Vec<ImportEntry>,
+    pub members: usize,
+    pub errors: Vec<ImportRowError>,
+}
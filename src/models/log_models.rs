@@ -0,0 +1,11 @@
+// fields of the request being served , shared by the middlewares and the
+// handler so every line logged for a request carries them : )
+pub struct LogContext {
+    pub request_id: String,
+    // set by AdminOnlyGuard from the session
+    pub admin: Option<String>,
+}
+
+// echoed on every response , an incoming one (ie: from nginx) is kept
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+pub const REQUEST_ID_MAX_LEN: usize = 64;
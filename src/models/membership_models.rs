@@ -0,0 +1,41 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// members a team has , the team_size of its event and how many were being added
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct TeamCapacity {
+    pub current: u32,
+    pub max: u32,
+    pub adding: u32,
+}
+
+// a full team puts the user on its waitlist if `waitlist` is set , else fails
+// with team_size_mismatch
+#[derive(Deserialize)]
+pub struct JoinReq {
+    pub user_id: Uuid,
+    #[serde(default)]
+    pub waitlist: bool,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum JoinRes {
+    Member,
+    // 1 is the next one in
+    Waitlisted { position: u32 },
+}
+
+#[derive(Serialize)]
+pub struct WaitlistEntry {
+    pub position: u32,
+    pub user_id: Uuid,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+pub struct CaptainReq {
+    pub user_id: Uuid,
+}
@@ -0,0 +1,29 @@
+use prometheus::{
+    Histogram, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Registry,
+};
+
+// everything on /metrics , one global registry so actors can record too : )
+pub struct VaderMetrics {
+    pub registry: Registry,
+    // by method , route pattern (not path) and status
+    pub http_requests: IntCounterVec,
+    pub http_duration: HistogramVec,
+    // open websockets by actor : vboard_client , fts_query , cur_event_fts ,
+    // audit_client
+    pub ws_connections: IntGaugeVec,
+    pub sse_connections: IntGauge,
+    pub vb_broadcasts: IntCounter,
+    pub vb_broadcast_duration: Histogram,
+    pub score_updates: IntCounterVec,
+    // pool connections by state : idle , in_use , max
+    pub db_pool: IntGaugeVec,
+    // vaderboard , search , page and the scrape time ping
+    pub db_query_duration: HistogramVec,
+    pub login_failures: IntCounter,
+}
+
+// websocket actor kinds , the `actor` label of ws_connections
+pub const WS_VBOARD_CLIENT: &str = "vboard_client";
+pub const WS_FTS_QUERY: &str = "fts_query";
+pub const WS_CUR_EVENT_FTS: &str = "cur_event_fts";
+pub const WS_AUDIT_CLIENT: &str = "audit_client";
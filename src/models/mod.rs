@@ -1,5 +1,23 @@
+pub mod audit_models;
+pub mod backup_models;
+pub mod checkin_models;
+pub mod cli_models;
 pub mod command_models;
+pub mod config_models;
 pub mod error_models;
+pub mod export_models;
+pub mod fts_models;
+pub mod health_models;
+pub mod import_models;
+pub mod log_models;
+pub mod membership_models;
+pub mod metrics_models;
+pub mod page_models;
 pub mod query_models;
+pub mod registration_models;
+pub mod search_models;
+pub mod trash_models;
 pub mod v_models;
+pub mod validation_models;
 pub mod wrapper_models;
+pub mod ws_models;
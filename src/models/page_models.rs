@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::validation_models::{PAGE_LIMIT_MAX, SEARCH_OFFSET_MAX};
+
+pub const PAGE_LIMIT_DEFAULT: u32 = 50;
+
+#[derive(Deserialize, Serialize, Clone, Copy, Default, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum SortKey {
+    Name,
+    Score,
+    #[default]
+    CreatedAt,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+// ?sort=name|score|created_at&order=asc|desc&limit=..&cursor=.. , cursor is the
+// `next_cursor` of the previous page : )
+#[derive(Deserialize, Validate, Default)]
+pub struct PageQuery {
+    #[serde(default)]
+    pub sort: SortKey,
+    // names ascending , scores and dates descending by default
+    pub order: Option<SortOrder>,
+    #[validate(range(min = 1, max = "PAGE_LIMIT_MAX", message = "must be between 1 and 500"))]
+    pub limit: Option<u32>,
+    pub cursor: Option<String>,
+}
+
+// ?q=..&offset=..&count=.. for the http searches , same replies as the websockets : )
+#[derive(Deserialize, Validate, Default)]
+pub struct SearchQuery {
+    #[serde(default)]
+    pub q: String,
+    #[serde(default)]
+    #[validate(range(max = "SEARCH_OFFSET_MAX", message = "must be at most 1000"))]
+    pub offset: u32,
+    #[validate(range(min = 1, max = "PAGE_LIMIT_MAX", message = "must be between 1 and 500"))]
+    pub count: Option<u32>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum CursorValue {
+    Int(i64),
+    Text(String),
+}
+
+// position after the last row of a page , opaque to clients
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Cursor {
+    pub sort: SortKey,
+    pub order: SortOrder,
+    pub value: CursorValue,
+    pub id: Uuid,
+}
+
+#[derive(Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+// websocket search requests , plain text is still a new search : )
+#[derive(Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum FtsReq {
+    Search { query: String },
+    More,
+}
+
+// one websocket search reply , `more` when another page can be loaded
+#[derive(Serialize)]
+pub struct SearchPage<'a, T> {
+    pub query: &'a str,
+    pub offset: u32,
+    pub items: Vec<T>,
+    pub more: bool,
+}
+
+// websocket search state , plain text starts a new search and
+// {"op":"more"} asks for the page after the last one : )
+#[derive(Default)]
+pub struct FtsPager {
+    pub query: String,
+    pub offset: u32,
+}
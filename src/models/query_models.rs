@@ -1,8 +1,9 @@
 use core::hash::Hash;
 use std::borrow::Cow;
-use std::collections::HashSet;
+use std::collections::{HashSet, VecDeque};
 use std::marker::PhantomData;
 use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use actix::{Actor, Addr, AsyncContext, Message};
 use actix_web::{web, Either};
@@ -10,10 +11,16 @@ use actix_web_actors::ws;
 use serde::{Deserialize, Serialize};
 use serde_repr::Serialize_repr;
 use sqlx::{Pool, Sqlite, SqlitePool};
+use tokio::sync::mpsc;
 use uuid::Uuid;
 
-use super::v_models::{AppState, Player, Team, User};
+use super::metrics_models::WS_VBOARD_CLIENT;
+use super::page_models::FtsPager;
+use super::v_models::{AppState, Player, ScoreLimits, Team, User};
+use super::ws_models::WsConn;
+use crate::services::metrics_services::ws_gauge;
 use crate::services::query_services::Queriable;
+use crate::services::ws_services::start_heartbeat;
 
 // #[derive(Serialize)]
 // pub struct EventQuery<'a, T: Player<'a>> {
@@ -31,6 +38,8 @@ pub struct EventQuery<'a> {
     pub name: Cow<'a, str>,
     pub logo: Option<Cow<'a, str>>,
     pub event_type: EventType,
+    pub score_limits: ScoreLimits,
+    pub exclude_unchecked: bool,
     pub state: EventQueryState,
 }
 
@@ -39,6 +48,8 @@ pub struct EventQueryBuilder<'a> {
     pub name: Cow<'a, str>,
     pub logo: Option<Cow<'a, str>>,
     pub event_type: EventType,
+    pub score_limits: ScoreLimits,
+    pub exclude_unchecked: bool,
 }
 impl<'a> EventQueryBuilder<'a> {
     pub fn build_with_state(self, state: EventQueryState) -> EventQuery<'a> {
@@ -47,18 +58,30 @@ impl<'a> EventQueryBuilder<'a> {
             name: self.name,
             logo: self.logo,
             event_type: self.event_type,
+            score_limits: self.score_limits,
+            exclude_unchecked: self.exclude_unchecked,
             state,
         }
     }
 }
 
-#[derive(Serialize_repr)]
+#[derive(Serialize_repr, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
 pub enum EventQueryState {
     Added,
     Start,
     Stop,
 }
+impl EventQueryState {
+    // state as stored in events.state column : )
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Added => "added",
+            Self::Start => "started",
+            Self::Stop => "stopped",
+        }
+    }
+}
 
 #[derive(Serialize, Deserialize)]
 pub enum EventType {
@@ -90,16 +113,20 @@ pub struct TeamInfo<'a> {
 pub struct FtsQuery<'a, T: Queriable> {
     pub db_pool: Arc<SqlitePool>,
     pub count: u32,
+    pub pager: FtsPager,
+    pub conn: WsConn,
     type_marker: PhantomData<&'a T>,
 }
 impl<'a, T> FtsQuery<'a, T>
 where
     T: Queriable,
 {
-    pub fn new(count: u32, db_pool: Arc<SqlitePool>) -> Self {
+    pub fn new(count: u32, db_pool: Arc<SqlitePool>, conn: WsConn) -> Self {
         Self {
             db_pool,
             count,
+            pager: FtsPager::default(),
+            conn,
             type_marker: PhantomData::<&'a T>,
         }
     }
@@ -145,6 +172,11 @@ pub struct CurFtsDisconnect<'a: 'static>(pub CurEventFtsWrapper<'a>);
 #[rtype(result = "()")]
 pub struct CurFtsConnect<'a: 'static>(pub CurEventFtsWrapper<'a>);
 
+// no of registered current event searches
+#[derive(Message)]
+#[rtype(result = "usize")]
+pub struct CurFtsCount;
+
 #[derive(Clone, Copy)]
 pub enum TeamFtsOpt {
     TeamInfo,
@@ -156,6 +188,7 @@ pub struct CurFtsBuilder<'a, P: Player<'a>> {
     db_pool: Arc<SqlitePool>,
     type_marker: PhantomData<&'a P>,
     count: u32,
+    conn: WsConn,
 }
 
 pub struct CurFtsTeamBuilder {
@@ -164,6 +197,7 @@ pub struct CurFtsTeamBuilder {
     db_pool: Arc<SqlitePool>,
     team_opt: TeamFtsOpt,
     count: u32,
+    conn: WsConn,
 }
 
 pub trait CurEventFtsMarker: Queriable {}
@@ -180,6 +214,8 @@ where
     pub db_pool: Arc<SqlitePool>,
     pub count: u32,
     pub team_opt: Option<TeamFtsOpt>,
+    pub pager: FtsPager,
+    pub conn: WsConn,
     type_marker: PhantomData<&'a T>,
 }
 
@@ -192,12 +228,14 @@ where
         srv_addr: Arc<Addr<CurFtsServer>>,
         count: u32,
         db_pool: Arc<SqlitePool>,
+        conn: WsConn,
     ) -> Self {
         CurFtsBuilder {
             event_id,
             db_pool,
             srv_addr,
             count,
+            conn,
             type_marker: PhantomData::<&'a P>,
         }
     }
@@ -215,6 +253,8 @@ where
             srv_addr: self.srv_addr,
             team_opt: None,
             count: self.count,
+            pager: FtsPager::default(),
+            conn: self.conn,
             type_marker: PhantomData::<&'a User>,
         }
     }
@@ -227,6 +267,7 @@ impl<'a> CurFtsBuilder<'a, Team<'a>> {
             db_pool: self.db_pool,
             count: self.count,
             team_opt: TeamFtsOpt::TeamInfo,
+            conn: self.conn,
         }
     }
     pub fn rem_user_fts(self) -> CurFtsTeamBuilder {
@@ -236,6 +277,7 @@ impl<'a> CurFtsBuilder<'a, Team<'a>> {
             db_pool: self.db_pool,
             count: self.count,
             team_opt: TeamFtsOpt::RemUserInfo,
+            conn: self.conn,
         }
     }
 }
@@ -251,14 +293,65 @@ where
             srv_addr: self.srv_addr,
             count: self.count,
             team_opt: Some(self.team_opt),
+            pager: FtsPager::default(),
+            conn: self.conn,
             type_marker: PhantomData::<&'a TeamInfo>,
         }
     }
 }
 
+// server sent events : keepalive comment interval , reconnect delay hint ,
+// events kept for Last-Event-ID replays and frames buffered per client : )
+pub const SSE_KEEPALIVE: Duration = Duration::from_secs(15);
+pub const SSE_RETRY_MS: u32 = 3000;
+pub const SSE_HISTORY: usize = 32;
+// a fresh stream takes the retry: frame and a whole replay (or a snapshot) at once
+pub const SSE_BUFFER: usize = SSE_HISTORY + 2;
+
 pub enum TransferType {
     Broadcast,
     Unicast(Addr<VboardClient>),
+    // current VaderBoard for a new event stream
+    SseUnicast(mpsc::Sender<web::Bytes>),
+}
+
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Lifecycle {
+    Started,
+    Ended,
+}
+impl Lifecycle {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Started => "started",
+            Self::Ended => "ended",
+        }
+    }
+}
+
+// event started/stopped , only the event streams get these
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct VboardLifecycle(pub Lifecycle, pub Uuid);
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct SseConnect {
+    pub tx: mpsc::Sender<web::Bytes>,
+    pub last_event_id: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub struct LifecycleData {
+    pub event_id: Uuid,
+    pub state: Lifecycle,
+}
+
+pub struct SseEvent {
+    pub id: u64,
+    pub kind: &'static str,
+    pub data: Cow<'static, str>,
 }
 
 #[derive(Message)]
@@ -277,15 +370,22 @@ pub struct VbDisconnect(pub Addr<VboardClient>);
 #[rtype(result = "()")]
 pub struct VbConnect(pub Addr<VboardClient>);
 
+// (websockets , event streams) registered with VboardSrv
+#[derive(Message)]
+#[rtype(result = "(usize, usize)")]
+pub struct VbCount;
+
 pub struct VboardClient {
     pub srv_addr: Arc<Addr<VboardSrv>>,
     pub addr: Option<Addr<Self>>,
+    pub conn: WsConn,
 }
 impl VboardClient {
-    pub fn new(srv_addr: web::Data<Addr<VboardSrv>>) -> Self {
+    pub fn new(srv_addr: web::Data<Addr<VboardSrv>>, conn: WsConn) -> Self {
         Self {
             srv_addr: srv_addr.into_inner(),
             addr: None,
+            conn,
         }
     }
 }
@@ -295,9 +395,12 @@ impl Actor for VboardClient {
     fn started(&mut self, ctx: &mut Self::Context) {
         let addr = ctx.address();
         self.addr = Some(addr.clone());
+        ws_gauge(WS_VBOARD_CLIENT).inc();
+        start_heartbeat(self, ctx);
         self.srv_addr.do_send(VbConnect(addr))
     }
     fn stopped(&mut self, _ctx: &mut Self::Context) {
+        ws_gauge(WS_VBOARD_CLIENT).dec();
         if let Some(addr) = &self.addr {
             self.srv_addr.do_send(VbDisconnect(addr.clone()))
         }
@@ -306,13 +409,25 @@ impl Actor for VboardClient {
 
 pub struct VboardSrv {
     pub vb_addr: HashSet<Addr<VboardClient>>,
+    // event stream subscribers , dropped once their stream is closed or lagging
+    pub sse_clients: Vec<mpsc::Sender<web::Bytes>>,
+    pub sse_history: VecDeque<SseEvent>,
+    pub sse_seq: u64,
     pub app_state: web::Data<AppState>,
     pub db_pool: Pool<Sqlite>,
 }
 impl VboardSrv {
     pub fn new(app_state: web::Data<AppState>, db_pool: Pool<Sqlite>) -> Self {
+        //ids start from the clock , so ids from before a restart are never replayed
+        let sse_seq = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
         VboardSrv {
             vb_addr: HashSet::new(),
+            sse_clients: Vec::new(),
+            sse_history: VecDeque::with_capacity(SSE_HISTORY),
+            sse_seq,
             app_state,
             db_pool,
         }
@@ -320,4 +435,9 @@ impl VboardSrv {
 }
 impl Actor for VboardSrv {
     type Context = actix::Context<Self>;
+    fn started(&mut self, ctx: &mut Self::Context) {
+        ctx.run_interval(SSE_KEEPALIVE, |srv, _ctx| {
+            srv.sse_send(web::Bytes::from_static(b": keepalive\n\n"))
+        });
+    }
 }
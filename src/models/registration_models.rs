@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::command_models::ContestantInfo;
+use super::config_models::RegistrationConfig;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum RegistrationStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+// public sign ups counted per client address , shared by register and join
+pub struct RegistrationGate {
+    pub config: RegistrationConfig,
+    pub trust_forwarded: bool,
+    // start of the window and sign ups in it
+    pub attempts: Mutex<HashMap<IpAddr, (Instant, u32)>>,
+}
+
+// whether the current event takes registrations , for the public sign up page
+#[derive(Serialize)]
+pub struct RegistrationState {
+    pub event_id: Uuid,
+    pub open: bool,
+    pub team_size: Option<u32>,
+}
+
+// teammates join with the invite_code , the team waits for an admin till then
+#[derive(Serialize)]
+pub struct RegistrationRes {
+    pub id: Uuid,
+    pub team_id: Uuid,
+    pub invite_code: String,
+    pub status: RegistrationStatus,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct JoinRegistration<'a> {
+    #[validate(length(min = 1, max = 32, message = "must be 1 to 32 characters"))]
+    pub invite_code: String,
+    #[validate]
+    pub member: ContestantInfo<'a>,
+}
+
+#[derive(Serialize)]
+pub struct Registration {
+    pub id: Uuid,
+    pub team_id: Uuid,
+    pub team_name: String,
+    pub members: u32,
+    pub invite_code: String,
+    pub status: RegistrationStatus,
+    pub created_at: DateTime<Utc>,
+    pub decided_at: Option<DateTime<Utc>>,
+}
+
+// ?status=pending|approved|rejected , all of them if not set
+#[derive(Deserialize)]
+pub struct RegistrationQuery {
+    pub status: Option<RegistrationStatus>,
+}
+
+#[derive(Deserialize)]
+pub struct RegistrationToggle {
+    pub open: bool,
+}
@@ -0,0 +1,50 @@
+use serde::Serialize;
+
+// share of the query trigrams a name must contain to count as a typo match
+pub const FUZZY_MIN_SIMILARITY: f64 = 0.5;
+// trigram candidates fetched per wanted result , filtered by similarity after
+pub const FUZZY_CANDIDATES: u32 = 4;
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchKind {
+    Name,
+    Member,
+    Fuzzy,
+}
+
+// a search result , the fields of the item plus where the query matched : )
+#[derive(Serialize)]
+pub struct SearchHit<T> {
+    #[serde(flatten)]
+    pub item: T,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matched: Option<MatchKind>,
+    // html escaped name (member name on member matches) with hits in <mark>
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub highlight: Option<String>,
+}
+
+impl<T> SearchHit<T> {
+    // plain listing , no search term
+    pub fn plain(item: T) -> Self {
+        Self {
+            item,
+            matched: None,
+            highlight: None,
+        }
+    }
+}
+
+// where a search runs , `alias` names the content table in `select`/`joins`/`filter`
+// and `filter` binds the event id as ?3 : )
+pub struct SearchSpec {
+    pub select: &'static str,
+    pub table: &'static str,
+    pub alias: &'static str,
+    pub fts: &'static str,
+    pub trgm: &'static str,
+    pub joins: &'static str,
+    pub filter: &'static str,
+    pub event_id: Option<String>,
+}
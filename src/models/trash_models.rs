@@ -0,0 +1,42 @@
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum TrashKind {
+    Event,
+    Team,
+    User,
+}
+
+// a deleted event/team/user , restorable until `purge_after`
+#[derive(Serialize)]
+pub struct TrashItem {
+    pub kind: TrashKind,
+    pub id: Uuid,
+    pub name: String,
+    pub deleted_at: DateTime<Utc>,
+    pub purge_after: DateTime<Utc>,
+}
+
+// ?kind=event|team|user , everything in the trash if not set
+#[derive(Deserialize)]
+pub struct TrashQuery {
+    pub kind: Option<TrashKind>,
+}
+
+// ?older_than_days=.. , trash.retention_days if not set
+#[derive(Deserialize)]
+pub struct PurgeQuery {
+    pub older_than_days: Option<u32>,
+}
+
+#[derive(Serialize)]
+pub struct PurgeReport {
+    pub cutoff: DateTime<Utc>,
+    pub events: u64,
+    pub teams: u64,
+    pub users: u64,
+}
@@ -2,33 +2,44 @@ use std::borrow::Cow;
 use std::future::Future;
 use std::marker::PhantomData;
 use std::pin::Pin;
+use std::sync::atomic::AtomicU64;
 
-use bcrypt::verify;
+use bcrypt::{hash, verify, DEFAULT_COST};
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{FromRow, SqliteConnection, SqlitePool};
 use tokio::sync::Mutex;
 use uuid::Uuid;
+use validator::Validate;
 
 use super::error_models::VaderError;
 use super::wrapper_models::EventWrapper;
+use crate::services::validation_services::validate_score_limits;
 
-pub type AsyncDbRes<'a, T> = Pin<Box<dyn Future<Output = Result<T, VaderError<'a>>> + Send + 'a>>;
+pub type AsyncDbRes<'a, T> =
+    Pin<Box<dyn Future<Output = Result<T, VaderError<'static>>> + Send + 'a>>;
 
 pub struct AppState {
     pub current_event: Mutex<Option<EventWrapper<'static>>>,
     pub vb_count: u32,
+    // bumped by VboardSrv on every broadcast , the http VaderBoard etag : )
+    pub vb_version: AtomicU64,
 }
 impl AppState {
     pub fn new(vb_count: u32) -> Self {
         AppState {
             current_event: Mutex::new(None),
             vb_count,
+            vb_version: AtomicU64::new(0),
         }
     }
 }
 
+// inserts take a connection , pass a transaction to make them part of a bigger
+// command , ie: team + members + event link all or nothing : )
 pub trait Player<'a>: Send + Sync {
-    fn add_player(&'a self, db_pool: &'a SqlitePool) -> AsyncDbRes<'a, ()>;
+    fn add_player<'c>(&'c self, conn: &'c mut SqliteConnection) -> AsyncDbRes<'c, ()>;
     fn get_id(&self) -> Uuid;
     fn get_logo(&self) -> String;
 }
@@ -36,13 +47,16 @@ pub trait Player<'a>: Send + Sync {
 pub trait VaderEvent<'a> {
     type Participant: Player<'a>;
     fn add_event(&'a self, db_pool: &'a SqlitePool) -> AsyncDbRes<'a, ()>;
-    fn add_participant(
-        &'a self,
+    fn add_participant<'c>(
+        &'c self,
         participant: &Self::Participant,
-        db_pool: &'a SqlitePool,
-    ) -> AsyncDbRes<'a, ()>;
-    fn add_participant_from_id(&'a self, p_id: Uuid, db_pool: &'a SqlitePool)
-        -> AsyncDbRes<'a, ()>;
+        conn: &'c mut SqliteConnection,
+    ) -> AsyncDbRes<'c, ()>;
+    fn add_participant_from_id<'c>(
+        &'c self,
+        p_id: Uuid,
+        conn: &'c mut SqliteConnection,
+    ) -> AsyncDbRes<'c, ()>;
     fn get_logo(&self) -> String;
 }
 
@@ -55,6 +69,18 @@ impl EventState for NewEvent {}
 impl EventState for ActiveEvent {}
 impl EventState for EndEvent {}
 
+// per event score rules , None is unbounded : )
+#[derive(Serialize, Deserialize, Validate, Default, Clone, Copy, Debug)]
+#[validate(schema(function = "validate_score_limits"))]
+pub struct ScoreLimits {
+    #[serde(default)]
+    pub min_score: Option<i64>,
+    #[serde(default)]
+    pub max_score: Option<i64>,
+    #[serde(default)]
+    pub max_delta: Option<i64>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct Event<'a, T: Player<'a>, U: EventState = NewEvent> {
     pub id: Uuid,
@@ -62,6 +88,11 @@ pub struct Event<'a, T: Player<'a>, U: EventState = NewEvent> {
     #[serde(default)]
     pub logo: Option<Cow<'a, str>>,
     pub team_size: Option<u32>,
+    #[serde(default)]
+    pub score_limits: ScoreLimits,
+    // teams/users not checked in stay off the VaderBoard
+    #[serde(default)]
+    pub exclude_unchecked: bool,
     pub player_marker: PhantomData<&'a T>,
     pub state_marker: PhantomData<&'a U>,
 }
@@ -81,13 +112,77 @@ impl AdminInfo {
                 "SELECT username,password FROM admin_login WHERE username = ?",
             )
             .bind(&self.username)
-            .fetch_one(db_pool)
+            .fetch_optional(db_pool)
             .await?;
+            // unknown admin is a wrong password too , no hint which usernames exist
+            let Some(res) = res else {
+                return Ok(false);
+            };
             let verify_res =
                 actix_web::web::block(move || verify(self.password, &res.password)).await??;
             Ok(verify_res)
         })
     }
+    // returns false if the admin already exists : )
+    pub fn add_admin(self, db_pool: &SqlitePool) -> AsyncDbRes<'_, bool> {
+        Box::pin(async move {
+            // why no argon2 , veruthe : )
+            let AdminInfo { username, password } = self;
+            let passwd = actix_web::web::block(move || hash(password, DEFAULT_COST)).await??;
+            let res =
+                sqlx::query("INSERT OR IGNORE INTO admin_login (username,password) VALUES (?,?)")
+                    .bind(username)
+                    .bind(passwd)
+                    .execute(db_pool)
+                    .await?;
+            Ok(res.rows_affected().ne(&0))
+        })
+    }
+    pub fn update_passwd(self, db_pool: &SqlitePool) -> AsyncDbRes<'_, ()> {
+        Box::pin(async move {
+            let AdminInfo { username, password } = self;
+            let passwd = actix_web::web::block(move || hash(password, DEFAULT_COST)).await??;
+            let res = sqlx::query("UPDATE admin_login SET password = ? WHERE username = ?")
+                .bind(passwd)
+                .bind(username)
+                .execute(db_pool)
+                .await?;
+            if res.rows_affected().eq(&0) {
+                return Err(VaderError::AdminNotFound("No admin found"));
+            }
+            Ok(())
+        })
+    }
+    pub fn delete_admin<'b>(username: &'b str, db_pool: &'b SqlitePool) -> AsyncDbRes<'b, ()> {
+        Box::pin(async move {
+            let mut transaction = db_pool.begin().await?;
+            let res = sqlx::query("DELETE FROM admin_login WHERE username = ?")
+                .bind(username)
+                .execute(&mut *transaction)
+                .await?;
+            if res.rows_affected().eq(&0) {
+                return Err(VaderError::AdminNotFound("No admin found"));
+            }
+            // never lock everyone out : )
+            let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM admin_login")
+                .fetch_one(&mut *transaction)
+                .await?;
+            if count.eq(&0) {
+                return Err(VaderError::AdminRequired("Cannot remove the last admin"));
+            }
+            transaction.commit().await?;
+            Ok(())
+        })
+    }
+    pub fn get_all_admins(db_pool: &SqlitePool) -> AsyncDbRes<'_, Vec<String>> {
+        Box::pin(async move {
+            let admins: Vec<(String,)> =
+                sqlx::query_as("SELECT username FROM admin_login ORDER BY username")
+                    .fetch_all(db_pool)
+                    .await?;
+            Ok(admins.into_iter().map(|(username,)| username).collect())
+        })
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -98,6 +193,8 @@ pub struct Team<'a> {
     #[serde(default)]
     pub logo: Option<Cow<'a, str>>,
     pub members: Vec<Uuid>,
+    #[serde(default)]
+    pub captain: Option<Uuid>,
 }
 #[derive(Serialize, Deserialize)]
 pub struct User<'a> {
@@ -115,16 +212,44 @@ impl<'a, T: Player<'a>, U: EventState> Event<'a, T, U> {
             name,
             logo,
             team_size,
+            score_limits: ScoreLimits::default(),
+            exclude_unchecked: false,
             player_marker: PhantomData::<&'a T>,
             state_marker: PhantomData::<&'a U>,
         }
     }
+    pub fn with_score_limits(mut self, score_limits: ScoreLimits) -> Self {
+        self.score_limits = score_limits;
+        self
+    }
+    pub fn with_exclude_unchecked(mut self, exclude_unchecked: bool) -> Self {
+        self.exclude_unchecked = exclude_unchecked;
+        self
+    }
+    pub fn get_event(id: &'a Uuid, db_pool: &'a SqlitePool) -> AsyncDbRes<'a, Self>
+    where
+        Self: for<'r> FromRow<'r, SqliteRow> + Unpin,
+    {
+        let id = id.to_string();
+        Box::pin(async move {
+            let event =
+                sqlx::query_as::<_, Self>("SELECT id,name,logo,team_size,min_score,max_score,max_delta,exclude_unchecked FROM events WHERE id = ? AND deleted_at IS NULL")
+                    .bind(id)
+                    .fetch_one(db_pool)
+                    .await?;
+            Ok(event)
+        })
+    }
+    // moves the event to the trash , its teams/users stay linked for a restore
     pub fn delete_event(id: &'a Uuid, db_pool: &'a SqlitePool) -> AsyncDbRes<'a, ()> {
         let id = id.to_string();
         Box::pin(async move {
-            let res = sqlx::query!("DELETE FROM events WHERE id = ? ", id)
-                .execute(db_pool)
-                .await?;
+            let res =
+                sqlx::query("UPDATE events SET deleted_at = ? WHERE id = ? AND deleted_at IS NULL")
+                    .bind(Utc::now())
+                    .bind(id)
+                    .execute(db_pool)
+                    .await?;
             if res.rows_affected().eq(&0) {
                 return Err(VaderError::EventNotFound("No event found"));
             }
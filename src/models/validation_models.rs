@@ -0,0 +1,19 @@
+use serde::Serialize;
+
+pub const NAME_MAX_LEN: usize = 64;
+pub const LOGO_MAX_LEN: usize = 2048;
+pub const TEAM_SIZE_MAX: u32 = 100;
+// hard cap on a single score update , events can set a lower max_delta : )
+pub const SCORE_DELTA_MAX: i64 = 1_000_000;
+pub const PAGE_LIMIT_MAX: u32 = 500;
+// searches rerun their merged queries up to offset + count , keep that window small
+pub const SEARCH_OFFSET_MAX: u32 = 1000;
+pub const SEARCH_WINDOW_MAX: u32 = SEARCH_OFFSET_MAX + PAGE_LIMIT_MAX + 1;
+
+// one entry per failed rule , nested fields are dotted ie: members[1].name
+#[derive(Serialize, Debug, Clone)]
+pub struct FieldError {
+    pub field: String,
+    pub code: String,
+    pub message: String,
+}
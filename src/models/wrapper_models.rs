@@ -1,15 +1,22 @@
 use std::borrow::Cow;
 
-use sqlx::SqlitePool;
+use sqlx::{FromRow, Row, SqlitePool};
 use uuid::Uuid;
 
 use super::command_models::MemberInfo;
 use super::error_models::VaderError;
+use super::import_models::{ImportReport, ImportRow};
 use super::query_models::{EventQuery, EventQueryBuilder, EventQueryState};
+use super::registration_models::{
+    Registration, RegistrationRes, RegistrationState, RegistrationStatus,
+};
 use super::v_models::{
     ActiveEvent, AsyncDbRes, EndEvent, Event, NewEvent, Player, Team, User, VaderEvent,
 };
 use crate::services::event_services::VaderBoard;
+use crate::services::registration_services::{
+    get_registrations, registration_open, set_registration,
+};
 
 pub enum EventStateWrapper<'a, T: Player<'a>> {
     New(Event<'a, T, NewEvent>),
@@ -21,26 +28,34 @@ impl<'a, T> EventStateWrapper<'a, T>
 where
     T: Player<'a>,
 {
-    fn start_event(&mut self) -> Result<(), VaderError> {
+    fn can_start(&self) -> Result<(), VaderError<'static>> {
         match self {
-            Self::New(event) => {
-                *self = Self::Active(event.start_event());
-                Ok(())
-            }
+            Self::New(_) => Ok(()),
             Self::Active(_) => Err(VaderError::EventActive("Event already Started")),
             Self::End(_) => Err(VaderError::EventEnded("Event already Ended")),
         }
     }
-    fn end_event(&mut self) -> Result<(), VaderError> {
+    fn can_end(&self) -> Result<(), VaderError<'static>> {
         match self {
-            Self::Active(event) => {
-                *self = Self::End(event.end_event());
-                Ok(())
-            }
+            Self::Active(_) => Ok(()),
             Self::New(_) => Err(VaderError::EventNotActive("Event didn't start")),
             Self::End(_) => Err(VaderError::EventEnded("Event already Ended")),
         }
     }
+    fn start_event(&mut self) -> Result<(), VaderError<'static>> {
+        self.can_start()?;
+        if let Self::New(event) = self {
+            *self = Self::Active(event.start_event());
+        }
+        Ok(())
+    }
+    fn end_event(&mut self) -> Result<(), VaderError<'static>> {
+        self.can_end()?;
+        if let Self::Active(event) = self {
+            *self = Self::End(event.end_event());
+        }
+        Ok(())
+    }
     fn get_id(&self) -> Uuid {
         match self {
             Self::New(e) => e.id,
@@ -48,19 +63,38 @@ where
             Self::End(e) => e.id,
         }
     }
+    // rebuild the typestate from events.state : )
+    fn from_state(event: Event<'a, T, NewEvent>, state: &str) -> Result<Self, VaderError<'static>> {
+        match state {
+            "added" => Ok(Self::New(event)),
+            "started" => Ok(Self::Active(event.start_event())),
+            _ => Err(VaderError::SqlxFieldError("Error decoding Event state")),
+        }
+    }
 }
 pub enum EventWrapper<'a> {
     TeamEvent(EventStateWrapper<'a, Team<'a>>),
     UserEvent(EventStateWrapper<'a, User<'a>>),
 }
 impl<'a> EventWrapper<'a> {
-    pub fn start_event(&mut self) -> Result<(), VaderError> {
+    // the new state is saved first , so memory and events.state never disagree : )
+    pub async fn start_event(&mut self, db_pool: &SqlitePool) -> Result<(), VaderError<'static>> {
+        match self {
+            Self::TeamEvent(sw) => sw.can_start()?,
+            Self::UserEvent(sw) => sw.can_start()?,
+        }
+        self.save_state(EventQueryState::Start, db_pool).await?;
         match self {
             Self::TeamEvent(sw) => sw.start_event(),
             Self::UserEvent(sw) => sw.start_event(),
         }
     }
-    pub fn end_event(&mut self) -> Result<(), VaderError> {
+    pub async fn end_event(&mut self, db_pool: &SqlitePool) -> Result<(), VaderError<'static>> {
+        match self {
+            Self::TeamEvent(sw) => sw.can_end()?,
+            Self::UserEvent(sw) => sw.can_end()?,
+        }
+        self.save_state(EventQueryState::Stop, db_pool).await?;
         match self {
             Self::TeamEvent(sw) => sw.end_event(),
             Self::UserEvent(sw) => sw.end_event(),
@@ -73,6 +107,50 @@ impl<'a> EventWrapper<'a> {
             Self::UserEvent(sw) => sw.get_id(),
         }
     }
+    // current event is the one not stopped , atmost one by the unique index : )
+    pub fn get_current(db_pool: &SqlitePool) -> AsyncDbRes<'_, Option<EventWrapper<'static>>> {
+        Box::pin(async move {
+            let row = sqlx::query(
+                "SELECT id,name,logo,event_type,team_size,min_score,max_score,max_delta,exclude_unchecked,state
+                 FROM events WHERE state != 'stopped'",
+            )
+            .fetch_optional(db_pool)
+            .await?;
+            let Some(row) = row else {
+                return Ok(None);
+            };
+            let event_type: String = row.get("event_type");
+            let state: String = row.get("state");
+            let event = match event_type.as_str() {
+                "team_event" => {
+                    let e = Event::<Team, NewEvent>::from_row(&row)?;
+                    EventWrapper::TeamEvent(EventStateWrapper::from_state(e, &state)?)
+                }
+                "user_event" => {
+                    let e = Event::<User, NewEvent>::from_row(&row)?;
+                    EventWrapper::UserEvent(EventStateWrapper::from_state(e, &state)?)
+                }
+                _ => return Err(VaderError::SqlxFieldError("Error decoding EventType")),
+            };
+            Ok(Some(event))
+        })
+    }
+    fn save_state<'b>(
+        &self,
+        state: EventQueryState,
+        db_pool: &'b SqlitePool,
+    ) -> AsyncDbRes<'b, ()> {
+        let id = self.get_id().to_string();
+        let state = state.as_str();
+        Box::pin(async move {
+            sqlx::query("UPDATE events SET state = ? WHERE id = ?")
+                .bind(state)
+                .bind(id)
+                .execute(db_pool)
+                .await?;
+            Ok(())
+        })
+    }
     pub fn update_score_by_id(
         &'a self,
         p_id: &'a Uuid,
@@ -125,8 +203,11 @@ impl<'a> EventWrapper<'a> {
         match self {
             Self::TeamEvent(sw) => match sw {
                 EventStateWrapper::New(e) => Box::pin(async move {
-                    let _ = team.add_player(db_pool).await;
-                    e.add_participant_from_id(team.id, db_pool).await
+                    let mut transaction = db_pool.begin().await?;
+                    team.add_player(&mut transaction).await?;
+                    e.add_participant(&team, &mut transaction).await?;
+                    transaction.commit().await?;
+                    Ok(())
                 }),
                 _ => Box::pin(async move {
                     Err(VaderError::EventActive(
@@ -151,10 +232,15 @@ impl<'a> EventWrapper<'a> {
         match self {
             Self::TeamEvent(sw) => match sw {
                 EventStateWrapper::New(e) => Box::pin(async move {
-                    let team_id = team.id;
-                    let team_size = e.team_size.unwrap() as usize;
-                    team.with_members(members, team_size, db_pool).await?;
-                    e.add_participant_from_id(team_id, db_pool).await
+                    let team_size = e
+                        .team_size
+                        .ok_or(VaderError::EventTypeMismatch("Team size not specified"))?;
+                    let mut transaction = db_pool.begin().await?;
+                    team.with_members(members, team_size as usize, &mut transaction)
+                        .await?;
+                    e.add_participant_from_id(team.id, &mut transaction).await?;
+                    transaction.commit().await?;
+                    Ok(())
                 }),
                 _ => Box::pin(async move {
                     Err(VaderError::EventActive(
@@ -170,7 +256,11 @@ impl<'a> EventWrapper<'a> {
         }
     }
 
-    pub fn add_team_members(&self, mi: &'a MemberInfo, db_pool: &'a SqlitePool) -> AsyncDbRes<()> {
+    pub fn add_team_members(
+        &self,
+        mi: &'a MemberInfo,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'_, ()> {
         match self {
             Self::TeamEvent(sw) => match sw {
                 EventStateWrapper::New(e) => Box::pin(async move {
@@ -190,10 +280,13 @@ impl<'a> EventWrapper<'a> {
             }),
         }
     }
-    pub fn add_user(&self, user: &'a User, db_pool: &'a SqlitePool) -> AsyncDbRes<()> {
+    pub fn add_user(&self, user: &'a User, db_pool: &'a SqlitePool) -> AsyncDbRes<'_, ()> {
         match self {
             Self::TeamEvent(sw) => match sw {
-                EventStateWrapper::New(_) => user.add_player(db_pool),
+                EventStateWrapper::New(_) => Box::pin(async move {
+                    let mut conn = db_pool.acquire().await?;
+                    user.add_player(&mut conn).await
+                }),
                 _ => Box::pin(async move {
                     Err(VaderError::EventActive(
                         "User cannot be added as Event already started",
@@ -202,8 +295,11 @@ impl<'a> EventWrapper<'a> {
             },
             Self::UserEvent(sw) => match sw {
                 EventStateWrapper::New(e) => Box::pin(async move {
-                    let _ = user.add_player(db_pool).await;
-                    e.add_participant_from_id(user.id, db_pool).await
+                    let mut transaction = db_pool.begin().await?;
+                    user.add_player(&mut transaction).await?;
+                    e.add_participant_from_id(user.id, &mut transaction).await?;
+                    transaction.commit().await?;
+                    Ok(())
                 }),
                 _ => Box::pin(async move {
                     Err(VaderError::EventActive(
@@ -213,6 +309,136 @@ impl<'a> EventWrapper<'a> {
             },
         }
     }
+    pub fn import_rows(
+        &'a self,
+        rows: Vec<(usize, ImportRow)>,
+        report: ImportReport,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, ImportReport> {
+        match self {
+            Self::TeamEvent(EventStateWrapper::New(e)) => e.import_rows(rows, report, db_pool),
+            Self::UserEvent(EventStateWrapper::New(e)) => e.import_rows(rows, report, db_pool),
+            _ => Box::pin(async move {
+                Err(VaderError::EventActive(
+                    "Cannot import as Event already started",
+                ))
+            }),
+        }
+    }
+    // registration is only open while the team event is yet to start
+    pub fn registration_state(
+        &'a self,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, RegistrationState> {
+        let event_id = self.get_id();
+        match self {
+            Self::TeamEvent(EventStateWrapper::New(e)) => Box::pin(async move {
+                let mut conn = db_pool.acquire().await?;
+                Ok(RegistrationState {
+                    event_id,
+                    open: registration_open(&e.id, &mut conn).await?,
+                    team_size: e.team_size,
+                })
+            }),
+            _ => Box::pin(async move {
+                Ok(RegistrationState {
+                    event_id,
+                    open: false,
+                    team_size: None,
+                })
+            }),
+        }
+    }
+    pub fn set_registration(&'a self, open: bool, db_pool: &'a SqlitePool) -> AsyncDbRes<'a, ()> {
+        match self {
+            Self::TeamEvent(_) => {
+                Box::pin(async move { set_registration(&self.get_id(), open, db_pool).await })
+            }
+            Self::UserEvent(_) => Box::pin(async move {
+                Err(VaderError::EventTypeMismatch(
+                    "Cannot take registrations in user event",
+                ))
+            }),
+        }
+    }
+    pub fn register_team(
+        &'a self,
+        team: &'a Team<'a>,
+        members: &'a [User<'a>],
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, RegistrationRes> {
+        match self {
+            Self::TeamEvent(EventStateWrapper::New(e)) => e.register_team(team, members, db_pool),
+            Self::TeamEvent(_) => Box::pin(async move {
+                Err(VaderError::RegistrationClosed(
+                    "Registration closed as Event already started",
+                ))
+            }),
+            Self::UserEvent(_) => Box::pin(async move {
+                Err(VaderError::EventTypeMismatch(
+                    "Cannot register team in user event",
+                ))
+            }),
+        }
+    }
+    pub fn join_registration(
+        &'a self,
+        invite_code: &'a str,
+        user: &'a User<'a>,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, Uuid> {
+        match self {
+            Self::TeamEvent(EventStateWrapper::New(e)) => {
+                e.join_registration(invite_code, user, db_pool)
+            }
+            Self::TeamEvent(_) => Box::pin(async move {
+                Err(VaderError::RegistrationClosed(
+                    "Registration closed as Event already started",
+                ))
+            }),
+            Self::UserEvent(_) => Box::pin(async move {
+                Err(VaderError::EventTypeMismatch(
+                    "Cannot join team in user event",
+                ))
+            }),
+        }
+    }
+    pub fn get_registrations(
+        &'a self,
+        status: Option<RegistrationStatus>,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, Vec<Registration>> {
+        match self {
+            Self::TeamEvent(_) => get_registrations(&self.get_id(), status, db_pool),
+            Self::UserEvent(_) => Box::pin(async move {
+                Err(VaderError::EventTypeMismatch(
+                    "No registrations in user event",
+                ))
+            }),
+        }
+    }
+    pub fn decide_registration(
+        &'a self,
+        id: &'a Uuid,
+        status: RegistrationStatus,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, Uuid> {
+        match self {
+            Self::TeamEvent(EventStateWrapper::New(e)) => {
+                e.decide_registration(id, status, db_pool)
+            }
+            Self::TeamEvent(_) => Box::pin(async move {
+                Err(VaderError::EventActive(
+                    "Registration cannot be decided as Event already started",
+                ))
+            }),
+            Self::UserEvent(_) => Box::pin(async move {
+                Err(VaderError::EventTypeMismatch(
+                    "No registrations in user event",
+                ))
+            }),
+        }
+    }
     pub fn get_event(&'a self) -> EventQuery<'a> {
         match self {
             EventWrapper::TeamEvent(sw) => match sw {
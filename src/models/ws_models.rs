@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::atomic::AtomicUsize;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use actix::{Actor, SpawnHandle};
+use actix_web_actors::ws;
+use serde::Serialize;
+
+use super::config_models::WsConfig;
+
+// shared by every websocket route , admits connections within the limits
+// and hands each one a WsConn : )
+pub struct WsGate {
+    pub config: WsConfig,
+    // open websockets , a WsConn counts itself until it is dropped
+    pub live: Arc<AtomicUsize>,
+    pub per_ip: Arc<Mutex<HashMap<IpAddr, usize>>>,
+}
+
+// heartbeat , query budget and connection slot of one websocket
+pub struct WsConn {
+    // the request id of the upgrade request , logged for the whole session
+    pub id: String,
+    pub opened: Instant,
+    pub config: WsConfig,
+    pub last_seen: Instant,
+    // token bucket , refilled at `queries_per_sec`
+    pub tokens: f64,
+    pub refilled: Instant,
+    // search waiting out the debounce
+    pub pending: Option<SpawnHandle>,
+    pub ip: Option<IpAddr>,
+    pub live: Arc<AtomicUsize>,
+    pub per_ip: Arc<Mutex<HashMap<IpAddr, usize>>>,
+}
+
+// why a websocket was refused or closed , each has its own close code
+pub enum WsLimit {
+    Connections,
+    PerIp,
+    Count(u32),
+    Rate,
+}
+
+pub trait WsActor: Actor<Context = ws::WebsocketContext<Self>> {
+    fn conn(&mut self) -> &mut WsConn;
+}
+
+// closes a refused websocket right after the handshake , browsers only get
+// to see close codes , not http statuses
+pub struct WsReject(pub WsLimit);
+
+#[derive(Serialize)]
+pub struct WsStats {
+    // every open websocket , searches included
+    pub total: usize,
+    pub vaderboard: usize,
+    pub current_event: usize,
+    pub event_streams: usize,
+}
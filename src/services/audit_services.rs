@@ -0,0 +1,398 @@
+use std::future::{ready, Ready};
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix::{ActorContext, Handler, StreamHandler};
+use actix_web::dev::Payload;
+use actix_web::{FromRequest, HttpMessage, HttpRequest};
+use actix_web_actors::ws;
+use chrono::{SecondsFormat, Utc};
+use serde::Serialize;
+use sqlx::sqlite::SqliteRow;
+use sqlx::{FromRow, QueryBuilder, Row, Sqlite, SqlitePool};
+use uuid::Uuid;
+
+use crate::models::audit_models::{
+    Audit, AuditAppend, AuditClient, AuditConnect, AuditDisconnect, AuditEntry, AuditFormat,
+    AuditNote, AuditOutcome, AuditQuery, AuditRes, AuditSrv,
+};
+use crate::models::config_models::AuditConfig;
+use crate::models::error_models::VaderError;
+use crate::models::page_models::{Page, PAGE_LIMIT_DEFAULT};
+use crate::models::ws_models::{WsActor, WsConn};
+
+// handlers note what they did , extracting never fails : a request the
+// AuditLog middleware didn't see just gets a detached note
+impl FromRequest for Audit {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(Ok(req
+            .extensions()
+            .get::<Audit>()
+            .cloned()
+            .unwrap_or_default()))
+    }
+}
+
+impl Audit {
+    pub fn action(&self, action: &'static str) -> &Self {
+        self.0.borrow_mut().action = Some(action);
+        self
+    }
+    // for requests without an admin session , ie: a successful login
+    pub fn actor(&self, actor: &str) -> &Self {
+        self.0.borrow_mut().actor = Some(actor.to_string());
+        self
+    }
+    pub fn event(&self, event_id: Uuid) -> &Self {
+        self.0.borrow_mut().event_id = Some(event_id);
+        self
+    }
+    pub fn target(&self, id: Uuid) -> &Self {
+        self.0.borrow_mut().target_ids.push(id);
+        self
+    }
+    pub fn targets(&self, ids: &[Uuid]) -> &Self {
+        self.0.borrow_mut().target_ids.extend_from_slice(ids);
+        self
+    }
+    // a summary of the payload , never secrets
+    pub fn summary<T: Serialize>(&self, key: &str, value: T) -> &Self {
+        if let Ok(value) = serde_json::to_value(value) {
+            self.0.borrow_mut().summary.insert(key.to_string(), value);
+        }
+        self
+    }
+    pub fn take(&self) -> AuditNote {
+        self.0.take()
+    }
+}
+
+impl FromRow<'_, SqliteRow> for AuditEntry {
+    fn from_row(row: &'_ SqliteRow) -> Result<Self, sqlx::Error> {
+        let decode_err =
+            |index: &str, e: Box<dyn std::error::Error + Send + Sync>| sqlx::Error::ColumnDecode {
+                index: index.to_string(),
+                source: e,
+            };
+        let event_id: Option<String> = row.try_get("event_id")?;
+        let event_id = event_id
+            .map(|id| Uuid::parse_str(&id))
+            .transpose()
+            .map_err(|e| decode_err("event_id", Box::new(e)))?;
+        let target_ids: String = row.try_get("target_ids")?;
+        let summary: String = row.try_get("summary")?;
+        let outcome: String = row.try_get("outcome")?;
+        let status: i64 = row.try_get("status")?;
+        Ok(AuditEntry {
+            id: row.try_get("id")?,
+            created_at: row.try_get("created_at")?,
+            actor: row.try_get("actor")?,
+            request_id: row.try_get("request_id")?,
+            action: row.try_get("action")?,
+            event_id,
+            target_ids: serde_json::from_str(&target_ids)
+                .map_err(|e| decode_err("target_ids", Box::new(e)))?,
+            summary: serde_json::from_str(&summary)
+                .map_err(|e| decode_err("summary", Box::new(e)))?,
+            outcome: match outcome.as_str() {
+                "ok" => AuditOutcome::Ok,
+                _ => AuditOutcome::Error,
+            },
+            status: status as u16,
+            error: row.try_get("error")?,
+        })
+    }
+}
+
+impl AuditOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditOutcome::Ok => "ok",
+            AuditOutcome::Error => "error",
+        }
+    }
+}
+
+impl AuditFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Csv => "text/csv; charset=utf-8",
+        }
+    }
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+        }
+    }
+}
+
+const AUDIT_COLUMNS: &str =
+    "id,created_at,actor,request_id,action,event_id,target_ids,summary,outcome,status,error";
+
+impl AuditQuery {
+    fn push_filters(&self, qb: &mut QueryBuilder<'_, Sqlite>) {
+        qb.push(" WHERE 1 = 1");
+        if let Some(action) = &self.action {
+            qb.push(" AND action = ").push_bind(action.clone());
+        }
+        if let Some(actor) = &self.actor {
+            qb.push(" AND actor = ").push_bind(actor.clone());
+        }
+        if let Some(event_id) = &self.event_id {
+            qb.push(" AND event_id = ").push_bind(event_id.to_string());
+        }
+        if let Some(target_id) = &self.target_id {
+            qb.push(" AND EXISTS (SELECT 1 FROM json_each(target_ids) WHERE value = ")
+                .push_bind(target_id.to_string())
+                .push(")");
+        }
+        if let Some(outcome) = &self.outcome {
+            qb.push(" AND outcome = ").push_bind(outcome.as_str());
+        }
+    }
+}
+
+impl AuditEntry {
+    pub async fn record(
+        note: AuditNote,
+        action: String,
+        request_id: Option<String>,
+        status: u16,
+        error: Option<String>,
+        db_pool: &SqlitePool,
+    ) -> Result<AuditEntry, VaderError<'static>> {
+        let outcome = match status {
+            s if s < 400 => AuditOutcome::Ok,
+            _ => AuditOutcome::Error,
+        };
+        let mut entry = AuditEntry {
+            id: 0,
+            created_at: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            actor: note.actor,
+            request_id,
+            action: note.action.map(String::from).unwrap_or(action),
+            event_id: note.event_id,
+            target_ids: note.target_ids,
+            summary: serde_json::Value::Object(note.summary),
+            outcome,
+            status,
+            error,
+        };
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO audit_log
+             (created_at,actor,request_id,action,event_id,target_ids,summary,outcome,status,error)
+             VALUES (?,?,?,?,?,?,?,?,?,?) RETURNING id",
+        )
+        .bind(&entry.created_at)
+        .bind(&entry.actor)
+        .bind(&entry.request_id)
+        .bind(&entry.action)
+        .bind(entry.event_id.map(|id| id.to_string()))
+        .bind(serde_json::to_string(&entry.target_ids)?)
+        .bind(entry.summary.to_string())
+        .bind(entry.outcome.as_str())
+        .bind(entry.status as i64)
+        .bind(&entry.error)
+        .fetch_one(db_pool)
+        .await?;
+        entry.id = id;
+        Ok(entry)
+    }
+
+    // newest first , the cursor is the id of the last entry of the page
+    pub async fn get_page(
+        query: &AuditQuery,
+        db_pool: &SqlitePool,
+    ) -> Result<Page<AuditEntry>, VaderError<'static>> {
+        let limit = query.limit.unwrap_or(PAGE_LIMIT_DEFAULT);
+        let before = query
+            .cursor
+            .as_deref()
+            .map(|c| {
+                c.parse::<i64>()
+                    .map_err(|_| VaderError::InvalidRequest("invalid cursor".to_string()))
+            })
+            .transpose()?;
+        let mut qb = QueryBuilder::new(format!("SELECT {} FROM audit_log", AUDIT_COLUMNS));
+        query.push_filters(&mut qb);
+        if let Some(before) = before {
+            qb.push(" AND id < ").push_bind(before);
+        }
+        qb.push(" ORDER BY id DESC LIMIT ")
+            .push_bind(limit as i64 + 1);
+        let mut items = qb.build_query_as::<AuditEntry>().fetch_all(db_pool).await?;
+        let more = items.len() > limit as usize;
+        items.truncate(limit as usize);
+        let next_cursor = match items.last() {
+            Some(last) if more => Some(last.id.to_string()),
+            _ => None,
+        };
+        Ok(Page { items, next_cursor })
+    }
+
+    // drops the entries older than `retention_days` , returns how many went
+    pub async fn prune(
+        retention_days: u32,
+        db_pool: &SqlitePool,
+    ) -> Result<u64, VaderError<'static>> {
+        let cutoff = (Utc::now() - chrono::Duration::days(retention_days.into()))
+            .to_rfc3339_opts(SecondsFormat::Millis, true);
+        let res = sqlx::query("DELETE FROM audit_log WHERE created_at < ?")
+            .bind(cutoff)
+            .execute(db_pool)
+            .await?;
+        Ok(res.rows_affected())
+    }
+
+    // every matching entry , oldest first
+    pub async fn export(
+        query: &AuditQuery,
+        format: AuditFormat,
+        db_pool: &SqlitePool,
+    ) -> Result<String, VaderError<'static>> {
+        let mut qb = QueryBuilder::new(format!("SELECT {} FROM audit_log", AUDIT_COLUMNS));
+        query.push_filters(&mut qb);
+        qb.push(" ORDER BY id ASC");
+        let entries = qb.build_query_as::<AuditEntry>().fetch_all(db_pool).await?;
+        match format {
+            AuditFormat::Json => Ok(serde_json::to_string_pretty(&entries)?),
+            AuditFormat::Csv => audit_csv(&entries),
+        }
+    }
+}
+
+// prunes once at startup and then daily , retention 0 keeps everything : )
+pub fn schedule_audit_prune(db_pool: SqlitePool, config: AuditConfig) {
+    if config.retention_days == 0 {
+        return;
+    }
+    actix_web::rt::spawn(async move {
+        let mut interval = actix_web::rt::time::interval(Duration::from_secs(24 * 60 * 60));
+        loop {
+            interval.tick().await;
+            match AuditEntry::prune(config.retention_days, &db_pool).await {
+                Ok(0) => {}
+                Ok(n) => log::info!(
+                    "Pruned {} audit entries older than {} days",
+                    n,
+                    config.retention_days
+                ),
+                Err(e) => log::error!("Audit log prune failed : {}", e),
+            }
+        }
+    });
+}
+
+fn audit_csv(entries: &[AuditEntry]) -> Result<String, VaderError<'static>> {
+    let csv_err = |e: csv::Error| VaderError::ExportError(e.to_string());
+    let mut wtr = csv::Writer::from_writer(Vec::new());
+    wtr.write_record([
+        "id",
+        "created_at",
+        "actor",
+        "request_id",
+        "action",
+        "event_id",
+        "target_ids",
+        "summary",
+        "outcome",
+        "status",
+        "error",
+    ])
+    .map_err(csv_err)?;
+    for entry in entries {
+        let target_ids = entry
+            .target_ids
+            .iter()
+            .map(Uuid::to_string)
+            .collect::<Vec<String>>()
+            .join("; ");
+        wtr.write_record([
+            entry.id.to_string().as_str(),
+            entry.created_at.as_str(),
+            entry.actor.as_deref().unwrap_or(""),
+            entry.request_id.as_deref().unwrap_or(""),
+            entry.action.as_str(),
+            entry
+                .event_id
+                .map(|id| id.to_string())
+                .unwrap_or_default()
+                .as_str(),
+            target_ids.as_str(),
+            entry.summary.to_string().as_str(),
+            entry.outcome.as_str(),
+            entry.status.to_string().as_str(),
+            entry.error.as_deref().unwrap_or(""),
+        ])
+        .map_err(csv_err)?;
+    }
+    let data = wtr
+        .into_inner()
+        .map_err(|e| VaderError::ExportError(e.to_string()))?;
+    String::from_utf8(data).map_err(|e| VaderError::ExportError(e.to_string()))
+}
+
+impl Handler<AuditConnect> for AuditSrv {
+    type Result = ();
+    fn handle(&mut self, msg: AuditConnect, _ctx: &mut Self::Context) -> Self::Result {
+        self.clients.insert(msg.0);
+    }
+}
+impl Handler<AuditDisconnect> for AuditSrv {
+    type Result = ();
+    fn handle(&mut self, msg: AuditDisconnect, _ctx: &mut Self::Context) -> Self::Result {
+        self.clients.remove(&msg.0);
+    }
+}
+impl Handler<AuditAppend> for AuditSrv {
+    type Result = ();
+    fn handle(&mut self, msg: AuditAppend, _ctx: &mut Self::Context) -> Self::Result {
+        if self.clients.is_empty() {
+            return;
+        }
+        match serde_json::to_string(&msg.0) {
+            Ok(entry) => {
+                let entry: Arc<str> = entry.into();
+                for client in &self.clients {
+                    client.do_send(AuditRes(entry.clone()));
+                }
+            }
+            Err(e) => log::error!("Error serializing audit entry : {}", e),
+        }
+    }
+}
+
+// admins only listen , anything they send just counts as a heartbeat
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for AuditClient {
+    fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        use ws::Message::*;
+        self.conn.beat();
+        match msg {
+            Ok(Ping(msg)) => ctx.pong(&msg),
+            Ok(Close(reason)) => {
+                ctx.close(reason);
+                ctx.stop();
+            }
+            Err(_) => ctx.stop(),
+            _ => {}
+        }
+    }
+}
+
+impl WsActor for AuditClient {
+    fn conn(&mut self) -> &mut WsConn {
+        &mut self.conn
+    }
+}
+
+impl Handler<AuditRes> for AuditClient {
+    type Result = ();
+    fn handle(&mut self, msg: AuditRes, ctx: &mut Self::Context) -> Self::Result {
+        ctx.text(msg.0.as_ref());
+    }
+}
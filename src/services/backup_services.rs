@@ -0,0 +1,281 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::SqlitePool;
+
+use super::db_services::MIGRATOR;
+use crate::models::backup_models::BackupInfo;
+use crate::models::config_models::{BackupConfig, VaderConfig};
+use crate::models::error_models::VaderError;
+
+const BACKUP_PREFIX: &str = "vaderboard-";
+const PRE_RESTORE_PREFIX: &str = "pre-restore-";
+const BACKUP_EXT: &str = ".db";
+// a validated backup waiting to be swapped in on the next start : )
+const STAGED_RESTORE_SUFFIX: &str = ".restore";
+
+fn backup_err(msg: String) -> VaderError<'static> {
+    VaderError::BackupError(msg)
+}
+
+fn io_err(path: &Path) -> impl Fn(std::io::Error) -> VaderError<'static> + '_ {
+    move |e| VaderError::BackupIoError(format!("{} : {}", path.display(), e))
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+fn timestamp() -> String {
+    Utc::now().format("%Y%m%dT%H%M%S%.3fZ").to_string()
+}
+
+impl VaderConfig {
+    pub fn db_path(&self) -> Result<PathBuf, VaderError<'static>> {
+        let options = SqliteConnectOptions::from_str(&self.database.url)?;
+        let path = options.get_filename().to_path_buf();
+        if path.as_os_str() == ":memory:" || self.in_memory() {
+            return Err(backup_err(
+                "In memory databases cannot be backed up or restored".to_string(),
+            ));
+        }
+        Ok(path)
+    }
+
+    fn in_memory(&self) -> bool {
+        self.database.url.contains(":memory:")
+    }
+
+    fn staged_restore_path(&self) -> Result<PathBuf, VaderError<'static>> {
+        Ok(with_suffix(&self.db_path()?, STAGED_RESTORE_SUFFIX))
+    }
+}
+
+// VACUUM INTO takes a consistent snapshot from the live pool , no file copying : )
+pub async fn backup_to(
+    db_pool: &SqlitePool,
+    path: &Path,
+) -> Result<BackupInfo, VaderError<'static>> {
+    if path.exists() {
+        return Err(backup_err(format!("{} already exists", path.display())));
+    }
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(io_err(parent))?;
+    }
+    sqlx::query("VACUUM INTO ?")
+        .bind(path.to_string_lossy())
+        .execute(db_pool)
+        .await?;
+    log::info!("Database backed up to {}", path.display());
+    backup_info(path)
+}
+
+pub async fn backup_db(
+    db_pool: &SqlitePool,
+    config: &BackupConfig,
+) -> Result<BackupInfo, VaderError<'static>> {
+    let name = format!("{}{}{}", BACKUP_PREFIX, timestamp(), BACKUP_EXT);
+    let info = backup_to(db_pool, &config.dir.join(name)).await?;
+    prune_backups(config)?;
+    Ok(info)
+}
+
+fn backup_info(path: &Path) -> Result<BackupInfo, VaderError<'static>> {
+    let meta = fs::metadata(path).map_err(io_err(path))?;
+    let created_at: DateTime<Utc> = meta.modified().map_err(io_err(path))?.into();
+    Ok(BackupInfo {
+        name: path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        size: meta.len(),
+        created_at,
+    })
+}
+
+// newest first , pre-restore safety copies are listed but never pruned : )
+pub fn list_backups(config: &BackupConfig) -> Result<Vec<BackupInfo>, VaderError<'static>> {
+    let dir = &config.dir;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut backups = fs::read_dir(dir)
+        .map_err(io_err(dir))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy())
+                .unwrap_or_default();
+            (name.starts_with(BACKUP_PREFIX) || name.starts_with(PRE_RESTORE_PREFIX))
+                && name.ends_with(BACKUP_EXT)
+        })
+        .map(|path| backup_info(&path))
+        .collect::<Result<Vec<BackupInfo>, VaderError>>()?;
+    backups.sort_by(|a, b| b.name.cmp(&a.name));
+    Ok(backups)
+}
+
+fn prune_backups(config: &BackupConfig) -> Result<(), VaderError<'static>> {
+    let scheduled = list_backups(config)?
+        .into_iter()
+        .filter(|b| b.name.starts_with(BACKUP_PREFIX))
+        .collect::<Vec<BackupInfo>>();
+    for old in scheduled.iter().skip(config.retention) {
+        let path = config.dir.join(&old.name);
+        fs::remove_file(&path).map_err(io_err(&path))?;
+        log::info!("Removed old backup {}", old.name);
+    }
+    Ok(())
+}
+
+pub fn find_backup(config: &BackupConfig, name: &str) -> Result<PathBuf, VaderError<'static>> {
+    // only plain file names , no escaping the backup dir : )
+    if Path::new(name).file_name().map(|n| n.to_string_lossy()) != Some(name.into()) {
+        return Err(backup_err(format!("Invalid backup name : {}", name)));
+    }
+    let path = config.dir.join(name);
+    if !path.is_file() {
+        return Err(backup_err(format!("No backup named {}", name)));
+    }
+    Ok(path)
+}
+
+// backup must be intact and only have migrations known to this build
+// fts5 integrity checks need a writable db , so run this on a copy : )
+async fn validate_backup(path: &Path) -> Result<i64, VaderError<'static>> {
+    let options = SqliteConnectOptions::new().filename(path);
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await?;
+    let res = async {
+        let (integrity,): (String,) = sqlx::query_as("PRAGMA integrity_check")
+            .fetch_one(&pool)
+            .await
+            .map_err(|e| backup_err(format!("Not a valid database : {}", e)))?;
+        if integrity != "ok" {
+            return Err(backup_err(format!(
+                "Integrity check failed : {}",
+                integrity
+            )));
+        }
+        let applied: Vec<(i64, Vec<u8>)> = sqlx::query_as(
+            "SELECT version,checksum FROM _sqlx_migrations WHERE success = 1 ORDER BY version",
+        )
+        .fetch_all(&pool)
+        .await
+        .map_err(|_| backup_err("Not a VaderBoard database , no migrations found".to_string()))?;
+        for (version, checksum) in &applied {
+            match MIGRATOR
+                .iter()
+                .find(|m| m.version.eq(version) && !m.migration_type.is_down_migration())
+            {
+                None => {
+                    return Err(backup_err(format!(
+                    "Schema version {} is unknown to this build , backup is from a newer version",
+                    version
+                )))
+                }
+                Some(m) if m.checksum.as_ref() != checksum.as_slice() => {
+                    return Err(backup_err(format!(
+                        "Schema version {} was modified , checksum mismatch",
+                        version
+                    )))
+                }
+                Some(_) => {}
+            }
+        }
+        applied.last().map(|(version, _)| *version).ok_or_else(|| {
+            backup_err("Not a VaderBoard database , no migrations found".to_string())
+        })
+    }
+    .await;
+    pool.close().await;
+    res
+}
+
+// the live pool can't be swapped , restore is staged and applied on the next start : )
+pub async fn stage_restore(config: &VaderConfig, src: &Path) -> Result<i64, VaderError<'static>> {
+    let staged = config.staged_restore_path()?;
+    let tmp = with_suffix(&staged, ".tmp");
+    fs::copy(src, &tmp).map_err(io_err(&tmp))?;
+    let version = match validate_backup(&tmp).await {
+        Ok(version) => version,
+        Err(e) => {
+            let _ = fs::remove_file(&tmp);
+            return Err(e);
+        }
+    };
+    fs::rename(&tmp, &staged).map_err(io_err(&staged))?;
+    log::info!(
+        "Restore of {} staged [schema version : {}]",
+        src.display(),
+        version
+    );
+    Ok(version)
+}
+
+// must run before the pool is opened , current db is kept as a pre-restore backup
+pub async fn apply_staged_restore(config: &VaderConfig) -> Result<(), VaderError<'static>> {
+    // nothing can be staged for a database without a file
+    if config.in_memory() {
+        return Ok(());
+    }
+    let staged = config.staged_restore_path()?;
+    if !staged.exists() {
+        return Ok(());
+    }
+    let db_path = config.db_path()?;
+    if db_path.exists() {
+        let db_pool = SqlitePool::connect(&config.database.url).await?;
+        let safety = config.backup.dir.join(format!(
+            "{}{}{}",
+            PRE_RESTORE_PREFIX,
+            timestamp(),
+            BACKUP_EXT
+        ));
+        let res = backup_to(&db_pool, &safety).await;
+        db_pool.close().await;
+        res?;
+    }
+    fs::rename(&staged, &db_path).map_err(io_err(&db_path))?;
+    // stale journal files belong to the old database
+    for suffix in ["-wal", "-shm", "-journal"] {
+        let path = with_suffix(&db_path, suffix);
+        if path.exists() {
+            fs::remove_file(&path).map_err(io_err(&path))?;
+        }
+    }
+    log::info!("Restored database from staged backup");
+    Ok(())
+}
+
+pub fn schedule_backups(db_pool: SqlitePool, config: BackupConfig) {
+    let Some(secs) = config.interval_secs else {
+        return;
+    };
+    log::info!(
+        "Scheduled backups every {}s to {} [retention : {}]",
+        secs,
+        config.dir.display(),
+        config.retention
+    );
+    actix_web::rt::spawn(async move {
+        let mut interval = actix_web::rt::time::interval(Duration::from_secs(secs));
+        // first tick is immediate , skip it
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            if let Err(e) = backup_db(&db_pool, &config).await {
+                log::error!("Scheduled backup failed : {}", e);
+            }
+        }
+    });
+}
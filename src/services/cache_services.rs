@@ -0,0 +1,57 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use actix_web::http::header::{
+    CacheControl, CacheDirective, ContentType, EntityTag, IfNoneMatch, ETAG,
+};
+use actix_web::{HttpMessage, HttpRequest, HttpResponse, HttpResponseBuilder};
+
+// weak etag of a response , for data without a version counter : )
+pub fn body_etag(body: &str) -> EntityTag {
+    let mut hasher = DefaultHasher::new();
+    body.hash(&mut hasher);
+    EntityTag::new_weak(format!("{:016x}", hasher.finish()))
+}
+
+// true when the client already holds `etag` (If-None-Match)
+pub fn is_fresh(req: &HttpRequest, etag: &EntityTag) -> bool {
+    match req.get_header::<IfNoneMatch>() {
+        Some(IfNoneMatch::Any) => true,
+        Some(IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(etag)),
+        None => false,
+    }
+}
+
+pub fn not_modified(etag: &EntityTag, max_age: u32) -> HttpResponse {
+    cache_headers(HttpResponse::NotModified(), etag, max_age).finish()
+}
+
+pub fn cached_json(etag: &EntityTag, max_age: u32, body: String) -> HttpResponse {
+    cache_headers(HttpResponse::Ok(), etag, max_age)
+        .content_type(ContentType::json())
+        .body(body)
+}
+
+// search results change with any add/edit , so their etag is the body's hash
+pub fn search_response(req: &HttpRequest, max_age: u32, body: String) -> HttpResponse {
+    let etag = body_etag(&body);
+    if is_fresh(req, &etag) {
+        not_modified(&etag, max_age)
+    } else {
+        cached_json(&etag, max_age, body)
+    }
+}
+
+fn cache_headers(
+    mut res: HttpResponseBuilder,
+    etag: &EntityTag,
+    max_age: u32,
+) -> HttpResponseBuilder {
+    let cache = match max_age {
+        0 => vec![CacheDirective::NoCache],
+        secs => vec![CacheDirective::Public, CacheDirective::MaxAge(secs)],
+    };
+    res.insert_header((ETAG, etag.to_string()))
+        .insert_header(CacheControl(cache));
+    res
+}
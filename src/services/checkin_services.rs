@@ -0,0 +1,292 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use sqlx::{Row, SqlitePool};
+use uuid::Uuid;
+
+use crate::models::checkin_models::{CheckIn, CheckInReport};
+use crate::models::config_models::CheckinConfig;
+use crate::models::error_models::VaderError;
+use crate::models::v_models::AsyncDbRes;
+use crate::models::wrapper_models::{EventStateWrapper, EventWrapper};
+
+type HmacSha256 = Hmac<Sha256>;
+
+fn token_mac(secret: &str, event_id: &Uuid, id: &Uuid) -> HmacSha256 {
+    // new_from_slice takes a key of any length
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("hmac key");
+    mac.update(format!("{}:{}", event_id, id).as_bytes());
+    mac
+}
+
+// `<participant id>.<signature>` , only valid for the event it was made for : )
+pub fn checkin_token(secret: &str, event_id: &Uuid, id: &Uuid) -> String {
+    let sig = token_mac(secret, event_id, id).finalize().into_bytes();
+    format!("{}.{}", id, URL_SAFE_NO_PAD.encode(sig))
+}
+
+pub fn verify_checkin_token(
+    secret: &str,
+    event_id: &Uuid,
+    token: &str,
+) -> Result<Uuid, VaderError<'static>> {
+    let invalid = || VaderError::InvalidRequest("Invalid check-in token".to_string());
+    let (id, sig) = token.trim().split_once('.').ok_or_else(invalid)?;
+    let id = Uuid::parse_str(id).map_err(|_e| invalid())?;
+    let sig = URL_SAFE_NO_PAD.decode(sig).map_err(|_e| invalid())?;
+    token_mac(secret, event_id, &id)
+        .verify_slice(&sig)
+        .map_err(|_e| invalid())?;
+    Ok(id)
+}
+
+impl<'a> EventWrapper<'a> {
+    // event link table , its participant column and the participant table
+    fn participants(&self) -> (&'static str, &'static str, &'static str) {
+        match self {
+            Self::TeamEvent(_) => ("event_teams", "team_id", "teams"),
+            Self::UserEvent(_) => ("event_users", "user_id", "users"),
+        }
+    }
+    fn not_in_event(&self) -> VaderError<'static> {
+        match self {
+            Self::TeamEvent(_) => VaderError::TeamNotFound("Team is not in the event"),
+            Self::UserEvent(_) => VaderError::UserNotFound("User is not in the event"),
+        }
+    }
+    fn check_in_open(&self) -> Result<(), VaderError<'static>> {
+        match self {
+            Self::TeamEvent(EventStateWrapper::End(_))
+            | Self::UserEvent(EventStateWrapper::End(_)) => Err(VaderError::EventEnded(
+                "Check-in closed as Event already Ended",
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    // all or none , a repeated check-in keeps the first time
+    pub fn check_in(
+        &'a self,
+        ids: &'a [Uuid],
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, CheckInReport> {
+        let (link, col, players) = self.participants();
+        let event_id = self.get_id().to_string();
+        Box::pin(async move {
+            self.check_in_open()?;
+            let mut transaction = db_pool.begin().await?;
+            let mut report = CheckInReport {
+                checked_in: 0,
+                already_checked_in: 0,
+            };
+            let now = Utc::now();
+            for id in ids {
+                let id = id.to_string();
+                let res = sqlx::query(&format!(
+                    "UPDATE {link} SET checked_in_at = ? WHERE event_id = ? AND {col} = ?
+                     AND checked_in_at IS NULL
+                     AND {col} IN (SELECT id FROM {players} WHERE deleted_at IS NULL)"
+                ))
+                .bind(now)
+                .bind(&event_id)
+                .bind(&id)
+                .execute(&mut *transaction)
+                .await?;
+                if res.rows_affected() > 0 {
+                    report.checked_in += 1;
+                    continue;
+                }
+                let found = sqlx::query(&format!(
+                    "SELECT 1 FROM {link} WHERE event_id = ? AND {col} = ?
+                     AND {col} IN (SELECT id FROM {players} WHERE deleted_at IS NULL)"
+                ))
+                .bind(&event_id)
+                .bind(&id)
+                .fetch_optional(&mut *transaction)
+                .await?;
+                match found {
+                    Some(_) => report.already_checked_in += 1,
+                    None => return Err(self.not_in_event()),
+                }
+            }
+            transaction.commit().await?;
+            Ok(report)
+        })
+    }
+
+    pub fn undo_check_in(&'a self, id: &'a Uuid, db_pool: &'a SqlitePool) -> AsyncDbRes<'a, ()> {
+        let (link, col, _) = self.participants();
+        let event_id = self.get_id().to_string();
+        Box::pin(async move {
+            self.check_in_open()?;
+            let res = sqlx::query(&format!(
+                "UPDATE {link} SET checked_in_at = NULL WHERE event_id = ? AND {col} = ?"
+            ))
+            .bind(event_id)
+            .bind(id.to_string())
+            .execute(db_pool)
+            .await?;
+            if res.rows_affected().eq(&0) {
+                return Err(self.not_in_event());
+            }
+            Ok(())
+        })
+    }
+
+    // errors out if the participant is not in the event
+    pub fn checked_in_at(
+        &'a self,
+        id: &'a Uuid,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, Option<DateTime<Utc>>> {
+        let (link, col, players) = self.participants();
+        let event_id = self.get_id().to_string();
+        Box::pin(async move {
+            let checked_in_at: Option<Option<DateTime<Utc>>> = sqlx::query_scalar(&format!(
+                "SELECT checked_in_at FROM {link} WHERE event_id = ? AND {col} = ?
+                 AND {col} IN (SELECT id FROM {players} WHERE deleted_at IS NULL)"
+            ))
+            .bind(event_id)
+            .bind(id.to_string())
+            .fetch_optional(db_pool)
+            .await?;
+            checked_in_at.ok_or_else(|| self.not_in_event())
+        })
+    }
+
+    // everyone in the event by name , checked_in_at is null till they check in
+    pub fn get_check_ins(&'a self, db_pool: &'a SqlitePool) -> AsyncDbRes<'a, Vec<CheckIn>> {
+        let (link, col, players) = self.participants();
+        let event_id = self.get_id().to_string();
+        Box::pin(async move {
+            let rows = sqlx::query(&format!(
+                "SELECT p.id AS id,p.name AS name,l.checked_in_at AS checked_in_at
+                 FROM {link} l JOIN {players} p ON p.id = l.{col}
+                 WHERE l.event_id = ? AND p.deleted_at IS NULL ORDER BY p.name"
+            ))
+            .bind(event_id)
+            .fetch_all(db_pool)
+            .await?;
+            rows.iter()
+                .map(|row| {
+                    let id = Uuid::parse_str(row.get("id")).map_err(|_e| {
+                        VaderError::SqlxFieldError("Error decoding Participant Id")
+                    })?;
+                    Ok(CheckIn {
+                        id,
+                        name: row.get("name"),
+                        checked_in_at: row.get::<Option<DateTime<Utc>>, _>("checked_in_at"),
+                    })
+                })
+                .collect()
+        })
+    }
+
+    // who the VaderBoard leaves out as the event starts , nothing is removed from
+    // the event : )
+    pub fn unchecked(&'a self, db_pool: &'a SqlitePool) -> AsyncDbRes<'a, Vec<Uuid>> {
+        let (link, col, _) = self.participants();
+        let event_id = self.get_id().to_string();
+        let exclude = match self {
+            Self::TeamEvent(EventStateWrapper::New(e)) => e.exclude_unchecked,
+            Self::UserEvent(EventStateWrapper::New(e)) => e.exclude_unchecked,
+            _ => false,
+        };
+        Box::pin(async move {
+            if !exclude {
+                return Ok(Vec::new());
+            }
+            let ids: Vec<String> = sqlx::query_scalar(&format!(
+                "SELECT {col} FROM {link} WHERE event_id = ? AND checked_in_at IS NULL"
+            ))
+            .bind(event_id)
+            .fetch_all(db_pool)
+            .await?;
+            ids.iter()
+                .map(|id| {
+                    Uuid::parse_str(id)
+                        .map_err(|_e| VaderError::SqlxFieldError("Error decoding Participant Id"))
+                })
+                .collect()
+        })
+    }
+}
+
+pub fn checkin_secret(config: &CheckinConfig) -> Result<&str, VaderError<'static>> {
+    config.secret.as_deref().ok_or_else(|| {
+        VaderError::InvalidRequest("Check-in tokens are disabled , set checkin.secret".to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "a check-in secret of atleast 32 bytes";
+
+    #[test]
+    fn token_round_trip() {
+        let (event_id, id) = (Uuid::new_v4(), Uuid::new_v4());
+        let token = checkin_token(SECRET, &event_id, &id);
+        assert_eq!(verify_checkin_token(SECRET, &event_id, &token).unwrap(), id);
+        // scanners may add whitespace
+        let padded = format!(" {}\n", token);
+        assert_eq!(
+            verify_checkin_token(SECRET, &event_id, &padded).unwrap(),
+            id
+        );
+    }
+
+    #[test]
+    fn token_of_another_event_fails() {
+        let id = Uuid::new_v4();
+        let token = checkin_token(SECRET, &Uuid::new_v4(), &id);
+        assert!(verify_checkin_token(SECRET, &Uuid::new_v4(), &token).is_err());
+    }
+
+    #[test]
+    fn token_of_another_secret_fails() {
+        let (event_id, id) = (Uuid::new_v4(), Uuid::new_v4());
+        let token = checkin_token("another secret of atleast 32 bytes!", &event_id, &id);
+        assert!(verify_checkin_token(SECRET, &event_id, &token).is_err());
+    }
+
+    #[test]
+    fn tampered_token_fails() {
+        let event_id = Uuid::new_v4();
+        let token = checkin_token(SECRET, &event_id, &Uuid::new_v4());
+        let (_, sig) = token.split_once('.').unwrap();
+        // someone else's id with the signature kept
+        let swapped = format!("{}.{}", Uuid::new_v4(), sig);
+        assert!(verify_checkin_token(SECRET, &event_id, &swapped).is_err());
+        // a flipped signature character
+        let mut flipped = token.clone().into_bytes();
+        let last = flipped.len() - 1;
+        flipped[last] = if flipped[last] == b'A' { b'B' } else { b'A' };
+        let flipped = String::from_utf8(flipped).unwrap();
+        assert!(verify_checkin_token(SECRET, &event_id, &flipped).is_err());
+        // a cut signature
+        assert!(verify_checkin_token(SECRET, &event_id, &token[..token.len() - 4]).is_err());
+    }
+
+    #[test]
+    fn malformed_token_fails() {
+        let event_id = Uuid::new_v4();
+        for token in [
+            "",
+            ".",
+            "no-dot",
+            "not-a-uuid.c2ln",
+            &format!("{}.", Uuid::new_v4()),
+        ] {
+            assert!(matches!(
+                verify_checkin_token(SECRET, &event_id, token),
+                Err(VaderError::InvalidRequest(_))
+            ));
+        }
+        let bad_base64 = format!("{}.!!!", Uuid::new_v4());
+        assert!(verify_checkin_token(SECRET, &event_id, &bad_base64).is_err());
+    }
+}
@@ -0,0 +1,283 @@
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use actix_cors::Cors;
+use actix_web::cookie::Key;
+use log::LevelFilter;
+
+use crate::models::config_models::{ConfigOverrides, VaderArgs, VaderConfig};
+use crate::models::error_models::VaderError;
+
+const DEFAULT_CONFIG_PATH: &str = "vaderboard.toml";
+// actix cookie Key needs atleast 64 bytes : )
+const MIN_SESSION_KEY_LEN: usize = 64;
+const MIN_CHECKIN_SECRET_LEN: usize = 32;
+
+impl VaderConfig {
+    pub fn load(args: &VaderArgs) -> Result<Self, VaderError<'static>> {
+        let mut config = match &args.config {
+            Some(path) => Self::from_file(path)?,
+            None if Path::new(DEFAULT_CONFIG_PATH).exists() => {
+                Self::from_file(Path::new(DEFAULT_CONFIG_PATH))?
+            }
+            None => Self::default(),
+        };
+        config.apply_overrides(&args.overrides);
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn from_file(path: &Path) -> Result<Self, VaderError<'static>> {
+        let content = fs::read_to_string(path).map_err(|e| {
+            VaderError::ConfigError(format!(
+                "Unable to read config file {} : {}",
+                path.display(),
+                e
+            ))
+        })?;
+        toml::from_str(&content).map_err(|e| {
+            VaderError::ConfigError(format!(
+                "Unable to parse config file {} : {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+
+    fn apply_overrides(&mut self, o: &ConfigOverrides) {
+        if let Some(host) = &o.host {
+            self.server.host = host.clone();
+        }
+        if let Some(port) = o.port {
+            self.server.port = port;
+        }
+        if let Some(workers) = o.workers {
+            self.server.workers = Some(workers);
+        }
+        if let Some(dist_dir) = &o.dist_dir {
+            self.server.dist_dir = dist_dir.clone();
+        }
+        if let Some(url) = &o.database_url {
+            self.database.url = url.clone();
+        }
+        if let Some(auto_migrate) = o.auto_migrate {
+            self.database.auto_migrate = auto_migrate;
+        }
+        if let Some(username) = &o.admin_username {
+            self.admin.username = Some(username.clone());
+        }
+        if let Some(password) = &o.admin_password {
+            self.admin.password = Some(password.clone());
+        }
+        if let Some(key) = &o.session_key {
+            self.session.key = Some(key.clone());
+        }
+        if let Some(secure) = o.cookie_secure {
+            self.session.cookie_secure = secure;
+        }
+        if let Some(origins) = &o.cors_origins {
+            self.cors.allowed_origins = origins.clone();
+        }
+        if let Some(count) = o.vb_count {
+            self.vaderboard.count = count;
+        }
+        if let Some(secs) = o.vb_cache_secs {
+            self.vaderboard.cache_secs = secs;
+        }
+        if let Some(secs) = o.ws_heartbeat {
+            self.websocket.heartbeat_secs = secs;
+        }
+        if let Some(secs) = o.ws_timeout {
+            self.websocket.timeout_secs = secs;
+        }
+        if let Some(max) = o.ws_max_connections {
+            self.websocket.max_connections = max;
+        }
+        if let Some(max) = o.ws_max_per_ip {
+            self.websocket.max_per_ip = max;
+        }
+        if let Some(level) = &o.log_level {
+            self.log.level = level.clone();
+        }
+        if let Some(format) = o.log_format {
+            self.log.format = format;
+        }
+        if let Some(dir) = &o.backup_dir {
+            self.backup.dir = dir.clone();
+        }
+        if let Some(interval) = o.backup_interval {
+            self.backup.interval_secs = Some(interval);
+        }
+        if let Some(retention) = o.backup_retention {
+            self.backup.retention = retention;
+        }
+        if let Some(days) = o.trash_retention_days {
+            self.trash.retention_days = days;
+        }
+        if let Some(days) = o.audit_retention_days {
+            self.audit.retention_days = days;
+        }
+        if let Some(secret) = &o.checkin_secret {
+            self.checkin.secret = Some(secret.clone());
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), VaderError<'static>> {
+        let mut errors: Vec<String> = Vec::new();
+        if self.server.host.trim().is_empty() {
+            errors.push("server.host cannot be empty".to_string());
+        }
+        if self.server.port == 0 {
+            errors.push("server.port must be between 1 and 65535".to_string());
+        }
+        if let Some(0) = self.server.workers {
+            errors.push("server.workers must be greater than 0".to_string());
+        }
+        if !self.database.url.starts_with("sqlite:") {
+            errors.push(format!(
+                "database.url must be a sqlite url (sqlite:<path>), found : {}",
+                self.database.url
+            ));
+        }
+        if let Some(key) = &self.session.key {
+            if key.len() < MIN_SESSION_KEY_LEN {
+                errors.push(format!(
+                    "session.key must be atleast {} bytes long, found : {} bytes",
+                    MIN_SESSION_KEY_LEN,
+                    key.len()
+                ));
+            }
+        }
+        if let Some(secret) = &self.checkin.secret {
+            if secret.len() < MIN_CHECKIN_SECRET_LEN {
+                errors.push(format!(
+                    "checkin.secret must be atleast {} bytes long, found : {} bytes",
+                    MIN_CHECKIN_SECRET_LEN,
+                    secret.len()
+                ));
+            }
+        }
+        if self.cors.allowed_origins.is_empty() {
+            errors.push("cors.allowed_origins cannot be empty, use [\"*\"] to allow any".into());
+        }
+        for origin in &self.cors.allowed_origins {
+            if origin != "*" && !(origin.starts_with("http://") || origin.starts_with("https://")) {
+                errors.push(format!(
+                    "cors.allowed_origins entry must start with http:// or https:// , found : {}",
+                    origin
+                ));
+            }
+        }
+        if self.vaderboard.count == 0 {
+            errors.push("vaderboard.count must be greater than 0".to_string());
+        }
+        if self.websocket.heartbeat_secs == 0 {
+            errors.push("websocket.heartbeat_secs must be greater than 0".to_string());
+        }
+        if self.websocket.timeout_secs <= self.websocket.heartbeat_secs {
+            errors.push(format!(
+                "websocket.timeout_secs must be greater than websocket.heartbeat_secs ({}) , found : {}",
+                self.websocket.heartbeat_secs, self.websocket.timeout_secs
+            ));
+        }
+        if self.websocket.max_connections == 0 {
+            errors.push("websocket.max_connections must be greater than 0".to_string());
+        }
+        if self.websocket.max_per_ip == 0 {
+            errors.push("websocket.max_per_ip must be greater than 0".to_string());
+        }
+        if self.websocket.max_count == 0 {
+            errors.push("websocket.max_count must be greater than 0".to_string());
+        }
+        if self.websocket.queries_per_sec == 0 {
+            errors.push("websocket.queries_per_sec must be greater than 0".to_string());
+        }
+        if self.registration.max_per_ip == 0 {
+            errors.push("registration.max_per_ip must be greater than 0".to_string());
+        }
+        if self.registration.window_secs == 0 {
+            errors.push("registration.window_secs must be greater than 0".to_string());
+        }
+        if LevelFilter::from_str(&self.log.level).is_err() {
+            errors.push(format!(
+                "log.level must be one of off,error,warn,info,debug,trace , found : {}",
+                self.log.level
+            ));
+        }
+        if let Some(0) = self.backup.interval_secs {
+            errors.push("backup.interval_secs must be greater than 0".to_string());
+        }
+        if self.backup.retention == 0 {
+            errors.push("backup.retention must be greater than 0".to_string());
+        }
+        match (&self.admin.username, &self.admin.password) {
+            (Some(username), Some(password)) => {
+                if username.trim().is_empty() || password.is_empty() {
+                    errors.push("admin.username and admin.password cannot be empty".to_string());
+                }
+            }
+            (None, None) => {}
+            _ => errors.push("admin.username and admin.password must be set together".to_string()),
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(VaderError::ConfigError(errors.join("\n")))
+        }
+    }
+
+    pub fn workers(&self) -> usize {
+        self.server.workers.unwrap_or_else(|| num_cpus::get() * 2)
+    }
+
+    pub fn host_port(&self) -> String {
+        format!("{}:{}", self.server.host, self.server.port)
+    }
+
+    pub fn log_level(&self) -> LevelFilter {
+        LevelFilter::from_str(&self.log.level).unwrap_or(LevelFilter::Info)
+    }
+
+    pub fn session_key(&self) -> Key {
+        match &self.session.key {
+            Some(key) => Key::from(key.as_bytes()),
+            None => {
+                log::warn!("session.key not set, sessions won't survive a restart");
+                Key::generate()
+            }
+        }
+    }
+
+    pub fn cors(&self) -> Cors {
+        if self.cors.allowed_origins.iter().any(|o| o == "*") {
+            //warning : Never use Cors::permissive in production : )
+            Cors::permissive()
+        } else {
+            self.cors
+                .allowed_origins
+                .iter()
+                .fold(Cors::default(), |cors, origin| cors.allowed_origin(origin))
+                .allow_any_method()
+                .allow_any_header()
+                .supports_credentials()
+        }
+    }
+
+    // secrets are redacted before printing : )
+    pub fn to_toml(&self) -> Result<String, VaderError<'static>> {
+        let mut config = self.clone();
+        config.server.workers = Some(self.workers());
+        if config.session.key.is_some() {
+            config.session.key = Some("<redacted>".to_string());
+        }
+        if config.admin.password.is_some() {
+            config.admin.password = Some("<redacted>".to_string());
+        }
+        if config.checkin.secret.is_some() {
+            config.checkin.secret = Some("<redacted>".to_string());
+        }
+        toml::to_string_pretty(&config)
+            .map_err(|e| VaderError::ConfigError(format!("Unable to serialize config : {}", e)))
+    }
+}
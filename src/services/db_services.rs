@@ -0,0 +1,88 @@
+use sqlx::migrate::{MigrateDatabase, Migrator};
+use sqlx::{Sqlite, SqlitePool};
+
+use crate::models::config_models::VaderConfig;
+use crate::models::error_models::VaderError;
+use crate::models::v_models::AdminInfo;
+
+// migrations are embedded in the binary , no sqlx-cli needed at runtime : )
+pub static MIGRATOR: Migrator = sqlx::migrate!();
+
+pub async fn connect_db(config: &VaderConfig) -> Result<SqlitePool, VaderError<'static>> {
+    let db_url = &config.database.url;
+    if !Sqlite::database_exists(db_url).await? {
+        log::info!("Creating Vaderboard database");
+        Sqlite::create_database(db_url).await?;
+    }
+    let db_pool = SqlitePool::connect(db_url).await?;
+    Ok(db_pool)
+}
+
+pub async fn run_migrations(db_pool: &SqlitePool) -> Result<(), VaderError<'static>> {
+    log::info!("Running Vaderboard Migrations");
+    // table rebuilds drop the old table , with foreign keys on that would cascade
+    // into every child row , the pragma is ignored inside the migration transaction
+    // so it is set on the connection before
+    let mut conn = db_pool.acquire().await?;
+    sqlx::query("PRAGMA foreign_keys = OFF")
+        .execute(&mut *conn)
+        .await?;
+    let res = MIGRATOR.run(&mut *conn).await;
+    let violations = sqlx::query("PRAGMA foreign_key_check")
+        .fetch_all(&mut *conn)
+        .await;
+    sqlx::query("PRAGMA foreign_keys = ON")
+        .execute(&mut *conn)
+        .await?;
+    res?;
+    let violations = violations?.len();
+    if violations > 0 {
+        log::warn!(
+            "{} rows point to missing parents , check PRAGMA foreign_key_check",
+            violations
+        );
+    }
+    Ok(())
+}
+
+pub async fn seed_admin<'a>(
+    config: &VaderConfig,
+    db_pool: &'a SqlitePool,
+) -> Result<(), VaderError<'a>> {
+    if let (Some(username), Some(password)) = (&config.admin.username, &config.admin.password) {
+        // auto_migrate = false and `vader-admin migrate` not run yet , nothing to seed into
+        if !admin_table_exists(db_pool).await? {
+            log::warn!("admin_login table missing , run migrations to register the admin");
+            return Ok(());
+        }
+        let admin = AdminInfo {
+            username: username.clone(),
+            password: password.clone(),
+        };
+        if admin.add_admin(db_pool).await? {
+            log::info!("Successfully registered admin cred");
+        }
+    }
+    Ok(())
+}
+
+async fn admin_table_exists(db_pool: &SqlitePool) -> Result<bool, VaderError<'static>> {
+    let table: Option<String> = sqlx::query_scalar(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name = 'admin_login'",
+    )
+    .fetch_optional(db_pool)
+    .await?;
+    Ok(table.is_some())
+}
+
+// every connection to :memory: opens a database of its own , so just the one
+#[cfg(test)]
+pub async fn test_pool() -> SqlitePool {
+    let db_pool = sqlx::sqlite::SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("in memory database");
+    run_migrations(&db_pool).await.expect("migrations");
+    db_pool
+}
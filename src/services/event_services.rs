@@ -1,36 +1,38 @@
 use std::borrow::Cow;
 use std::marker::PhantomData;
-use std::ops::Deref;
-use std::sync::Arc;
 
 use chrono::Utc;
-use sqlx::SqlitePool;
+use sqlx::{SqliteConnection, SqlitePool};
 use uuid::Uuid;
 
+use super::page_services::fetch_page;
 use super::query_services::Queriable;
+use super::search_services::search;
+use crate::models::command_models::PlayerPatch;
 use crate::models::error_models::VaderError;
+use crate::models::membership_models::TeamCapacity;
+use crate::models::page_models::{Page, PageQuery, SortKey};
 use crate::models::query_models::TeamInfo;
+use crate::models::search_models::{SearchHit, SearchSpec};
 use crate::models::v_models::{
     ActiveEvent, AsyncDbRes, EndEvent, Event, EventState, NewEvent, Player, Team, User, VaderEvent,
 };
 
 impl<'a> Player<'a> for User<'a> {
-    fn add_player(&'a self, db_pool: &'a SqlitePool) -> AsyncDbRes<'a, ()> {
+    fn add_player<'c>(&'c self, conn: &'c mut SqliteConnection) -> AsyncDbRes<'c, ()> {
         let id = self.id.to_string();
         let name = &self.name;
         let logo = self.get_logo();
         let created_at = Utc::now();
         Box::pin(async move {
-            sqlx::query!(
-                "INSERT INTO users (id,name,score,logo,created_at) VALUES (?,?,?,?,?)",
-                id,
-                name,
-                self.score,
-                logo,
-                created_at
-            )
-            .execute(db_pool)
-            .await?;
+            sqlx::query("INSERT INTO users (id,name,score,logo,created_at) VALUES (?,?,?,?,?)")
+                .bind(id)
+                .bind(name)
+                .bind(self.score)
+                .bind(logo)
+                .bind(created_at)
+                .execute(conn)
+                .await?;
             Ok(())
         })
     }
@@ -43,22 +45,20 @@ impl<'a> Player<'a> for User<'a> {
 }
 
 impl<'a> Player<'a> for Team<'a> {
-    fn add_player(&'a self, db_pool: &'a SqlitePool) -> AsyncDbRes<'a, ()> {
+    fn add_player<'c>(&'c self, conn: &'c mut SqliteConnection) -> AsyncDbRes<'c, ()> {
         Box::pin(async move {
             let id = self.id.to_string();
             let name = &self.name;
             let logo = self.get_logo();
             let created_at = Utc::now();
-            sqlx::query!(
-                "INSERT INTO teams (id,name,score,logo,created_at) VALUES (?,?,?,?,?)",
-                id,
-                name,
-                self.score,
-                logo,
-                created_at
-            )
-            .execute(db_pool)
-            .await?;
+            sqlx::query("INSERT INTO teams (id,name,score,logo,created_at) VALUES (?,?,?,?,?)")
+                .bind(id)
+                .bind(name)
+                .bind(self.score)
+                .bind(logo)
+                .bind(created_at)
+                .execute(conn)
+                .await?;
             Ok(())
         })
     }
@@ -69,108 +69,57 @@ impl<'a> Player<'a> for Team<'a> {
         self.logo.clone().unwrap_or(Cow::Borrowed("")).to_string()
     }
 }
-//Vec newtype : )
-#[derive(Clone)]
-struct Uarc(Arc<Vec<Uuid>>);
-impl Deref for Uarc {
-    type Target = [Uuid];
-
-    fn deref(&self) -> &Self::Target {
-        self.0.as_slice()
-    }
-}
 impl<'a> Team<'a> {
-    pub fn with_members(
-        &'a self,
-        mem_users: &'a [User<'a>],
+    // team , users and memberships on the caller's transaction , any failure
+    // rolls back all of them : )
+    pub fn with_members<'c>(
+        &'c self,
+        mem_users: &'c [User<'c>],
         team_size: usize,
-        db_pool: &'a SqlitePool,
-    ) -> AsyncDbRes<'a, ()> {
+        conn: &'c mut SqliteConnection,
+    ) -> AsyncDbRes<'c, ()> {
         Box::pin(async move {
             if mem_users.len() > team_size {
-                return Err(VaderError::TeamSizeMismatch(
-                    "No of members greater than team size",
-                ));
+                return Err(VaderError::TeamSizeMismatch(TeamCapacity {
+                    current: 0,
+                    max: team_size as u32,
+                    adding: mem_users.len() as u32,
+                }));
             }
-            self.add_player(db_pool).await?;
-            let members: Uarc = Uarc(Arc::new(
-                mem_users.iter().map(|u| u.id).collect::<Vec<Uuid>>(),
-            ));
+            self.add_player(&mut *conn).await?;
             for mem_user in mem_users {
-                // todo! implement add_users()
-                // can be optimized by using single transaction
-                // athakumbol our player add aayillelum full rollback cheyyam : )
-                mem_user.add_player(db_pool).await?;
+                mem_user
+                    .add_player(&mut *conn)
+                    .await
+                    .inspect_err(|_| log::error!("Unable to add member : {}", mem_user.name))?;
             }
-            Self::add_members_from_id(&self.id, members.clone(), db_pool).await
-        })
-    }
-
-    fn add_members_from_id<T>(
-        team_id: &'a Uuid,
-        members: T,
-        db_pool: &'a SqlitePool,
-    ) -> AsyncDbRes<'a, ()>
-    where
-        T: Send + 'a + Deref<Target = [Uuid]>,
-    {
-        Box::pin(async move {
-            let mut transaction = db_pool.begin().await?;
-            let team_id = team_id.to_string();
-            for mem_id in members.as_ref() {
-                let user_id = mem_id.to_string();
-                let res = sqlx::query!(
-                    "INSERT INTO team_members (team_id,user_id) VALUES (?,?)",
-                    team_id,
-                    user_id,
-                )
-                .execute(&mut *transaction)
-                .await;
-                match res {
-                    Ok(c) => {
-                        if c.rows_affected().eq(&0) {
-                            transaction.rollback().await?;
-                            return Err(VaderError::TeamNotFound(
-                                "No Team found to Add Team Members",
-                            ));
-                        }
-                    }
-                    Err(err) => {
-                        transaction.rollback().await?;
-                        log::error!("Unable to add member :  {}", mem_id);
-                        return Err(VaderError::SqlxError(err));
-                    }
-                }
-            }
-            transaction.commit().await?;
-            Ok(())
+            let members = mem_users.iter().map(|u| u.id).collect::<Vec<Uuid>>();
+            Self::add_members_from_id(&self.id, &members, conn).await
         })
     }
 }
 impl<'a> VaderEvent<'a> for Event<'a, Team<'a>> {
     type Participant = Team<'a>;
-    fn add_participant(
-        &'a self,
+    fn add_participant<'c>(
+        &'c self,
         participant: &Self::Participant,
-        db_pool: &'a SqlitePool,
-    ) -> AsyncDbRes<'a, ()> {
-        Self::add_participant_from_id(self, participant.get_id(), db_pool)
+        conn: &'c mut SqliteConnection,
+    ) -> AsyncDbRes<'c, ()> {
+        Self::add_participant_from_id(self, participant.get_id(), conn)
     }
-    fn add_participant_from_id(
-        &'a self,
+    fn add_participant_from_id<'c>(
+        &'c self,
         team_id: Uuid,
-        db_pool: &'a SqlitePool,
-    ) -> AsyncDbRes<'a, ()> {
+        conn: &'c mut SqliteConnection,
+    ) -> AsyncDbRes<'c, ()> {
         let event_id = self.id.to_string();
         let team_id = team_id.to_string();
         Box::pin(async move {
-            sqlx::query!(
-                "INSERT INTO event_teams (event_id,team_id) VALUES (?,?)",
-                event_id,
-                team_id
-            )
-            .execute(db_pool)
-            .await?;
+            sqlx::query("INSERT INTO event_teams (event_id,team_id) VALUES (?,?)")
+                .bind(event_id)
+                .bind(team_id)
+                .execute(conn)
+                .await?;
             Ok(())
         })
     }
@@ -182,20 +131,25 @@ impl<'a> VaderEvent<'a> for Event<'a, Team<'a>> {
         let created_at = Utc::now();
         Box::pin(async move {
             if let Some(team_size) = self.team_size {
-                sqlx::query!(
-                    "INSERT INTO events (id,name,logo,event_type,team_size,created_at) VALUES (?,?,?,?,?,?)",
-                    id,
-                    name,
-                    logo,
-                    "team_event",
-                    team_size,
-                    created_at
+                sqlx::query(
+                    "INSERT INTO events (id,name,logo,event_type,team_size,min_score,max_score,max_delta,exclude_unchecked,state,created_at)
+                     VALUES (?,?,?,?,?,?,?,?,?,'added',?)",
                 )
+                .bind(id)
+                .bind(name)
+                .bind(logo)
+                .bind("team_event")
+                .bind(team_size)
+                .bind(self.score_limits.min_score)
+                .bind(self.score_limits.max_score)
+                .bind(self.score_limits.max_delta)
+                .bind(self.exclude_unchecked)
+                .bind(created_at)
                 .execute(db_pool)
                 .await?;
                 Ok(())
             } else {
-                return Err(VaderError::TeamSizeMismatch("Team size not specified"));
+                Err(VaderError::EventTypeMismatch("Team size not specified"))
             }
         })
     }
@@ -206,28 +160,26 @@ impl<'a> VaderEvent<'a> for Event<'a, Team<'a>> {
 
 impl<'a> VaderEvent<'a> for Event<'a, User<'a>> {
     type Participant = User<'a>;
-    fn add_participant(
-        &'a self,
+    fn add_participant<'c>(
+        &'c self,
         participant: &Self::Participant,
-        db_pool: &'a SqlitePool,
-    ) -> AsyncDbRes<'a, ()> {
-        Self::add_participant_from_id(self, participant.get_id(), db_pool)
+        conn: &'c mut SqliteConnection,
+    ) -> AsyncDbRes<'c, ()> {
+        Self::add_participant_from_id(self, participant.get_id(), conn)
     }
-    fn add_participant_from_id(
-        &'a self,
+    fn add_participant_from_id<'c>(
+        &'c self,
         user_id: Uuid,
-        db_pool: &'a SqlitePool,
-    ) -> AsyncDbRes<'a, ()> {
+        conn: &'c mut SqliteConnection,
+    ) -> AsyncDbRes<'c, ()> {
         let event_id = self.id.to_string();
         let user_id = user_id.to_string();
         Box::pin(async move {
-            sqlx::query!(
-                "INSERT INTO event_users (event_id,user_id) VALUES (?,?)",
-                event_id,
-                user_id
-            )
-            .execute(db_pool)
-            .await?;
+            sqlx::query("INSERT INTO event_users (event_id,user_id) VALUES (?,?)")
+                .bind(event_id)
+                .bind(user_id)
+                .execute(conn)
+                .await?;
             Ok(())
         })
     }
@@ -237,14 +189,19 @@ impl<'a> VaderEvent<'a> for Event<'a, User<'a>> {
         let name = &self.name;
         let created_at = Utc::now();
         Box::pin(async move {
-            sqlx::query!(
-                "INSERT INTO events (id,name,logo,event_type,created_at) VALUES (?,?,?,?,?)",
-                id,
-                name,
-                logo,
-                "user_event",
-                created_at
+            sqlx::query(
+                "INSERT INTO events (id,name,logo,event_type,min_score,max_score,max_delta,exclude_unchecked,state,created_at)
+                 VALUES (?,?,?,?,?,?,?,?,'added',?)",
             )
+            .bind(id)
+            .bind(name)
+            .bind(logo)
+            .bind("user_event")
+            .bind(self.score_limits.min_score)
+            .bind(self.score_limits.max_score)
+            .bind(self.score_limits.max_delta)
+            .bind(self.exclude_unchecked)
+            .bind(created_at)
             .execute(db_pool)
             .await?;
             Ok(())
@@ -263,28 +220,49 @@ impl<'a> Event<'a, User<'a>, ActiveEvent> {
         db_pool: &'a SqlitePool,
     ) -> AsyncDbRes<'a, ()> {
         let id = user_id.to_string();
+        let limits = self.score_limits;
         Box::pin(async move {
-            let res = sqlx::query("UPDATE users set score=score+? WHERE id=?")
-                .bind(points)
-                .bind(id)
-                .execute(db_pool)
-                .await;
-            match res {
-                Ok(_) => Ok(()),
-                Err(err) => return Err(VaderError::SqlxError(err)),
+            limits.check_delta(points)?;
+            // bounds checked in the update itself , no read-modify-write race : )
+            let res = sqlx::query(
+                "UPDATE users set score=score+?1 WHERE id=?2 AND deleted_at IS NULL
+                 AND (?3 IS NULL OR score+?1 >= ?3) AND (?4 IS NULL OR score+?1 <= ?4)",
+            )
+            .bind(points)
+            .bind(&id)
+            .bind(limits.min_score)
+            .bind(limits.max_score)
+            .execute(db_pool)
+            .await?;
+            if res.rows_affected() == 0 {
+                let exists = sqlx::query("SELECT 1 FROM users WHERE id=? AND deleted_at IS NULL")
+                    .bind(&id)
+                    .fetch_optional(db_pool)
+                    .await?;
+                return match exists {
+                    Some(_) => Err(limits.out_of_bounds()),
+                    None => Err(VaderError::UserNotFound("No user found")),
+                };
             }
+            Ok(())
         })
     }
 }
 
 impl<'a> Event<'a, Team<'a>> {
+    // all members or none
     pub fn add_team_members(
         &'a self,
         team_id: &'a Uuid,
         members: &'a [Uuid],
         db_pool: &'a SqlitePool,
-    ) -> AsyncDbRes<()> {
-        Team::add_members_from_id(team_id, members, db_pool)
+    ) -> AsyncDbRes<'a, ()> {
+        Box::pin(async move {
+            let mut transaction = db_pool.begin().await?;
+            Team::add_members_from_id(team_id, members, &mut transaction).await?;
+            transaction.commit().await?;
+            Ok(())
+        })
     }
 }
 
@@ -296,16 +274,31 @@ impl<'a> Event<'a, Team<'a>, ActiveEvent> {
         db_pool: &'a SqlitePool,
     ) -> AsyncDbRes<'a, ()> {
         let id = team_id.to_string();
+        let limits = self.score_limits;
         Box::pin(async move {
-            let res = sqlx::query("UPDATE teams set score=score+? WHERE id=?")
-                .bind(points)
-                .bind(id)
-                .execute(db_pool)
-                .await;
-            match res {
-                Ok(_) => Ok(()),
-                Err(err) => return Err(VaderError::SqlxError(err)),
+            limits.check_delta(points)?;
+            // bounds checked in the update itself , no read-modify-write race : )
+            let res = sqlx::query(
+                "UPDATE teams set score=score+?1 WHERE id=?2 AND deleted_at IS NULL
+                 AND (?3 IS NULL OR score+?1 >= ?3) AND (?4 IS NULL OR score+?1 <= ?4)",
+            )
+            .bind(points)
+            .bind(&id)
+            .bind(limits.min_score)
+            .bind(limits.max_score)
+            .execute(db_pool)
+            .await?;
+            if res.rows_affected() == 0 {
+                let exists = sqlx::query("SELECT 1 FROM teams WHERE id=? AND deleted_at IS NULL")
+                    .bind(&id)
+                    .fetch_optional(db_pool)
+                    .await?;
+                return match exists {
+                    Some(_) => Err(limits.out_of_bounds()),
+                    None => Err(VaderError::TeamNotFound("No team found")),
+                };
             }
+            Ok(())
         })
     }
 }
@@ -314,17 +307,17 @@ impl<'a> Event<'a, Team<'a>, NewEvent> {
     pub fn reset_score(&self, db_pool: &'a SqlitePool) -> AsyncDbRes<'a, ()> {
         let event_id = self.id.to_string();
         Box::pin(async move {
-            sqlx::query!(
+            sqlx::query(
                 "UPDATE teams SET score=0 WHERE 
                  id IN(SELECT t.id FROM events e 
                  JOIN event_teams et ON et.event_id = e.id 
                  JOIN teams t ON et.team_id = t.id 
                  WHERE e.id = ?)",
-                event_id
             )
+            .bind(&event_id)
             .execute(db_pool)
             .await?;
-            sqlx::query!(
+            sqlx::query(
                 "UPDATE users SET score=0 
                  WHERE id IN(SELECT u.id FROM events e 
                  JOIN event_teams et ON et.event_id = e.id 
@@ -332,8 +325,8 @@ impl<'a> Event<'a, Team<'a>, NewEvent> {
                  JOIN team_members tm ON tm.team_id = t.id 
                  JOIN users u ON tm.user_id = u.id 
                  WHERE e.id = ? )",
-                event_id
             )
+            .bind(event_id)
             .execute(db_pool)
             .await?;
             Ok(())
@@ -344,14 +337,14 @@ impl<'a> Event<'a, User<'a>, NewEvent> {
     pub fn reset_score(&self, db_pool: &'a SqlitePool) -> AsyncDbRes<'a, ()> {
         let event_id = self.id.to_string();
         Box::pin(async move {
-            sqlx::query!(
+            sqlx::query(
                 "UPDATE users SET score=0 
                  WHERE id IN(SELECT u.id FROM events e 
                  JOIN event_users eu ON eu.event_id = e.id 
                  JOIN users u ON eu.user_id = u.id 
                  WHERE e.id = ?)",
-                event_id
             )
+            .bind(event_id)
             .execute(db_pool)
             .await?;
             Ok(())
@@ -389,6 +382,8 @@ where
             player_marker: PhantomData::<&'a T>,
             state_marker: PhantomData::<&'a ActiveEvent>,
             team_size: e.team_size,
+            score_limits: e.score_limits,
+            exclude_unchecked: e.exclude_unchecked,
         }
     }
 }
@@ -404,6 +399,8 @@ where
             player_marker: PhantomData::<&'a T>,
             state_marker: PhantomData::<&'a EndEvent>,
             team_size: e.team_size,
+            score_limits: e.score_limits,
+            exclude_unchecked: e.exclude_unchecked,
         }
     }
 }
@@ -415,17 +412,21 @@ impl<'a> Team<'a> {
             name,
             logo,
             members: Vec::new(),
+            captain: None,
             score: 0,
         }
     }
-    pub fn get_team<'b>(team_id: &'b Uuid, db_pool: &'b SqlitePool) -> AsyncDbRes<'b, Self> {
+    pub fn get_team<'b>(team_id: &Uuid, db_pool: &'b SqlitePool) -> AsyncDbRes<'b, Self> {
         let id = team_id.to_string();
         Box::pin(async move {
             let team = sqlx::query_as::<_, Team>(
                 "SELECT t.id AS id,t.name AS name, t.score AS score,t.logo AS logo,
-                GROUP_CONCAT(tm.user_id,',') AS team_members 
-                FROM teams t JOIN team_members tm ON tm.team_id = t.id 
-                WHERE t.id = ? GROUP BY t.id",
+                GROUP_CONCAT(u.id,',') AS team_members,
+                (SELECT c.user_id FROM team_members c JOIN users cu ON cu.id = c.user_id
+                 WHERE c.team_id = t.id AND c.role = 'captain' AND cu.deleted_at IS NULL) AS captain
+                FROM teams t LEFT JOIN team_members tm ON tm.team_id = t.id 
+                LEFT JOIN users u ON u.id = tm.user_id AND u.deleted_at IS NULL
+                WHERE t.id = ? AND t.deleted_at IS NULL GROUP BY t.id",
             )
             .bind(id)
             .fetch_one(db_pool)
@@ -433,12 +434,39 @@ impl<'a> Team<'a> {
             Ok(team)
         })
     }
+    // fts triggers reindex the new name/logo : )
+    pub fn update_team<'b>(
+        id: &Uuid,
+        patch: &'b PlayerPatch,
+        db_pool: &'b SqlitePool,
+    ) -> AsyncDbRes<'b, ()> {
+        let id = id.to_string();
+        Box::pin(async move {
+            let res = sqlx::query(
+                "UPDATE teams SET name=COALESCE(?,name),logo=COALESCE(?,logo)
+                 WHERE id = ? AND deleted_at IS NULL",
+            )
+            .bind(&patch.name)
+            .bind(&patch.logo)
+            .bind(id)
+            .execute(db_pool)
+            .await?;
+            if res.rows_affected().eq(&0) {
+                return Err(VaderError::TeamNotFound("No team found"));
+            }
+            Ok(())
+        })
+    }
+    // moves the team to the trash , members and event links are kept for a restore
     pub fn delete_team<'b>(id: &'b Uuid, db_pool: &'b SqlitePool) -> AsyncDbRes<'b, ()> {
         let id = id.to_string();
         Box::pin(async move {
-            let res = sqlx::query!("DELETE FROM teams WHERE id = ? ", id)
-                .execute(db_pool)
-                .await?;
+            let res =
+                sqlx::query("UPDATE teams SET deleted_at = ? WHERE id = ? AND deleted_at IS NULL")
+                    .bind(Utc::now())
+                    .bind(id)
+                    .execute(db_pool)
+                    .await?;
             if res.rows_affected().eq(&0) {
                 return Err(VaderError::TeamNotFound("No team found"));
             }
@@ -455,23 +483,50 @@ impl<'a> User<'a> {
             score: 0,
         }
     }
-    pub fn get_user<'b>(user_id: &'b Uuid, db_pool: &'b SqlitePool) -> AsyncDbRes<'b, Self> {
+    pub fn get_user<'b>(user_id: &Uuid, db_pool: &'b SqlitePool) -> AsyncDbRes<'b, Self> {
         let id = user_id.to_string();
         Box::pin(async move {
-            let user =
-                sqlx::query_as::<_, User>("SELECT id,name,score,logo FROM users WHERE id = ?")
-                    .bind(id)
-                    .fetch_one(db_pool)
-                    .await?;
+            let user = sqlx::query_as::<_, User>(
+                "SELECT id,name,score,logo FROM users WHERE id = ? AND deleted_at IS NULL",
+            )
+            .bind(id)
+            .fetch_one(db_pool)
+            .await?;
             Ok(user)
         })
     }
+    pub fn update_user<'b>(
+        id: &Uuid,
+        patch: &'b PlayerPatch,
+        db_pool: &'b SqlitePool,
+    ) -> AsyncDbRes<'b, ()> {
+        let id = id.to_string();
+        Box::pin(async move {
+            let res = sqlx::query(
+                "UPDATE users SET name=COALESCE(?,name),logo=COALESCE(?,logo)
+                 WHERE id = ? AND deleted_at IS NULL",
+            )
+            .bind(&patch.name)
+            .bind(&patch.logo)
+            .bind(id)
+            .execute(db_pool)
+            .await?;
+            if res.rows_affected().eq(&0) {
+                return Err(VaderError::UserNotFound("No User found"));
+            }
+            Ok(())
+        })
+    }
+    // moves the user to the trash , they keep their team/event until purged
     pub fn delete_user<'b>(id: &'b Uuid, db_pool: &'b SqlitePool) -> AsyncDbRes<'b, ()> {
         let id = id.to_string();
         Box::pin(async move {
-            let res = sqlx::query!("DELETE FROM users WHERE id = ? ", id)
-                .execute(db_pool)
-                .await?;
+            let res =
+                sqlx::query("UPDATE users SET deleted_at = ? WHERE id = ? AND deleted_at IS NULL")
+                    .bind(Utc::now())
+                    .bind(id)
+                    .execute(db_pool)
+                    .await?;
             if res.rows_affected().eq(&0) {
                 return Err(VaderError::UserNotFound("No User found"));
             }
@@ -480,43 +535,55 @@ impl<'a> User<'a> {
     }
     pub fn get_all_user(db_pool: &SqlitePool) -> AsyncDbRes<'_, Vec<Self>> {
         Box::pin(async move {
-            let users = sqlx::query_as::<_, User>("SELECT id,name,score,logo from users")
-                .fetch_all(db_pool)
-                .await?;
+            let users = sqlx::query_as::<_, User>(
+                "SELECT id,name,score,logo from users WHERE deleted_at IS NULL",
+            )
+            .fetch_all(db_pool)
+            .await?;
             Ok(users)
         })
     }
+    pub fn get_user_page(
+        page: &'a PageQuery,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, Page<Self>> {
+        Box::pin(fetch_page(
+            "u.id,u.name,u.score,u.logo",
+            "users u",
+            "u",
+            &[SortKey::Name, SortKey::Score, SortKey::CreatedAt],
+            page,
+            db_pool,
+        ))
+    }
     pub fn event_user_fts(
         event_id: &Uuid,
         param: &'a str,
+        offset: u32,
         count: u32,
         db_pool: &'a SqlitePool,
-    ) -> AsyncDbRes<'a, Vec<Self>> {
+    ) -> AsyncDbRes<'a, Vec<SearchHit<Self>>> {
+        let spec = SearchSpec::users(Some(event_id));
         let event_id = event_id.to_string();
         Box::pin(async move {
-            let users = if param.is_empty() {
+            let users = if param.trim().is_empty() {
                 sqlx::query_as::<_, User>(
                     "SELECT id,name,score,logo FROM users u 
                      JOIN event_users ut ON ut.user_id=u.id 
-                     WHERE ut.event_id = ? 
-                     LIMIT ?",
+                     WHERE ut.event_id = ? AND u.deleted_at IS NULL
+                     ORDER by u.created_at DESC 
+                     LIMIT ? OFFSET ?",
                 )
                 .bind(&event_id)
                 .bind(count)
+                .bind(offset)
                 .fetch_all(db_pool)
                 .await?
+                .into_iter()
+                .map(SearchHit::plain)
+                .collect()
             } else {
-                sqlx::query_as::<_, User>(
-                    "SELECT id,name,score,logo FROM users_fts u 
-                     JOIN event_users ut ON ut.user_id=u.id 
-                     WHERE ut.event_id = ? AND name MATCH  ? || '*'
-                     LIMIT ?",
-                )
-                .bind(&event_id)
-                .bind(param)
-                .bind(count)
-                .fetch_all(db_pool)
-                .await?
+                search(&spec, None, param, offset, count, db_pool).await?
             };
             Ok(users)
         })
@@ -538,6 +605,7 @@ pub trait VaderBoard<'a> {
         'b: 'a;
 }
 
+// with exclude_unchecked only the checked in make the board , late check-ins show up too : )
 impl<'a, S: VbStateMarker + EventState> VaderBoard<'a> for Event<'a, Team<'a>, S> {
     type VbRes = TeamInfo<'a>;
     fn get_vboard<'b>(
@@ -549,14 +617,17 @@ impl<'a, S: VbStateMarker + EventState> VaderBoard<'a> for Event<'a, Team<'a>, S
         'b: 'a,
     {
         let event_id = self.id.to_string();
+        let exclude_unchecked = self.exclude_unchecked;
         Box::pin(async move {
             let teams = sqlx::query_as::<_, TeamInfo>(
                 "SELECT id,name,score,logo FROM teams t  
                  JOIN event_teams et ON t.id=et.team_id 
-                 WHERE et.event_id=?
-                 ORDER BY score DESC LIMIT ?",
+                 WHERE et.event_id=?1 AND t.deleted_at IS NULL
+                 AND (NOT ?2 OR et.checked_in_at IS NOT NULL)
+                 ORDER BY score DESC LIMIT ?3",
             )
             .bind(&event_id)
+            .bind(exclude_unchecked)
             .bind(count)
             .fetch_all(db_pool)
             .await?;
@@ -576,14 +647,17 @@ impl<'a, S: VbStateMarker + EventState> VaderBoard<'a> for Event<'a, User<'a>, S
         'b: 'a,
     {
         let event_id = self.id.to_string();
+        let exclude_unchecked = self.exclude_unchecked;
         Box::pin(async move {
             let users = sqlx::query_as::<_, User>(
                 "SELECT id,name,score,logo FROM users u
                  JOIN event_users eu ON u.id=eu.user_id
-                 WHERE eu.event_id=?
-                 ORDER BY score DESC LIMIT ?",
+                 WHERE eu.event_id=?1 AND u.deleted_at IS NULL
+                 AND (NOT ?2 OR eu.checked_in_at IS NOT NULL)
+                 ORDER BY score DESC LIMIT ?3",
             )
             .bind(&event_id)
+            .bind(exclude_unchecked)
             .bind(count)
             .fetch_all(db_pool)
             .await?;
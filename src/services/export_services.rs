@@ -0,0 +1,342 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use sqlx::{Row, SqlitePool};
+use uuid::Uuid;
+
+use super::event_services::VaderBoard;
+use crate::models::error_models::VaderError;
+use crate::models::export_models::{EventExport, ExportEntry, ExportFormat, ExportMember};
+use crate::models::query_models::{EventInfo, EventType};
+use crate::models::v_models::{AsyncDbRes, EndEvent, Event, Team, User};
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Json => "application/json",
+            Self::Html => "text/html; charset=utf-8",
+        }
+    }
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+            Self::Html => "html",
+        }
+    }
+}
+
+// members of every team in the event , in a single query . Team::get_team only
+// carries member ids so reusing it would cost a team query plus a user query per
+// member , too many round trips for events with hundreds of teams : )
+async fn event_members(
+    event_id: &Uuid,
+    db_pool: &SqlitePool,
+) -> Result<HashMap<Uuid, Vec<ExportMember<'static>>>, VaderError<'static>> {
+    let rows = sqlx::query(
+        "SELECT tm.team_id AS team_id,u.id AS id,u.name AS name
+         FROM event_teams et JOIN team_members tm ON tm.team_id = et.team_id
+         JOIN users u ON u.id = tm.user_id AND u.deleted_at IS NULL
+         WHERE et.event_id = ? ORDER BY tm.rowid",
+    )
+    .bind(event_id.to_string())
+    .fetch_all(db_pool)
+    .await?;
+    let mut members: HashMap<Uuid, Vec<ExportMember>> = HashMap::new();
+    for row in rows {
+        let team_id = Uuid::parse_str(row.get("team_id"))
+            .map_err(|_e| VaderError::SqlxFieldError("Error decoding Team Id"))?;
+        let id = Uuid::parse_str(row.get("id"))
+            .map_err(|_e| VaderError::SqlxFieldError("Error decoding User Id"))?;
+        members.entry(team_id).or_default().push(ExportMember {
+            id,
+            name: Cow::Owned(row.get("name")),
+        });
+    }
+    Ok(members)
+}
+
+impl<'a> EventExport<'a> {
+    pub fn get_export(
+        event_id: &'a Uuid,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, EventExport<'static>> {
+        Box::pin(async move {
+            let event: EventInfo<'static> = match EventInfo::get_event_info(event_id, db_pool).await
+            {
+                Err(VaderError::SqlxError(sqlx::Error::RowNotFound)) => {
+                    return Err(VaderError::EventNotFound("No event found"))
+                }
+                event => event?,
+            };
+            // the results cover everyone linked to the event , the check-in filter
+            // is only for the live board
+            let mut ranking: Vec<ExportEntry> = match event.event_type {
+                EventType::TeamEvent { team_size: _ } => {
+                    let e = Event::<Team, EndEvent>::get_event(event_id, db_pool)
+                        .await?
+                        .with_exclude_unchecked(false);
+                    let teams = e.get_vboard(u32::MAX, db_pool).await?;
+                    let mut members = event_members(event_id, db_pool).await?;
+                    teams
+                        .into_iter()
+                        .map(|t| ExportEntry {
+                            rank: 0,
+                            id: t.id,
+                            members: members.remove(&t.id).unwrap_or_default(),
+                            name: Cow::Owned(t.name.into_owned()),
+                            score: t.score,
+                            logo: t.logo.map(|l| Cow::Owned(l.into_owned())),
+                        })
+                        .collect()
+                }
+                EventType::UserEvent => {
+                    let e = Event::<User, EndEvent>::get_event(event_id, db_pool)
+                        .await?
+                        .with_exclude_unchecked(false);
+                    e.get_vboard(u32::MAX, db_pool)
+                        .await?
+                        .into_iter()
+                        .map(|u| ExportEntry {
+                            rank: 0,
+                            id: u.id,
+                            name: Cow::Owned(u.name.into_owned()),
+                            score: u.score,
+                            logo: u.logo.map(|l| Cow::Owned(l.into_owned())),
+                            members: Vec::new(),
+                        })
+                        .collect()
+                }
+            };
+            // get_vboard orders by score , ties share a rank
+            let mut prev_score = None;
+            let mut rank = 0;
+            for (i, entry) in ranking.iter_mut().enumerate() {
+                if prev_score != Some(entry.score) {
+                    rank = i + 1;
+                    prev_score = Some(entry.score);
+                }
+                entry.rank = rank;
+            }
+            Ok(EventExport { event, ranking })
+        })
+    }
+
+    pub fn render(&self, format: ExportFormat) -> Result<String, VaderError<'static>> {
+        match format {
+            ExportFormat::Json => Ok(serde_json::to_string_pretty(self)?),
+            ExportFormat::Csv => self.to_csv(),
+            ExportFormat::Html => Ok(self.to_html()),
+        }
+    }
+
+    fn to_csv(&self) -> Result<String, VaderError<'static>> {
+        let csv_err = |e: csv::Error| VaderError::ExportError(e.to_string());
+        let mut wtr = csv::Writer::from_writer(Vec::new());
+        wtr.write_record(["rank", "id", "name", "score", "logo", "members"])
+            .map_err(csv_err)?;
+        for entry in &self.ranking {
+            let members = entry
+                .members
+                .iter()
+                .map(|m| csv_cell(&m.name))
+                .collect::<Vec<Cow<str>>>()
+                .join("; ");
+            wtr.write_record([
+                entry.rank.to_string().as_str(),
+                entry.id.to_string().as_str(),
+                &csv_cell(&entry.name),
+                entry.score.to_string().as_str(),
+                &csv_cell(entry.logo.as_deref().unwrap_or("")),
+                members.as_str(),
+            ])
+            .map_err(csv_err)?;
+        }
+        let data = wtr
+            .into_inner()
+            .map_err(|e| VaderError::ExportError(e.to_string()))?;
+        String::from_utf8(data).map_err(|e| VaderError::ExportError(e.to_string()))
+    }
+
+    // self contained page , no external css/js so it prints offline : )
+    fn to_html(&self) -> String {
+        let is_team = matches!(self.event.event_type, EventType::TeamEvent { .. });
+        let rows = self
+            .ranking
+            .iter()
+            .map(|entry| {
+                let members = if is_team {
+                    let names = entry
+                        .members
+                        .iter()
+                        .map(|m| escape_html(&m.name))
+                        .collect::<Vec<String>>()
+                        .join(", ");
+                    format!("<td>{}</td>", names)
+                } else {
+                    String::new()
+                };
+                format!(
+                    "<tr><td>{}</td><td>{}</td>{}<td>{}</td></tr>",
+                    entry.rank,
+                    escape_html(&entry.name),
+                    members,
+                    entry.score
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+        let (kind, members_head) = if is_team {
+            ("Team", "<th>Members</th>")
+        } else {
+            ("Participant", "")
+        };
+        let name = escape_html(&self.event.name);
+        format!(
+            r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{name} - Results</title>
+<style>
+body {{ font-family: sans-serif; margin: 2rem; color: #111; }}
+h1 {{ margin-bottom: 0.2rem; }}
+table {{ border-collapse: collapse; width: 100%; margin-top: 1rem; }}
+th, td {{ border: 1px solid #999; padding: 0.4rem 0.6rem; text-align: left; }}
+th {{ background: #eee; }}
+tbody tr:nth-child(-n+3) td {{ font-weight: bold; }}
+@media print {{ body {{ margin: 0; }} tr {{ page-break-inside: avoid; }} }}
+</style>
+</head>
+<body>
+<h1>{name}</h1>
+<p>Final results</p>
+<table>
+<thead><tr><th>Rank</th><th>{kind}</th>{members_head}<th>Score</th></tr></thead>
+<tbody>
+{rows}
+</tbody>
+</table>
+</body>
+</html>
+"#
+        )
+    }
+}
+
+// spreadsheets run cells starting with these as formulas , a leading ' keeps
+// them plain text
+fn csv_cell(s: &str) -> Cow<'_, str> {
+    match s.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        true => Cow::Owned(format!("'{}", s)),
+        false => Cow::Borrowed(s),
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::db_services::test_pool;
+
+    fn entry(name: &str, logo: Option<&str>, members: &[&str]) -> ExportEntry<'static> {
+        ExportEntry {
+            rank: 1,
+            id: Uuid::new_v4(),
+            name: Cow::Owned(name.to_string()),
+            score: 10,
+            logo: logo.map(|l| Cow::Owned(l.to_string())),
+            members: members
+                .iter()
+                .map(|m| ExportMember {
+                    id: Uuid::new_v4(),
+                    name: Cow::Owned(m.to_string()),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn csv_cells_are_not_formulas() {
+        let export = EventExport {
+            event: EventInfo {
+                id: Uuid::new_v4(),
+                name: Cow::Borrowed("event"),
+                logo: None,
+                event_type: EventType::TeamEvent { team_size: 2 },
+            },
+            ranking: vec![
+                entry("=HYPERLINK(\"http://x\")", Some("@SUM(A1)"), &["+1", "-1"]),
+                entry("\tteam", Some("\rlogo"), &["a=b"]),
+                entry("plain", None, &[]),
+            ],
+        };
+        let csv = export.render(ExportFormat::Csv).unwrap();
+        let mut rdr = csv::Reader::from_reader(csv.as_bytes());
+        let rows = rdr
+            .records()
+            .map(|r| r.unwrap())
+            .collect::<Vec<csv::StringRecord>>();
+        assert_eq!(&rows[0][2], "'=HYPERLINK(\"http://x\")");
+        assert_eq!(&rows[0][4], "'@SUM(A1)");
+        assert_eq!(&rows[0][5], "'+1; '-1");
+        assert_eq!(&rows[1][2], "'\tteam");
+        assert_eq!(&rows[1][4], "'\rlogo");
+        assert_eq!(&rows[1][5], "a=b");
+        assert_eq!(&rows[2][2], "plain");
+        assert_eq!(&rows[2][4], "");
+    }
+
+    #[actix_web::test]
+    async fn export_keeps_unchecked_participants() {
+        let db_pool = test_pool().await;
+        let event_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO events (id,name,event_type,exclude_unchecked,created_at)
+             VALUES (?,'event','user_event',1,'2024-01-01')",
+        )
+        .bind(event_id.to_string())
+        .execute(&db_pool)
+        .await
+        .unwrap();
+        for (name, score, checked_in) in [("checked", 5, Some("2024-01-01")), ("late", 9, None)] {
+            let id = Uuid::new_v4();
+            sqlx::query("INSERT INTO users (id,name,score,created_at) VALUES (?,?,?,'2024-01-01')")
+                .bind(id.to_string())
+                .bind(name)
+                .bind(score)
+                .execute(&db_pool)
+                .await
+                .unwrap();
+            sqlx::query("INSERT INTO event_users (event_id,user_id,checked_in_at) VALUES (?,?,?)")
+                .bind(event_id.to_string())
+                .bind(id.to_string())
+                .bind(checked_in)
+                .execute(&db_pool)
+                .await
+                .unwrap();
+        }
+        let export = EventExport::get_export(&event_id, &db_pool).await.unwrap();
+        let names = export
+            .ranking
+            .iter()
+            .map(|e| e.name.as_ref())
+            .collect::<Vec<&str>>();
+        assert_eq!(names, ["late", "checked"]);
+    }
+}
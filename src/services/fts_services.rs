@@ -0,0 +1,77 @@
+use sqlx::SqlitePool;
+
+use crate::models::error_models::VaderError;
+use crate::models::fts_models::{FtsOp, FtsReport};
+
+// (fts table , content table) , names are fixed so formatting them into sql is fine
+const FTS_TABLES: [(&str, &str); 6] = [
+    ("events_fts", "events"),
+    ("teams_fts", "teams"),
+    ("users_fts", "users"),
+    ("events_trgm", "events"),
+    ("teams_trgm", "teams"),
+    ("users_trgm", "users"),
+];
+
+pub async fn maintain_fts(
+    op: FtsOp,
+    db_pool: &SqlitePool,
+) -> Result<Vec<FtsReport>, VaderError<'static>> {
+    let command = match op {
+        FtsOp::Rebuild => Some("rebuild"),
+        FtsOp::Optimize => Some("optimize"),
+        FtsOp::Check => None,
+    };
+    let mut reports = Vec::with_capacity(FTS_TABLES.len());
+    for (fts, content) in FTS_TABLES {
+        if let Some(command) = command {
+            sqlx::query(&format!("INSERT INTO {0}({0}) VALUES(?)", fts))
+                .bind(command)
+                .execute(db_pool)
+                .await?;
+            log::info!("fts {} done on {}", command, fts);
+        }
+        reports.push(check_fts(fts, content, db_pool).await?);
+    }
+    Ok(reports)
+}
+
+// rank 1 also compares the index with the external content table : )
+async fn check_fts(
+    fts: &'static str,
+    content: &str,
+    db_pool: &SqlitePool,
+) -> Result<FtsReport, VaderError<'static>> {
+    let (rows,): (i64,) = sqlx::query_as(&format!("SELECT count(*) FROM {}", content))
+        .fetch_one(db_pool)
+        .await?;
+    let (indexed,): (i64,) = sqlx::query_as(&format!("SELECT count(*) FROM {}_docsize", fts))
+        .fetch_one(db_pool)
+        .await?;
+    let integrity = sqlx::query(&format!(
+        "INSERT INTO {0}({0},rank) VALUES('integrity-check',1)",
+        fts
+    ))
+    .execute(db_pool)
+    .await;
+    let details = match integrity {
+        Ok(_) if rows == indexed => None,
+        Ok(_) => Some(format!("{} rows but {} indexed", rows, indexed)),
+        Err(sqlx::Error::Database(e)) => Some(format!(
+            "index does not match {} ({})",
+            content,
+            e.message()
+        )),
+        Err(e) => return Err(e.into()),
+    };
+    if let Some(details) = &details {
+        log::warn!("fts table {} is out of sync : {}", fts, details);
+    }
+    Ok(FtsReport {
+        table: fts,
+        rows,
+        indexed,
+        ok: details.is_none(),
+        details,
+    })
+}
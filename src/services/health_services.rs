@@ -0,0 +1,97 @@
+use std::path::Path;
+
+use actix::dev::ToEnvelope;
+use actix::{Actor, Addr, Handler};
+use sqlx::SqlitePool;
+
+use crate::models::health_models::{
+    CheckStatus, HealthCheck, HealthPing, HealthReport, ACTOR_PING_TIMEOUT,
+};
+use crate::services::db_services::MIGRATOR;
+
+impl HealthCheck {
+    pub fn ok(name: &'static str, details: String) -> Self {
+        HealthCheck {
+            name,
+            status: CheckStatus::Ok,
+            details: Some(details),
+        }
+    }
+    pub fn fail(name: &'static str, details: String) -> Self {
+        HealthCheck {
+            name,
+            status: CheckStatus::Fail,
+            details: Some(details),
+        }
+    }
+}
+
+impl HealthReport {
+    pub fn new(checks: Vec<HealthCheck>) -> Self {
+        let status = if checks.iter().all(|c| c.status == CheckStatus::Ok) {
+            CheckStatus::Ok
+        } else {
+            CheckStatus::Fail
+        };
+        HealthReport { status, checks }
+    }
+    pub fn is_ok(&self) -> bool {
+        self.status == CheckStatus::Ok
+    }
+}
+
+pub async fn check_db(db_pool: &SqlitePool) -> HealthCheck {
+    match sqlx::query("SELECT 1").execute(db_pool).await {
+        Ok(_) => HealthCheck::ok(
+            "database",
+            format!(
+                "{} of {} connections open",
+                db_pool.size(),
+                db_pool.options().get_max_connections()
+            ),
+        ),
+        Err(e) => HealthCheck::fail("database", e.to_string()),
+    }
+}
+
+// the newest applied migration should be the newest one embedded in the binary
+pub async fn check_migrations(db_pool: &SqlitePool) -> HealthCheck {
+    let expected = MIGRATOR.iter().map(|m| m.version).max().unwrap_or(0);
+    let applied: Result<Option<i64>, sqlx::Error> =
+        sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success = 1")
+            .fetch_one(db_pool)
+            .await;
+    match applied {
+        Ok(Some(version)) if version == expected => {
+            HealthCheck::ok("migrations", format!("version {}", version))
+        }
+        Ok(version) => HealthCheck::fail(
+            "migrations",
+            format!("version {} , expected {}", version.unwrap_or(0), expected),
+        ),
+        Err(e) => HealthCheck::fail("migrations", e.to_string()),
+    }
+}
+
+pub async fn check_actor<A>(name: &'static str, addr: &Addr<A>) -> HealthCheck
+where
+    A: Actor + Handler<HealthPing>,
+    A::Context: ToEnvelope<A, HealthPing>,
+{
+    match tokio::time::timeout(ACTOR_PING_TIMEOUT, addr.send(HealthPing)).await {
+        Ok(Ok(())) => HealthCheck::ok(name, "responding".to_string()),
+        Ok(Err(e)) => HealthCheck::fail(name, e.to_string()),
+        Err(_) => HealthCheck::fail(
+            name,
+            format!("no reply in {}s", ACTOR_PING_TIMEOUT.as_secs()),
+        ),
+    }
+}
+
+pub fn check_dist(dist_dir: &Path) -> HealthCheck {
+    if dist_dir.is_dir() {
+        HealthCheck::ok("dist", dist_dir.display().to_string())
+    } else {
+        HealthCheck::fail("dist", format!("{} is not a directory", dist_dir.display()))
+    }
+}
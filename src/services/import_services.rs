@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use sqlx::{Connection, SqliteConnection, SqlitePool};
+use uuid::Uuid;
+
+use crate::models::error_models::VaderError;
+use crate::models::import_models::{
+    ImportEntry, ImportFormat, ImportReport, ImportRow, ImportRowError,
+};
+use crate::models::v_models::{AsyncDbRes, Event, NewEvent, Team, User};
+use crate::services::validation_services::validation_message;
+
+impl ImportFormat {
+    // rows failing to parse/validate go to report.errors , rest are returned : )
+    pub fn parse_rows(
+        &self,
+        data: &[u8],
+        report: &mut ImportReport,
+    ) -> Result<Vec<(usize, ImportRow)>, VaderError<'static>> {
+        let rows: Vec<(usize, Result<ImportRow, String>)> = match self {
+            Self::Csv => csv::ReaderBuilder::new()
+                .trim(csv::Trim::All)
+                .from_reader(data)
+                .deserialize::<ImportRow>()
+                .enumerate()
+                .map(|(i, r)| (i + 1, r.map_err(|e| e.to_string())))
+                .collect(),
+            Self::Json => serde_json::from_slice::<Vec<serde_json::Value>>(data)
+                .map_err(|e| {
+                    VaderError::ImportError(format!("Expected a json array of rows : {}", e))
+                })?
+                .into_iter()
+                .enumerate()
+                .map(|(i, r)| (i + 1, serde_json::from_value(r).map_err(|e| e.to_string())))
+                .collect(),
+        };
+        if rows.is_empty() {
+            return Err(VaderError::ImportError("No rows to import".to_string()));
+        }
+        let mut valid = Vec::with_capacity(rows.len());
+        for (row, res) in rows {
+            match res.and_then(ImportRow::validate) {
+                Ok(r) => valid.push((row, r)),
+                Err(error) => report.errors.push(ImportRowError { row, error }),
+            }
+        }
+        Ok(valid)
+    }
+}
+
+impl ImportRow {
+    fn validate(self) -> Result<Self, String> {
+        let non_empty =
+            |s: Option<String>| s.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+        let row = Self {
+            team: non_empty(self.team),
+            team_logo: non_empty(self.team_logo),
+            user: non_empty(self.user),
+            user_logo: non_empty(self.user_logo),
+        };
+        validation_message(&row)?;
+        match (&row.team, &row.user) {
+            (None, None) => Err("Row has neither a team nor a user".to_string()),
+            (None, Some(_)) if row.team_logo.is_some() => {
+                Err("team_logo given without a team".to_string())
+            }
+            (Some(_), None) if row.user_logo.is_some() => {
+                Err("user_logo given without a user".to_string())
+            }
+            _ => Ok(row),
+        }
+    }
+}
+
+// insert helpers run inside the import transaction , logo stored as "" like add_player : )
+async fn insert_team(
+    name: &str,
+    logo: &Option<String>,
+    event_id: &str,
+    conn: &mut SqliteConnection,
+) -> Result<Uuid, sqlx::Error> {
+    let id = Uuid::new_v4();
+    sqlx::query("INSERT INTO teams (id,name,score,logo,created_at) VALUES (?,?,0,?,?)")
+        .bind(id.to_string())
+        .bind(name)
+        .bind(logo.clone().unwrap_or_default())
+        .bind(Utc::now())
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query("INSERT INTO event_teams (event_id,team_id) VALUES (?,?)")
+        .bind(event_id)
+        .bind(id.to_string())
+        .execute(&mut *conn)
+        .await?;
+    Ok(id)
+}
+
+async fn insert_user(
+    name: &str,
+    logo: &Option<String>,
+    conn: &mut SqliteConnection,
+) -> Result<Uuid, sqlx::Error> {
+    let id = Uuid::new_v4();
+    sqlx::query("INSERT INTO users (id,name,score,logo,created_at) VALUES (?,?,0,?,?)")
+        .bind(id.to_string())
+        .bind(name)
+        .bind(logo.clone().unwrap_or_default())
+        .bind(Utc::now())
+        .execute(&mut *conn)
+        .await?;
+    Ok(id)
+}
+
+async fn finish_import(
+    transaction: sqlx::Transaction<'_, sqlx::Sqlite>,
+    mut report: ImportReport,
+) -> Result<ImportReport, sqlx::Error> {
+    report.errors.sort_by_key(|e| e.row);
+    if report.errors.is_empty() && !report.dry_run {
+        transaction.commit().await?;
+        report.committed = true;
+    } else {
+        transaction.rollback().await?;
+    }
+    Ok(report)
+}
+
+impl<'a> Event<'a, Team<'a>, NewEvent> {
+    // single transaction , each row in a savepoint to collect per row errors : )
+    pub fn import_rows(
+        &'a self,
+        rows: Vec<(usize, ImportRow)>,
+        mut report: ImportReport,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, ImportReport> {
+        let event_id = self.id.to_string();
+        Box::pin(async move {
+            let mut transaction = db_pool.begin().await?;
+            let mut teams: HashMap<String, Uuid> = HashMap::new();
+            for (row, r) in rows {
+                let mut savepoint = transaction.begin().await?;
+                let res: Result<(Option<Uuid>, Option<Uuid>), sqlx::Error> = async {
+                    let new_team = match &r.team {
+                        Some(name) if !teams.contains_key(name) => {
+                            Some(insert_team(name, &r.team_logo, &event_id, &mut savepoint).await?)
+                        }
+                        _ => None,
+                    };
+                    let team_id = new_team.or_else(|| r.team.as_ref().map(|t| teams[t]));
+                    let user_id = match &r.user {
+                        Some(name) => {
+                            let user_id = insert_user(name, &r.user_logo, &mut savepoint).await?;
+                            if let Some(team_id) = team_id {
+                                // team_members_check trigger guards the team size
+                                sqlx::query(
+                                    "INSERT INTO team_members (team_id,user_id) VALUES (?,?)",
+                                )
+                                .bind(team_id.to_string())
+                                .bind(user_id.to_string())
+                                .execute(&mut *savepoint)
+                                .await?;
+                            }
+                            Some(user_id)
+                        }
+                        None => None,
+                    };
+                    Ok((new_team, user_id))
+                }
+                .await;
+                match res {
+                    Ok((new_team, user_id)) => {
+                        savepoint.commit().await?;
+                        if let (Some(id), Some(name)) = (new_team, r.team.clone()) {
+                            teams.insert(name.clone(), id);
+                            report.teams.push(ImportEntry { row, id, name });
+                        }
+                        if let (Some(id), Some(name)) = (user_id, r.user) {
+                            report.users.push(ImportEntry { row, id, name });
+                            if r.team.is_some() {
+                                report.members += 1;
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        savepoint.rollback().await?;
+                        report.errors.push(ImportRowError {
+                            row,
+                            error: VaderError::SqlxError(e).to_string(),
+                        });
+                    }
+                }
+            }
+            Ok(finish_import(transaction, report).await?)
+        })
+    }
+}
+
+impl<'a> Event<'a, User<'a>, NewEvent> {
+    pub fn import_rows(
+        &'a self,
+        rows: Vec<(usize, ImportRow)>,
+        mut report: ImportReport,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, ImportReport> {
+        let event_id = self.id.to_string();
+        Box::pin(async move {
+            let mut transaction = db_pool.begin().await?;
+            for (row, r) in rows {
+                let name = match (r.team, r.user) {
+                    (None, Some(name)) => name,
+                    _ => {
+                        report.errors.push(ImportRowError {
+                            row,
+                            error: VaderError::EventTypeMismatch("Cannot add team in user event")
+                                .to_string(),
+                        });
+                        continue;
+                    }
+                };
+                let mut savepoint = transaction.begin().await?;
+                let res: Result<Uuid, sqlx::Error> = async {
+                    let user_id = insert_user(&name, &r.user_logo, &mut savepoint).await?;
+                    sqlx::query("INSERT INTO event_users (event_id,user_id) VALUES (?,?)")
+                        .bind(&event_id)
+                        .bind(user_id.to_string())
+                        .execute(&mut *savepoint)
+                        .await?;
+                    Ok(user_id)
+                }
+                .await;
+                match res {
+                    Ok(id) => {
+                        savepoint.commit().await?;
+                        report.users.push(ImportEntry { row, id, name });
+                    }
+                    Err(e) => {
+                        savepoint.rollback().await?;
+                        report.errors.push(ImportRowError {
+                            row,
+                            error: VaderError::SqlxError(e).to_string(),
+                        });
+                    }
+                }
+            }
+            Ok(finish_import(transaction, report).await?)
+        })
+    }
+}
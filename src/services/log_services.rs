@@ -0,0 +1,144 @@
+use std::cell::RefCell;
+use std::future::Future;
+use std::io::Write;
+use std::rc::Rc;
+
+use chrono::{SecondsFormat, Utc};
+use env_logger::fmt::Formatter;
+use log::kv::{self, Key, Value, VisitSource};
+use log::Record;
+use serde_json::Value as JsonValue;
+use uuid::Uuid;
+
+use crate::models::config_models::{LogFormat, VaderConfig};
+use crate::models::log_models::{LogContext, REQUEST_ID_MAX_LEN};
+
+tokio::task_local! {
+    static LOG_CONTEXT: Rc<RefCell<LogContext>>;
+}
+
+// log.level is the default , RUST_LOG directives (ie: sqlx=warn) go on top
+pub fn init_logger(config: &VaderConfig) {
+    let format = config.log.format;
+    env_logger::builder()
+        .filter_level(config.log_level())
+        .parse_default_env()
+        .format(move |buf, record| match format {
+            LogFormat::Text => write_text(buf, record),
+            LogFormat::Json => write_json(buf, record),
+        })
+        .init();
+}
+
+pub fn new_request_id() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+// ids from upstream proxies are only kept if they are short and plain
+pub fn valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= REQUEST_ID_MAX_LEN
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+pub fn request_id() -> Option<String> {
+    LOG_CONTEXT
+        .try_with(|ctx| ctx.borrow().request_id.clone())
+        .ok()
+}
+
+pub fn set_admin(username: String) {
+    let _ = LOG_CONTEXT.try_with(|ctx| ctx.borrow_mut().admin = Some(username));
+}
+
+// middlewares build the inner future in `call` , before it's ever polled , so
+// the context is set for both
+pub fn with_context_sync<R>(ctx: Rc<RefCell<LogContext>>, f: impl FnOnce() -> R) -> R {
+    LOG_CONTEXT.sync_scope(ctx, f)
+}
+
+pub fn with_context<F: Future>(
+    ctx: Rc<RefCell<LogContext>>,
+    fut: F,
+) -> impl Future<Output = F::Output> {
+    LOG_CONTEXT.scope(ctx, fut)
+}
+
+struct Fields(Vec<(String, JsonValue)>);
+
+impl<'kvs> VisitSource<'kvs> for Fields {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+        self.0.push((key.as_str().to_string(), json_value(&value)));
+        Ok(())
+    }
+}
+
+fn json_value(value: &Value) -> JsonValue {
+    if let Some(b) = value.to_bool() {
+        b.into()
+    } else if let Some(n) = value.to_i64() {
+        n.into()
+    } else if let Some(n) = value.to_u64() {
+        n.into()
+    } else if let Some(n) = value.to_f64() {
+        n.into()
+    } else {
+        value.to_string().into()
+    }
+}
+
+// the request context first , fields passed to the log macro win
+fn fields(record: &Record) -> Vec<(String, JsonValue)> {
+    let mut fields = Fields(Vec::new());
+    let _ = record.key_values().visit(&mut fields);
+    let mut ctx_fields = Vec::new();
+    let _ = LOG_CONTEXT.try_with(|ctx| {
+        let ctx = ctx.borrow();
+        ctx_fields.push(("request_id", JsonValue::from(ctx.request_id.as_str())));
+        if let Some(admin) = &ctx.admin {
+            ctx_fields.push(("admin", JsonValue::from(admin.as_str())));
+        }
+    });
+    let mut all: Vec<(String, JsonValue)> = ctx_fields
+        .into_iter()
+        .filter(|(k, _)| !fields.0.iter().any(|(f, _)| f == k))
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+    all.append(&mut fields.0);
+    all
+}
+
+fn write_text(buf: &mut Formatter, record: &Record) -> std::io::Result<()> {
+    let mut line = format!("{}", record.args());
+    for (key, value) in fields(record) {
+        match value {
+            JsonValue::String(s) => line.push_str(&format!(" {}={}", key, s)),
+            v => line.push_str(&format!(" {}={}", key, v)),
+        }
+    }
+    writeln!(
+        buf,
+        "[{} {:<5} {}] {}",
+        buf.timestamp(),
+        buf.default_styled_level(record.level()),
+        record.target(),
+        line
+    )
+}
+
+fn write_json(buf: &mut Formatter, record: &Record) -> std::io::Result<()> {
+    let mut line = format!(
+        "{{\"ts\":{},\"level\":{},\"target\":{},\"msg\":{}",
+        JsonValue::from(Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)),
+        JsonValue::from(record.level().as_str()),
+        JsonValue::from(record.target()),
+        JsonValue::from(record.args().to_string()),
+    );
+    for (key, value) in fields(record) {
+        line.push_str(&format!(",{}:{}", JsonValue::from(key), value));
+    }
+    line.push('}');
+    writeln!(buf, "{}", line)
+}
@@ -0,0 +1,567 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Row, SqliteConnection, SqlitePool};
+use uuid::Uuid;
+
+use crate::models::error_models::VaderError;
+use crate::models::membership_models::{JoinRes, TeamCapacity, WaitlistEntry};
+use crate::models::v_models::{AsyncDbRes, Team, User};
+
+impl TeamCapacity {
+    pub fn fits(&self) -> bool {
+        self.current + self.adding <= self.max
+    }
+}
+
+// members are checked against the team_size here so callers get the counts ,
+// the team_members_check trigger stays as the last guard : )
+impl<'a> Team<'a> {
+    // None for a team not yet linked to a team event or registration , nothing to
+    // check against
+    pub fn capacity<'c>(
+        team_id: &'c str,
+        adding: u32,
+        conn: &'c mut SqliteConnection,
+    ) -> AsyncDbRes<'c, Option<TeamCapacity>> {
+        Box::pin(async move {
+            // a registered team is held to its event before it's approved
+            let row = sqlx::query(
+                "SELECT (SELECT COUNT(*) FROM team_members WHERE team_id = ?1) AS current,
+                 e.team_size AS max FROM events e WHERE e.team_size IS NOT NULL AND e.id IN (
+                 SELECT event_id FROM event_teams WHERE team_id = ?1
+                 UNION SELECT event_id FROM registrations WHERE team_id = ?1) LIMIT 1",
+            )
+            .bind(team_id)
+            .fetch_optional(conn)
+            .await?;
+            Ok(row.map(|r| TeamCapacity {
+                current: r.get::<u32, _>("current"),
+                max: r.get::<u32, _>("max"),
+                adding,
+            }))
+        })
+    }
+
+    pub fn add_members_from_id<'c>(
+        team_id: &'c Uuid,
+        members: &'c [Uuid],
+        conn: &'c mut SqliteConnection,
+    ) -> AsyncDbRes<'c, ()> {
+        Box::pin(async move {
+            let team_id = team_id.to_string();
+            if let Some(capacity) =
+                Self::capacity(&team_id, members.len() as u32, &mut *conn).await?
+            {
+                if !capacity.fits() {
+                    return Err(VaderError::TeamSizeMismatch(capacity));
+                }
+            }
+            for mem_id in members {
+                let user_id = mem_id.to_string();
+                // members of a trashed team are free to join another one ,
+                // restoring that team brings it back without them : )
+                sqlx::query(
+                    "DELETE FROM team_members WHERE user_id = ?
+                     AND team_id IN (SELECT id FROM teams WHERE deleted_at IS NOT NULL)",
+                )
+                .bind(&user_id)
+                .execute(&mut *conn)
+                .await?;
+                let res = sqlx::query(
+                    "INSERT INTO team_members (team_id,user_id)
+                     SELECT t.id,u.id FROM teams t,users u WHERE t.id = ? AND u.id = ?
+                     AND t.deleted_at IS NULL AND u.deleted_at IS NULL",
+                )
+                .bind(&team_id)
+                .bind(&user_id)
+                .execute(&mut *conn)
+                .await
+                .inspect_err(|_| log::error!("Unable to add member :  {}", mem_id))?;
+                if res.rows_affected().eq(&0) {
+                    return Err(VaderError::TeamNotFound(
+                        "No Team/User found to Add Team Members",
+                    ));
+                }
+                // got a team , stop waiting for one
+                sqlx::query("DELETE FROM team_waitlist WHERE user_id = ?")
+                    .bind(&user_id)
+                    .execute(&mut *conn)
+                    .await?;
+            }
+            Ok(())
+        })
+    }
+
+    // joins the team if there is place , else waits in line when `waitlist` is set
+    pub fn join<'b>(
+        team_id: &'b Uuid,
+        user_id: &'b Uuid,
+        waitlist: bool,
+        db_pool: &'b SqlitePool,
+    ) -> AsyncDbRes<'b, JoinRes> {
+        Box::pin(async move {
+            let mut transaction = db_pool.begin().await?;
+            let capacity = Self::capacity(&team_id.to_string(), 1, &mut transaction).await?;
+            let res = match capacity {
+                Some(capacity) if !capacity.fits() => {
+                    if !waitlist {
+                        return Err(VaderError::TeamSizeMismatch(capacity));
+                    }
+                    let position = Self::wait_for(team_id, user_id, &mut transaction).await?;
+                    JoinRes::Waitlisted { position }
+                }
+                _ => {
+                    Self::add_members_from_id(
+                        team_id,
+                        std::slice::from_ref(user_id),
+                        &mut transaction,
+                    )
+                    .await?;
+                    JoinRes::Member
+                }
+            };
+            transaction.commit().await?;
+            Ok(res)
+        })
+    }
+
+    // a user waits for a single team and only while they have none
+    fn wait_for<'c>(
+        team_id: &'c Uuid,
+        user_id: &'c Uuid,
+        conn: &'c mut SqliteConnection,
+    ) -> AsyncDbRes<'c, u32> {
+        let team_id = team_id.to_string();
+        let user_id = user_id.to_string();
+        Box::pin(async move {
+            let team = sqlx::query("SELECT 1 FROM teams WHERE id = ? AND deleted_at IS NULL")
+                .bind(&team_id)
+                .fetch_optional(&mut *conn)
+                .await?;
+            if team.is_none() {
+                return Err(VaderError::TeamNotFound("No team found"));
+            }
+            let user = sqlx::query("SELECT 1 FROM users WHERE id = ? AND deleted_at IS NULL")
+                .bind(&user_id)
+                .fetch_optional(&mut *conn)
+                .await?;
+            if user.is_none() {
+                return Err(VaderError::UserNotFound("No User found"));
+            }
+            let in_team = sqlx::query(
+                "SELECT 1 FROM team_members tm JOIN teams t ON t.id = tm.team_id
+                 WHERE tm.user_id = ? AND t.deleted_at IS NULL",
+            )
+            .bind(&user_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+            if in_team.is_some() {
+                return Err(VaderError::AlreadyInTeam(
+                    "A member cannot wait for a place in a team",
+                ));
+            }
+            sqlx::query("INSERT INTO team_waitlist (team_id,user_id,created_at) VALUES (?,?,?)")
+                .bind(&team_id)
+                .bind(&user_id)
+                .bind(Utc::now())
+                .execute(&mut *conn)
+                .await?;
+            let position: u32 = sqlx::query_scalar(
+                "SELECT COUNT(*) FROM team_waitlist w JOIN users u ON u.id = w.user_id
+                 WHERE w.team_id = ? AND u.deleted_at IS NULL
+                 AND w.id <= (SELECT id FROM team_waitlist WHERE user_id = ?)",
+            )
+            .bind(&team_id)
+            .bind(&user_id)
+            .fetch_one(&mut *conn)
+            .await?;
+            Ok(position)
+        })
+    }
+
+    // fills free places from the waitlist , first in first out , returns who got in
+    pub fn promote_waitlist<'c>(
+        team_id: &'c Uuid,
+        conn: &'c mut SqliteConnection,
+    ) -> AsyncDbRes<'c, Vec<Uuid>> {
+        Box::pin(async move {
+            let id = team_id.to_string();
+            let mut promoted = Vec::new();
+            loop {
+                match Self::capacity(&id, 1, &mut *conn).await? {
+                    Some(capacity) if capacity.fits() => {}
+                    _ => break,
+                }
+                // trashed users keep their place until restored or purged
+                let next: Option<String> = sqlx::query_scalar(
+                    "SELECT w.user_id FROM team_waitlist w
+                     JOIN users u ON u.id = w.user_id AND u.deleted_at IS NULL
+                     WHERE w.team_id = ? ORDER BY w.id LIMIT 1",
+                )
+                .bind(&id)
+                .fetch_optional(&mut *conn)
+                .await?;
+                let Some(next) = next else { break };
+                let user_id = Uuid::parse_str(&next)
+                    .map_err(|_e| VaderError::SqlxFieldError("Error decoding User Id"))?;
+                Self::add_members_from_id(team_id, std::slice::from_ref(&user_id), &mut *conn)
+                    .await?;
+                promoted.push(user_id);
+            }
+            Ok(promoted)
+        })
+    }
+
+    pub fn get_waitlist<'b>(
+        team_id: &Uuid,
+        db_pool: &'b SqlitePool,
+    ) -> AsyncDbRes<'b, Vec<WaitlistEntry>> {
+        let team_id = team_id.to_string();
+        Box::pin(async move {
+            let team = sqlx::query("SELECT 1 FROM teams WHERE id = ? AND deleted_at IS NULL")
+                .bind(&team_id)
+                .fetch_optional(db_pool)
+                .await?;
+            if team.is_none() {
+                return Err(VaderError::TeamNotFound("No team found"));
+            }
+            let rows = sqlx::query(
+                "SELECT w.user_id AS user_id,u.name AS name,w.created_at AS created_at
+                 FROM team_waitlist w JOIN users u ON u.id = w.user_id
+                 WHERE w.team_id = ? AND u.deleted_at IS NULL ORDER BY w.id",
+            )
+            .bind(&team_id)
+            .fetch_all(db_pool)
+            .await?;
+            rows.iter()
+                .enumerate()
+                .map(|(i, row)| {
+                    let user_id = Uuid::parse_str(row.get("user_id"))
+                        .map_err(|_e| VaderError::SqlxFieldError("Error decoding User Id"))?;
+                    Ok(WaitlistEntry {
+                        position: i as u32 + 1,
+                        user_id,
+                        name: row.get("name"),
+                        created_at: row.get::<DateTime<Utc>, _>("created_at"),
+                    })
+                })
+                .collect()
+        })
+    }
+
+    pub fn leave_waitlist<'b>(
+        team_id: &Uuid,
+        user_id: &Uuid,
+        db_pool: &'b SqlitePool,
+    ) -> AsyncDbRes<'b, ()> {
+        let team_id = team_id.to_string();
+        let user_id = user_id.to_string();
+        Box::pin(async move {
+            let res = sqlx::query("DELETE FROM team_waitlist WHERE team_id = ? AND user_id = ?")
+                .bind(team_id)
+                .bind(user_id)
+                .execute(db_pool)
+                .await?;
+            if res.rows_affected().eq(&0) {
+                return Err(VaderError::UserNotFound(
+                    "User is not on the waitlist of the team",
+                ));
+            }
+            Ok(())
+        })
+    }
+
+    // the freed place goes to the waitlist , returns who got in
+    pub fn remove_member<'b>(
+        team_id: &'b Uuid,
+        user_id: &Uuid,
+        db_pool: &'b SqlitePool,
+    ) -> AsyncDbRes<'b, Vec<Uuid>> {
+        let user_id = user_id.to_string();
+        Box::pin(async move {
+            let mut transaction = db_pool.begin().await?;
+            let res = sqlx::query("DELETE FROM team_members WHERE team_id = ? AND user_id = ?")
+                .bind(team_id.to_string())
+                .bind(user_id)
+                .execute(&mut *transaction)
+                .await?;
+            if res.rows_affected().eq(&0) {
+                return Err(VaderError::UserNotFound("User is not a member of the team"));
+            }
+            let promoted = Self::promote_waitlist(team_id, &mut transaction).await?;
+            transaction.commit().await?;
+            Ok(promoted)
+        })
+    }
+
+    // atmost one captain , the old one goes back to being a member
+    pub fn set_captain<'b>(
+        team_id: &Uuid,
+        user_id: &Uuid,
+        db_pool: &'b SqlitePool,
+    ) -> AsyncDbRes<'b, ()> {
+        let team_id = team_id.to_string();
+        let user_id = user_id.to_string();
+        Box::pin(async move {
+            let mut transaction = db_pool.begin().await?;
+            let team = sqlx::query("SELECT 1 FROM teams WHERE id = ? AND deleted_at IS NULL")
+                .bind(&team_id)
+                .fetch_optional(&mut *transaction)
+                .await?;
+            if team.is_none() {
+                return Err(VaderError::TeamNotFound("No team found"));
+            }
+            sqlx::query(
+                "UPDATE team_members SET role = 'member' WHERE team_id = ? AND role = 'captain'",
+            )
+            .bind(&team_id)
+            .execute(&mut *transaction)
+            .await?;
+            let res = sqlx::query(
+                "UPDATE team_members SET role = 'captain' WHERE team_id = ? AND user_id = ?
+                 AND user_id IN (SELECT id FROM users WHERE deleted_at IS NULL)",
+            )
+            .bind(&team_id)
+            .bind(&user_id)
+            .execute(&mut *transaction)
+            .await?;
+            if res.rows_affected().eq(&0) {
+                return Err(VaderError::UserNotFound("User is not a member of the team"));
+            }
+            transaction.commit().await?;
+            Ok(())
+        })
+    }
+
+    pub fn clear_captain<'b>(team_id: &Uuid, db_pool: &'b SqlitePool) -> AsyncDbRes<'b, ()> {
+        let team_id = team_id.to_string();
+        Box::pin(async move {
+            let team = sqlx::query("SELECT 1 FROM teams WHERE id = ? AND deleted_at IS NULL")
+                .bind(&team_id)
+                .fetch_optional(db_pool)
+                .await?;
+            if team.is_none() {
+                return Err(VaderError::TeamNotFound("No team found"));
+            }
+            sqlx::query(
+                "UPDATE team_members SET role = 'member' WHERE team_id = ? AND role = 'captain'",
+            )
+            .bind(&team_id)
+            .execute(db_pool)
+            .await?;
+            Ok(())
+        })
+    }
+}
+
+impl<'a> User<'a> {
+    // delete + insert , not an update , so the new team's size is checked and the
+    // user joins as a member , the old team takes the next one from its waitlist
+    pub fn move_to_team<'b>(
+        user_id: &'b Uuid,
+        team_id: &'b Uuid,
+        db_pool: &'b SqlitePool,
+    ) -> AsyncDbRes<'b, Vec<Uuid>> {
+        Box::pin(async move {
+            let user = user_id.to_string();
+            let mut transaction = db_pool.begin().await?;
+            let team = sqlx::query("SELECT 1 FROM teams WHERE id = ? AND deleted_at IS NULL")
+                .bind(team_id.to_string())
+                .fetch_optional(&mut *transaction)
+                .await?;
+            if team.is_none() {
+                return Err(VaderError::TeamNotFound(
+                    "No team found to move the user to",
+                ));
+            }
+            let found = sqlx::query("SELECT 1 FROM users WHERE id = ? AND deleted_at IS NULL")
+                .bind(&user)
+                .fetch_optional(&mut *transaction)
+                .await?;
+            if found.is_none() {
+                return Err(VaderError::UserNotFound("No User found"));
+            }
+            let old_team: Option<String> =
+                sqlx::query_scalar("SELECT team_id FROM team_members WHERE user_id = ?")
+                    .bind(&user)
+                    .fetch_optional(&mut *transaction)
+                    .await?;
+            sqlx::query("DELETE FROM team_members WHERE user_id = ?")
+                .bind(&user)
+                .execute(&mut *transaction)
+                .await?;
+            Team::add_members_from_id(team_id, std::slice::from_ref(user_id), &mut transaction)
+                .await?;
+            let mut promoted = Vec::new();
+            if let Some(old_team) = old_team.and_then(|t| Uuid::parse_str(&t).ok()) {
+                if &old_team != team_id {
+                    promoted = Team::promote_waitlist(&old_team, &mut transaction).await?;
+                }
+            }
+            transaction.commit().await?;
+            Ok(promoted)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::db_services::test_pool;
+
+    // a team event of `team_size` with one linked team , returns the team id
+    async fn team_event(team_size: u32, db_pool: &SqlitePool) -> Uuid {
+        let (event_id, team_id) = (Uuid::new_v4(), Uuid::new_v4());
+        sqlx::query(
+            "INSERT INTO events (id,name,event_type,team_size,created_at)
+             VALUES (?,'event','team_event',?,'2024-01-01')",
+        )
+        .bind(event_id.to_string())
+        .bind(team_size)
+        .execute(db_pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO teams (id,name,score,created_at) VALUES (?,'team',0,'2024-01-01')",
+        )
+        .bind(team_id.to_string())
+        .execute(db_pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO event_teams (event_id,team_id) VALUES (?,?)")
+            .bind(event_id.to_string())
+            .bind(team_id.to_string())
+            .execute(db_pool)
+            .await
+            .unwrap();
+        team_id
+    }
+
+    async fn users(n: usize, db_pool: &SqlitePool) -> Vec<Uuid> {
+        let mut ids = Vec::new();
+        for i in 0..n {
+            let id = Uuid::new_v4();
+            sqlx::query("INSERT INTO users (id,name,score,created_at) VALUES (?,?,0,'2024-01-01')")
+                .bind(id.to_string())
+                .bind(format!("user {}", i))
+                .execute(db_pool)
+                .await
+                .unwrap();
+            ids.push(id);
+        }
+        ids
+    }
+
+    async fn waitlist(team_id: &Uuid, db_pool: &SqlitePool) -> Vec<Uuid> {
+        Team::get_waitlist(team_id, db_pool)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|w| w.user_id)
+            .collect()
+    }
+
+    #[test]
+    fn fits_up_to_team_size() {
+        let capacity = |current, adding| TeamCapacity {
+            current,
+            max: 3,
+            adding,
+        };
+        assert!(capacity(0, 3).fits());
+        assert!(capacity(2, 1).fits());
+        assert!(!capacity(3, 1).fits());
+        assert!(!capacity(1, 3).fits());
+    }
+
+    #[actix_web::test]
+    async fn capacity_of_linked_teams_only() {
+        let db_pool = test_pool().await;
+        let team_id = team_event(2, &db_pool).await;
+        let mut conn = db_pool.acquire().await.unwrap();
+        let capacity = Team::capacity(&team_id.to_string(), 1, &mut conn)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!((capacity.current, capacity.max, capacity.adding), (0, 2, 1));
+        let loose = Team::capacity(&Uuid::new_v4().to_string(), 1, &mut conn)
+            .await
+            .unwrap();
+        assert!(loose.is_none());
+    }
+
+    #[actix_web::test]
+    async fn full_team_fails_or_waitlists() {
+        let db_pool = test_pool().await;
+        let team_id = team_event(2, &db_pool).await;
+        let u = users(4, &db_pool).await;
+        for user_id in &u[..2] {
+            let res = Team::join(&team_id, user_id, false, &db_pool).await;
+            assert!(matches!(res, Ok(JoinRes::Member)));
+        }
+        let res = Team::join(&team_id, &u[2], false, &db_pool).await;
+        assert!(matches!(
+            res,
+            Err(VaderError::TeamSizeMismatch(TeamCapacity {
+                current: 2,
+                max: 2,
+                adding: 1
+            }))
+        ));
+        let res = Team::join(&team_id, &u[2], true, &db_pool).await;
+        assert!(matches!(res, Ok(JoinRes::Waitlisted { position: 1 })));
+        let res = Team::join(&team_id, &u[3], true, &db_pool).await;
+        assert!(matches!(res, Ok(JoinRes::Waitlisted { position: 2 })));
+        // members can't wait for another place
+        let res = Team::join(&team_id, &u[0], true, &db_pool).await;
+        assert!(matches!(res, Err(VaderError::AlreadyInTeam(_))));
+    }
+
+    #[actix_web::test]
+    async fn freed_place_goes_to_the_first_in_line() {
+        let db_pool = test_pool().await;
+        let team_id = team_event(2, &db_pool).await;
+        let u = users(4, &db_pool).await;
+        for user_id in &u {
+            Team::join(&team_id, user_id, true, &db_pool).await.unwrap();
+        }
+        assert_eq!(waitlist(&team_id, &db_pool).await, vec![u[2], u[3]]);
+        let promoted = Team::remove_member(&team_id, &u[0], &db_pool)
+            .await
+            .unwrap();
+        assert_eq!(promoted, vec![u[2]]);
+        assert_eq!(waitlist(&team_id, &db_pool).await, vec![u[3]]);
+        let members: u32 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM team_members WHERE team_id = ?")
+                .bind(team_id.to_string())
+                .fetch_one(&db_pool)
+                .await
+                .unwrap();
+        assert_eq!(members, 2);
+    }
+
+    #[actix_web::test]
+    async fn trashed_users_keep_their_place_but_are_skipped() {
+        let db_pool = test_pool().await;
+        let team_id = team_event(1, &db_pool).await;
+        let u = users(3, &db_pool).await;
+        for user_id in &u {
+            Team::join(&team_id, user_id, true, &db_pool).await.unwrap();
+        }
+        sqlx::query("UPDATE users SET deleted_at = '2024-01-02T00:00:00Z' WHERE id = ?")
+            .bind(u[1].to_string())
+            .execute(&db_pool)
+            .await
+            .unwrap();
+        let promoted = Team::remove_member(&team_id, &u[0], &db_pool)
+            .await
+            .unwrap();
+        assert_eq!(promoted, vec![u[2]]);
+        let waiting: u32 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM team_waitlist WHERE user_id = ?")
+                .bind(u[1].to_string())
+                .fetch_one(&db_pool)
+                .await
+                .unwrap();
+        assert_eq!(waiting, 1);
+    }
+}
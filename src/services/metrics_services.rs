@@ -0,0 +1,138 @@
+use std::sync::LazyLock;
+use std::time::Instant;
+
+use prometheus::{
+    histogram_opts, opts, Encoder, Histogram, HistogramVec, IntCounter, IntCounterVec, IntGauge,
+    IntGaugeVec, Registry, TextEncoder,
+};
+use sqlx::SqlitePool;
+
+use crate::models::error_models::VaderError;
+use crate::models::metrics_models::VaderMetrics;
+
+static METRICS: LazyLock<VaderMetrics> = LazyLock::new(VaderMetrics::new);
+
+pub fn metrics() -> &'static VaderMetrics {
+    &METRICS
+}
+
+pub fn ws_gauge(actor: &str) -> IntGauge {
+    METRICS.ws_connections.with_label_values(&[actor])
+}
+
+impl VaderMetrics {
+    fn new() -> Self {
+        let registry =
+            Registry::new_custom(Some("vader".to_string()), None).expect("Invalid metrics prefix");
+        let http_requests = IntCounterVec::new(
+            opts!("http_requests_total", "Http requests by route and status"),
+            &["method", "route", "status"],
+        )
+        .expect("Invalid metric");
+        let http_duration = HistogramVec::new(
+            histogram_opts!(
+                "http_request_duration_seconds",
+                "Http request latency by route"
+            ),
+            &["method", "route"],
+        )
+        .expect("Invalid metric");
+        let ws_connections = IntGaugeVec::new(
+            opts!("ws_connections", "Open websockets by actor"),
+            &["actor"],
+        )
+        .expect("Invalid metric");
+        let sse_connections = IntGauge::new("sse_connections", "Open VaderBoard event streams")
+            .expect("Invalid metric");
+        let vb_broadcasts = IntCounter::new(
+            "vaderboard_broadcasts_total",
+            "VaderBoard updates broadcast to the clients",
+        )
+        .expect("Invalid metric");
+        let vb_broadcast_duration = Histogram::with_opts(histogram_opts!(
+            "vaderboard_broadcast_duration_seconds",
+            "Time to build and send a VaderBoard broadcast"
+        ))
+        .expect("Invalid metric");
+        let score_updates = IntCounterVec::new(
+            opts!("score_updates_total", "Score updates by event"),
+            &["event_id"],
+        )
+        .expect("Invalid metric");
+        let db_pool = IntGaugeVec::new(
+            opts!("db_pool_connections", "Sqlite pool connections by state"),
+            &["state"],
+        )
+        .expect("Invalid metric");
+        let db_query_duration = HistogramVec::new(
+            histogram_opts!("db_query_duration_seconds", "Sqlite query latency"),
+            &["query"],
+        )
+        .expect("Invalid metric");
+        let login_failures =
+            IntCounter::new("login_failures_total", "Failed admin logins").expect("Invalid metric");
+        let metrics = VaderMetrics {
+            registry,
+            http_requests,
+            http_duration,
+            ws_connections,
+            sse_connections,
+            vb_broadcasts,
+            vb_broadcast_duration,
+            score_updates,
+            db_pool,
+            db_query_duration,
+            login_failures,
+        };
+        metrics.register().expect("Unable to register metrics");
+        metrics
+    }
+
+    fn register(&self) -> prometheus::Result<()> {
+        self.registry
+            .register(Box::new(self.http_requests.clone()))?;
+        self.registry
+            .register(Box::new(self.http_duration.clone()))?;
+        self.registry
+            .register(Box::new(self.ws_connections.clone()))?;
+        self.registry
+            .register(Box::new(self.sse_connections.clone()))?;
+        self.registry
+            .register(Box::new(self.vb_broadcasts.clone()))?;
+        self.registry
+            .register(Box::new(self.vb_broadcast_duration.clone()))?;
+        self.registry
+            .register(Box::new(self.score_updates.clone()))?;
+        self.registry.register(Box::new(self.db_pool.clone()))?;
+        self.registry
+            .register(Box::new(self.db_query_duration.clone()))?;
+        self.registry
+            .register(Box::new(self.login_failures.clone()))
+    }
+
+    pub fn observe_query(&self, query: &str, start: Instant) {
+        self.db_query_duration
+            .with_label_values(&[query])
+            .observe(start.elapsed().as_secs_f64());
+    }
+
+    // pool gauges are read when scraped , along with a ping for the db latency
+    pub async fn scrape(&self, db_pool: &SqlitePool) -> Result<String, VaderError<'static>> {
+        let start = Instant::now();
+        sqlx::query("SELECT 1").execute(db_pool).await?;
+        self.observe_query("ping", start);
+        let idle = db_pool.num_idle() as i64;
+        self.db_pool.with_label_values(&["idle"]).set(idle);
+        self.db_pool
+            .with_label_values(&["in_use"])
+            .set(db_pool.size() as i64 - idle);
+        self.db_pool
+            .with_label_values(&["max"])
+            .set(db_pool.options().get_max_connections() as i64);
+        let mut buf = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buf)
+            .map_err(|e| VaderError::MetricsError(e.to_string()))?;
+        String::from_utf8(buf).map_err(|e| VaderError::MetricsError(e.to_string()))
+    }
+}
@@ -1,4 +1,23 @@
+pub mod audit_services;
+pub mod backup_services;
+pub mod cache_services;
+pub mod checkin_services;
+pub mod config_services;
+pub mod db_services;
 pub mod event_services;
+pub mod export_services;
+pub mod fts_services;
+pub mod health_services;
+pub mod import_services;
+pub mod log_services;
+pub mod membership_services;
+pub mod metrics_services;
+pub mod page_services;
 pub mod query_services;
+pub mod registration_services;
+pub mod search_services;
+pub mod trash_services;
 pub mod v_middlewares;
+pub mod validation_services;
 pub mod vb_services;
+pub mod ws_services;
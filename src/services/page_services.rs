@@ -0,0 +1,348 @@
+use std::time::Instant;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::Serialize;
+use sqlx::sqlite::SqliteRow;
+use sqlx::{FromRow, Row, SqlitePool};
+use uuid::Uuid;
+
+use crate::models::error_models::VaderError;
+use crate::models::page_models::{
+    Cursor, CursorValue, FtsPager, FtsReq, Page, PageQuery, SearchPage, SortKey, SortOrder,
+    PAGE_LIMIT_DEFAULT,
+};
+use crate::services::metrics_services::metrics;
+
+fn invalid(msg: &str) -> VaderError<'static> {
+    VaderError::InvalidRequest(msg.to_string())
+}
+
+impl SortKey {
+    fn column(&self) -> &'static str {
+        match self {
+            SortKey::Name => "name",
+            SortKey::Score => "score",
+            SortKey::CreatedAt => "created_at",
+        }
+    }
+    fn collate(&self) -> &'static str {
+        match self {
+            SortKey::Name => "COLLATE NOCASE",
+            _ => "",
+        }
+    }
+    fn default_order(&self) -> SortOrder {
+        match self {
+            SortKey::Name => SortOrder::Asc,
+            _ => SortOrder::Desc,
+        }
+    }
+}
+
+impl Cursor {
+    fn encode(&self) -> Result<String, VaderError<'static>> {
+        Ok(URL_SAFE_NO_PAD.encode(serde_json::to_vec(self)?))
+    }
+    fn decode(cursor: &str) -> Result<Self, VaderError<'static>> {
+        URL_SAFE_NO_PAD
+            .decode(cursor)
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .ok_or_else(|| invalid("invalid cursor"))
+    }
+}
+
+// keyset pagination over `table alias` , ties on the sort key are broken by id
+// so rows are never skipped or repeated between pages : )
+// trashed rows (deleted_at set) are never listed
+pub async fn fetch_page<T>(
+    select: &str,
+    from: &str,
+    alias: &str,
+    keys: &[SortKey],
+    page: &PageQuery,
+    db_pool: &SqlitePool,
+) -> Result<Page<T>, VaderError<'static>>
+where
+    T: for<'r> FromRow<'r, SqliteRow> + Send + Unpin,
+{
+    if !keys.contains(&page.sort) {
+        return Err(invalid(&format!("can't sort by {}", page.sort.column())));
+    }
+    let order = page.order.unwrap_or(page.sort.default_order());
+    let limit = page.limit.unwrap_or(PAGE_LIMIT_DEFAULT);
+    let cursor = page.cursor.as_deref().map(Cursor::decode).transpose()?;
+    if let Some(cursor) = &cursor {
+        if cursor.sort != page.sort || cursor.order != order {
+            return Err(invalid("cursor belongs to another sort order"));
+        }
+    }
+    let (col, collate) = (page.sort.column(), page.sort.collate());
+    let (dir, cmp) = match order {
+        SortOrder::Asc => ("ASC", ">"),
+        SortOrder::Desc => ("DESC", "<"),
+    };
+    let after = match cursor {
+        Some(_) => format!("AND ({alias}.{col} {collate},{alias}.id) {cmp} (?,?)"),
+        None => String::new(),
+    };
+    let sql = format!(
+        "SELECT {select},{alias}.{col} AS sort_value FROM {from}
+         WHERE {alias}.deleted_at IS NULL {after}
+         ORDER BY {alias}.{col} {collate} {dir},{alias}.id {dir}
+         LIMIT ?"
+    );
+    let mut query = sqlx::query(&sql);
+    if let Some(cursor) = &cursor {
+        query = match &cursor.value {
+            CursorValue::Int(v) => query.bind(*v),
+            CursorValue::Text(v) => query.bind(v.clone()),
+        }
+        .bind(cursor.id.to_string());
+    }
+    let start = Instant::now();
+    let mut rows = query.bind(limit + 1).fetch_all(db_pool).await?;
+    metrics().observe_query("page", start);
+    let more = rows.len() > limit as usize;
+    rows.truncate(limit as usize);
+    let next_cursor = match rows.last() {
+        Some(last) if more => {
+            let value = match page.sort {
+                SortKey::Score => CursorValue::Int(last.try_get("sort_value")?),
+                _ => CursorValue::Text(last.try_get("sort_value")?),
+            };
+            let id: String = last.try_get("id")?;
+            let id = Uuid::parse_str(&id)
+                .map_err(|_| VaderError::SqlxFieldError("Error decoding Id"))?;
+            Some(
+                Cursor {
+                    sort: page.sort,
+                    order,
+                    value,
+                    id,
+                }
+                .encode()?,
+            )
+        }
+        _ => None,
+    };
+    let items = rows
+        .iter()
+        .map(T::from_row)
+        .collect::<Result<Vec<T>, sqlx::Error>>()?;
+    Ok(Page { items, next_cursor })
+}
+
+impl FtsPager {
+    // the (query , offset) to run for a websocket message
+    pub fn next(&mut self, msg: &str, count: u32) -> (String, u32) {
+        match serde_json::from_str::<FtsReq>(msg) {
+            // search() turns an offset past the window away
+            Ok(FtsReq::More) => self.offset = self.offset.saturating_add(count),
+            Ok(FtsReq::Search { query }) => {
+                self.query = query;
+                self.offset = 0;
+            }
+            Err(_) => {
+                self.query = msg.to_string();
+                self.offset = 0;
+            }
+        }
+        (self.query.clone(), self.offset)
+    }
+}
+
+// `items` is fetched with one extra row to tell if there is a next page
+pub fn search_page<T: Serialize>(
+    query: &str,
+    offset: u32,
+    count: u32,
+    mut items: Vec<T>,
+) -> Result<String, VaderError<'static>> {
+    let more = items.len() > count as usize;
+    items.truncate(count as usize);
+    Ok(serde_json::to_string(&SearchPage {
+        query,
+        offset,
+        items,
+        more,
+    })?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::db_services::test_pool;
+
+    async fn add_team(name: &str, score: i64, db_pool: &SqlitePool) -> Uuid {
+        let id = Uuid::new_v4();
+        sqlx::query("INSERT INTO teams (id,name,score,created_at) VALUES (?,?,?,?)")
+            .bind(id.to_string())
+            .bind(name)
+            .bind(score)
+            .bind(format!("2024-01-01T00:00:0{}Z", score))
+            .execute(db_pool)
+            .await
+            .unwrap();
+        id
+    }
+
+    async fn all_pages(page: &mut PageQuery, db_pool: &SqlitePool) -> Vec<(String, i64)> {
+        let mut rows = Vec::new();
+        loop {
+            let res: Page<(String, i64)> = fetch_page(
+                "t.id,t.score",
+                "teams t",
+                "t",
+                &[SortKey::Score],
+                page,
+                db_pool,
+            )
+            .await
+            .unwrap();
+            rows.extend(res.items);
+            match res.next_cursor {
+                Some(cursor) => page.cursor = Some(cursor),
+                None => return rows,
+            }
+        }
+    }
+
+    #[test]
+    fn cursor_round_trip() {
+        let cursor = Cursor {
+            sort: SortKey::Name,
+            order: SortOrder::Asc,
+            value: CursorValue::Text("Vader".to_string()),
+            id: Uuid::new_v4(),
+        };
+        let decoded = Cursor::decode(&cursor.encode().unwrap()).unwrap();
+        assert_eq!(decoded.sort, cursor.sort);
+        assert_eq!(decoded.order, cursor.order);
+        assert_eq!(decoded.id, cursor.id);
+        assert!(matches!(decoded.value, CursorValue::Text(v) if v == "Vader"));
+    }
+
+    #[test]
+    fn cursor_rejects_garbage() {
+        assert!(Cursor::decode("not a cursor").is_err());
+        assert!(Cursor::decode(&URL_SAFE_NO_PAD.encode(b"{\"sort\":\"score\"}")).is_err());
+    }
+
+    #[actix_web::test]
+    async fn ties_are_neither_skipped_nor_repeated() {
+        let db_pool = test_pool().await;
+        let mut ids = Vec::new();
+        for (i, score) in [3, 1, 3, 3, 2, 1, 3].into_iter().enumerate() {
+            ids.push(add_team(&format!("team {}", i), score, &db_pool).await);
+        }
+        let mut page = PageQuery {
+            sort: SortKey::Score,
+            limit: Some(2),
+            ..Default::default()
+        };
+        let rows = all_pages(&mut page, &db_pool).await;
+        assert_eq!(rows.len(), ids.len());
+        let scores = rows.iter().map(|(_, s)| *s).collect::<Vec<i64>>();
+        assert_eq!(scores, vec![3, 3, 3, 3, 2, 1, 1]);
+        // ties come in id order
+        for pair in rows.windows(2).filter(|p| p[0].1 == p[1].1) {
+            assert!(pair[0].0 > pair[1].0);
+        }
+        let mut seen = rows
+            .iter()
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<String>>();
+        seen.sort();
+        seen.dedup();
+        assert_eq!(seen.len(), ids.len());
+    }
+
+    #[actix_web::test]
+    async fn last_page_has_no_cursor() {
+        let db_pool = test_pool().await;
+        for score in 0..4 {
+            add_team(&format!("team {}", score), score, &db_pool).await;
+        }
+        let page = PageQuery {
+            sort: SortKey::Score,
+            limit: Some(4),
+            ..Default::default()
+        };
+        let res: Page<(String, i64)> = fetch_page(
+            "t.id,t.score",
+            "teams t",
+            "t",
+            &[SortKey::Score],
+            &page,
+            &db_pool,
+        )
+        .await
+        .unwrap();
+        assert_eq!(res.items.len(), 4);
+        assert!(res.next_cursor.is_none());
+    }
+
+    #[actix_web::test]
+    async fn trashed_rows_are_skipped() {
+        let db_pool = test_pool().await;
+        let live = add_team("live", 1, &db_pool).await;
+        let trashed = add_team("trashed", 2, &db_pool).await;
+        sqlx::query("UPDATE teams SET deleted_at = '2024-01-02T00:00:00Z' WHERE id = ?")
+            .bind(trashed.to_string())
+            .execute(&db_pool)
+            .await
+            .unwrap();
+        let rows = all_pages(
+            &mut PageQuery {
+                sort: SortKey::Score,
+                limit: Some(1),
+                ..Default::default()
+            },
+            &db_pool,
+        )
+        .await;
+        assert_eq!(rows, vec![(live.to_string(), 1)]);
+    }
+
+    #[actix_web::test]
+    async fn cursor_of_another_order_is_refused() {
+        let db_pool = test_pool().await;
+        let cursor = Cursor {
+            sort: SortKey::Score,
+            order: SortOrder::Desc,
+            value: CursorValue::Int(1),
+            id: Uuid::new_v4(),
+        };
+        let page = PageQuery {
+            sort: SortKey::Score,
+            order: Some(SortOrder::Asc),
+            cursor: Some(cursor.encode().unwrap()),
+            ..Default::default()
+        };
+        let res = fetch_page::<(String, i64)>(
+            "t.id,t.score",
+            "teams t",
+            "t",
+            &[SortKey::Score],
+            &page,
+            &db_pool,
+        )
+        .await;
+        assert!(matches!(res, Err(VaderError::InvalidRequest(_))));
+    }
+
+    #[test]
+    fn pager_pages_and_restarts() {
+        let mut pager = FtsPager::default();
+        assert_eq!(pager.next("vad", 10), ("vad".to_string(), 0));
+        assert_eq!(pager.next(r#"{"op":"more"}"#, 10), ("vad".to_string(), 10));
+        assert_eq!(
+            pager.next(r#"{"op":"search","query":"er"}"#, 10),
+            ("er".to_string(), 0)
+        );
+        pager.offset = u32::MAX - 1;
+        assert_eq!(pager.next(r#"{"op":"more"}"#, 10).1, u32::MAX);
+    }
+}
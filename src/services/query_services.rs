@@ -10,11 +10,21 @@ use sqlx::{FromRow, Row, SqlitePool};
 use uuid::Uuid;
 
 use crate::models::error_models::VaderError;
+use crate::models::health_models::HealthPing;
+use crate::models::metrics_models::{WS_CUR_EVENT_FTS, WS_FTS_QUERY};
+use crate::models::page_models::{Page, PageQuery, SortKey};
 use crate::models::query_models::{
-    CurEventFts, CurEventFtsWrapper, CurFtsConnect, CurFtsDisconnect, CurFtsServer, CurFtsStop,
-    EventInfo, EventQueryBuilder, EventType, FtsQuery, TeamFtsOpt, TeamInfo,
+    CurEventFts, CurEventFtsMarker, CurEventFtsWrapper, CurFtsConnect, CurFtsCount,
+    CurFtsDisconnect, CurFtsServer, CurFtsStop, EventInfo, EventQueryBuilder, EventType, FtsQuery,
+    TeamFtsOpt, TeamInfo,
 };
-use crate::models::v_models::{AsyncDbRes, Event, EventState, Player, Team, User};
+use crate::models::search_models::{SearchHit, SearchSpec};
+use crate::models::v_models::{AsyncDbRes, Event, EventState, Player, ScoreLimits, Team, User};
+use crate::models::ws_models::{WsActor, WsConn};
+use crate::services::metrics_services::ws_gauge;
+use crate::services::page_services::{fetch_page, search_page};
+use crate::services::search_services::{search, REM_USER_FILTER};
+use crate::services::ws_services::{schedule_query, start_heartbeat};
 
 impl FromRow<'_, SqliteRow> for Team<'_> {
     fn from_row(row: &'_ SqliteRow) -> Result<Self, sqlx::Error> {
@@ -25,9 +35,13 @@ impl FromRow<'_, SqliteRow> for Team<'_> {
         let name: String = row.get("name");
         let score: i64 = row.get("score");
         let logo: Option<String> = row.get("logo");
-        let tm: String = row.get("team_members");
+        // teams without members have NULL team_members : )
+        let tm: Option<String> = row.get("team_members");
         let members: Vec<Uuid> = tm
+            .as_deref()
+            .unwrap_or("")
             .split(',')
+            .filter(|m| !m.is_empty())
             .collect::<Vec<&str>>()
             .iter()
             .map(|m| {
@@ -37,12 +51,21 @@ impl FromRow<'_, SqliteRow> for Team<'_> {
                 })
             })
             .collect::<Result<Vec<Uuid>, sqlx::Error>>()?;
+        let captain: Option<String> = row.get("captain");
+        let captain = captain
+            .map(|c| Uuid::parse_str(&c))
+            .transpose()
+            .map_err(|_e| sqlx::Error::ColumnDecode {
+                index: "0".to_string(),
+                source: Box::new(VaderError::SqlxFieldError("Error decoding Captain Id")),
+            })?;
         Ok(Team {
             id,
             name: name.into(),
             logo: logo.map(|s| s.into()),
             score,
             members,
+            captain,
         })
     }
 }
@@ -97,6 +120,11 @@ where
         let name: String = row.get("name");
         let logo: Option<String> = row.get("logo");
         let team_size: Option<u32> = row.get("team_size");
+        let score_limits = ScoreLimits {
+            min_score: row.get("min_score"),
+            max_score: row.get("max_score"),
+            max_delta: row.get("max_delta"),
+        };
 
         Ok(Event {
             id,
@@ -105,6 +133,8 @@ where
             player_marker: PhantomData::<&'a T>,
             state_marker: PhantomData::<&'a U>,
             team_size,
+            score_limits,
+            exclude_unchecked: row.get("exclude_unchecked"),
         })
     }
 }
@@ -152,7 +182,7 @@ impl EventInfo<'_> {
         let id = event_id.to_string();
         Box::pin(async move {
             let event = sqlx::query_as::<_, EventInfo>(
-                "SELECT id,name,logo,event_type,team_size FROM events WHERE id = ?",
+                "SELECT id,name,logo,event_type,team_size FROM events WHERE id = ? AND deleted_at IS NULL",
             )
             .bind(&id)
             .fetch_one(db_pool)
@@ -163,99 +193,111 @@ impl EventInfo<'_> {
     pub fn get_all_event_info(db_pool: &SqlitePool) -> AsyncDbRes<'_, Vec<Self>> {
         Box::pin(async move {
             let event = sqlx::query_as::<_, EventInfo>(
-                "SELECT id,name,logo,event_type,team_size FROM events ORDER BY created_at DESC",
+                "SELECT id,name,logo,event_type,team_size FROM events WHERE deleted_at IS NULL
+                 ORDER BY created_at DESC",
             )
             .fetch_all(db_pool)
             .await?;
             Ok(event)
         })
     }
+    pub fn get_event_page<'a>(
+        page: &'a PageQuery,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, Page<EventInfo<'a>>> {
+        Box::pin(fetch_page(
+            "e.id,e.name,e.logo,e.event_type,e.team_size",
+            "events e",
+            "e",
+            &[SortKey::Name, SortKey::CreatedAt],
+            page,
+            db_pool,
+        ))
+    }
 }
 impl<'a> TeamInfo<'a> {
     pub fn get_all_team_info(db_pool: &SqlitePool) -> AsyncDbRes<'_, Vec<Self>> {
         Box::pin(async move {
             let teams = sqlx::query_as::<_, TeamInfo>(
-                "SELECT id,name,score,logo FROM teams ORDER BY created_at DESC",
+                "SELECT id,name,score,logo FROM teams WHERE deleted_at IS NULL
+                 ORDER BY created_at DESC",
             )
             .fetch_all(db_pool)
             .await?;
             Ok(teams)
         })
     }
+    pub fn get_team_page(
+        page: &'a PageQuery,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, Page<Self>> {
+        Box::pin(fetch_page(
+            "t.id,t.name,t.score,t.logo",
+            "teams t",
+            "t",
+            &[SortKey::Name, SortKey::Score, SortKey::CreatedAt],
+            page,
+            db_pool,
+        ))
+    }
     pub fn event_team_fts(
         event_id: &Uuid,
         param: &'a str,
+        offset: u32,
         count: u32,
         db_pool: &'a SqlitePool,
-    ) -> AsyncDbRes<'a, Vec<Self>> {
+    ) -> AsyncDbRes<'a, Vec<SearchHit<Self>>> {
+        let spec = SearchSpec::teams(Some(event_id));
+        let members = SearchSpec::team_members(Some(event_id));
         let event_id = event_id.to_string();
         Box::pin(async move {
-            let teams = if param.is_empty() {
+            let teams = if param.trim().is_empty() {
                 sqlx::query_as::<_, TeamInfo>(
                     "SELECT id,name,score,logo FROM teams t 
                      JOIN event_teams et ON et.team_id=t.id 
-                     WHERE et.event_id = ?
+                     WHERE et.event_id = ? AND t.deleted_at IS NULL
                      ORDER by t.created_at DESC 
-                     LIMIT ?
+                     LIMIT ? OFFSET ?
                     ",
                 )
                 .bind(&event_id)
                 .bind(count)
+                .bind(offset)
                 .fetch_all(db_pool)
                 .await?
+                .into_iter()
+                .map(SearchHit::plain)
+                .collect()
             } else {
-                sqlx::query_as::<_, TeamInfo>(
-                    "SELECT id,name,score,logo FROM teams_fts t 
-                    JOIN event_teams et ON et.team_id=t.id 
-                    WHERE et.event_id = ? AND name MATCH  ? || '*'
-                    LIMIT ?",
-                )
-                .bind(&event_id)
-                .bind(param)
-                .bind(count)
-                .fetch_all(db_pool)
-                .await?
+                search(&spec, Some(&members), param, offset, count, db_pool).await?
             };
             Ok(teams)
         })
     }
     pub fn event_rem_users_fts(
-        event_id: &Uuid,
         param: &'a str,
+        offset: u32,
         count: u32,
         db_pool: &'a SqlitePool,
-    ) -> AsyncDbRes<'a, Vec<User<'a>>> {
-        let event_id = event_id.to_string();
+    ) -> AsyncDbRes<'a, Vec<SearchHit<User<'a>>>> {
+        let spec = SearchSpec::rem_users();
         Box::pin(async move {
-            let rem_users = if param.is_empty() {
-                sqlx::query_as::<_, User>(
-                    "SELECT id,name,score,logo FROM users u 
-                    LEFT JOIN team_members tm ON tm.user_id=u.id 
-                    LEFT JOIN event_teams et ON  et.team_id=tm.team_id
-                    WHERE et.event_id = ? 
-                    AND tm.team_id IS NULL
-                    ORDER by u.created_at DESC 
-                    LIMIT ?",
-                )
-                .bind(&event_id)
+            let rem_users = if param.trim().is_empty() {
+                sqlx::query_as::<_, User>(&format!(
+                    "SELECT id,name,score,logo FROM users u
+                    WHERE u.deleted_at IS NULL {REM_USER_FILTER}
+                    ORDER by u.created_at DESC
+                    LIMIT ? OFFSET ?"
+                ))
                 .bind(count)
+                .bind(offset)
                 .fetch_all(db_pool)
                 .await?
+                .into_iter()
+                .map(SearchHit::plain)
+                .collect()
             } else {
-                sqlx::query_as::<_, User>(
-                    "SELECT id,name,score,logo FROM users_fts u 
-                    LEFT JOIN team_members tm ON tm.user_id=u.id 
-                    LEFT JOIN event_teams et ON  et.team_id=tm.team_id
-                    WHERE et.event_id = ? 
-                    AND tm.team_id IS NULL
-                    AND name MATCH  ? || '*'
-                    LIMIT ?",
-                )
-                .bind(&event_id)
-                .bind(param)
-                .bind(count)
-                .fetch_all(db_pool)
-                .await?
+                search(&spec, None, param, offset, count, db_pool).await?
             };
 
             Ok(rem_users)
@@ -264,9 +306,11 @@ impl<'a> TeamInfo<'a> {
 }
 
 pub trait Queriable {
+    // searches are ranked and report where they matched , see search_services : )
     type QueryRes;
     fn fts_query<'a, 'b>(
         param: &'a str,
+        offset: u32,
         count: u32,
         db_pool: &'b SqlitePool,
     ) -> AsyncDbRes<'a, Vec<Self::QueryRes>>
@@ -274,9 +318,10 @@ pub trait Queriable {
         'b: 'a;
 }
 impl Queriable for TeamInfo<'_> {
-    type QueryRes = Self;
+    type QueryRes = SearchHit<Self>;
     fn fts_query<'a, 'b>(
         param: &'a str,
+        offset: u32,
         count: u32,
         db_pool: &'b SqlitePool,
     ) -> AsyncDbRes<'a, Vec<Self::QueryRes>>
@@ -284,22 +329,29 @@ impl Queriable for TeamInfo<'_> {
         'b: 'a,
     {
         Box::pin(async move {
-            let teams = if param.is_empty() {
+            let teams = if param.trim().is_empty() {
                 sqlx::query_as::<_, TeamInfo>(
                     "SELECT id,name,score,logo FROM teams t
+                     WHERE t.deleted_at IS NULL
                      ORDER by t.created_at DESC 
-                     LIMIT ?",
+                     LIMIT ? OFFSET ?",
                 )
                 .bind(count)
+                .bind(offset)
                 .fetch_all(db_pool)
                 .await?
+                .into_iter()
+                .map(SearchHit::plain)
+                .collect()
             } else {
-                sqlx::query_as::<_, TeamInfo>(
-                    "SELECT id,name,score,logo FROM teams_fts WHERE name MATCH  ? || '*' LIMIT ?",
+                search(
+                    &SearchSpec::teams(None),
+                    Some(&SearchSpec::team_members(None)),
+                    param,
+                    offset,
+                    count,
+                    db_pool,
                 )
-                .bind(param)
-                .bind(count)
-                .fetch_all(db_pool)
                 .await?
             };
             Ok(teams)
@@ -307,9 +359,10 @@ impl Queriable for TeamInfo<'_> {
     }
 }
 impl Queriable for User<'_> {
-    type QueryRes = Self;
+    type QueryRes = SearchHit<Self>;
     fn fts_query<'a, 'b>(
         param: &'a str,
+        offset: u32,
         count: u32,
         db_pool: &'b SqlitePool,
     ) -> AsyncDbRes<'a, Vec<Self::QueryRes>>
@@ -317,22 +370,29 @@ impl Queriable for User<'_> {
         'b: 'a,
     {
         Box::pin(async move {
-            let users = if param.is_empty() {
+            let users = if param.trim().is_empty() {
                 sqlx::query_as::<_, User>(
                     "SELECT id,name,score,logo FROM users u
+                     WHERE u.deleted_at IS NULL
                      ORDER by u.created_at DESC 
-                     LIMIT ?",
+                     LIMIT ? OFFSET ?",
                 )
                 .bind(count)
+                .bind(offset)
                 .fetch_all(db_pool)
                 .await?
+                .into_iter()
+                .map(SearchHit::plain)
+                .collect()
             } else {
-                sqlx::query_as::<_, User>(
-                    "SELECT id,name,score,logo FROM users_fts WHERE name MATCH  ? || '*' LIMIT ?",
+                search(
+                    &SearchSpec::users(None),
+                    None,
+                    param,
+                    offset,
+                    count,
+                    db_pool,
                 )
-                .bind(param)
-                .bind(count)
-                .fetch_all(db_pool)
                 .await?
             };
 
@@ -341,9 +401,10 @@ impl Queriable for User<'_> {
     }
 }
 impl Queriable for EventInfo<'_> {
-    type QueryRes = Self;
+    type QueryRes = SearchHit<Self>;
     fn fts_query<'a, 'b>(
         param: &'a str,
+        offset: u32,
         count: u32,
         db_pool: &'b SqlitePool,
     ) -> AsyncDbRes<'a, Vec<Self::QueryRes>>
@@ -351,23 +412,22 @@ impl Queriable for EventInfo<'_> {
         'b: 'a,
     {
         Box::pin(async move {
-            let events = if param.is_empty() {
+            let events = if param.trim().is_empty() {
                 sqlx::query_as::<_, EventInfo>(
                     "SELECT id,name,logo,event_type,team_size FROM events e
+                     WHERE e.deleted_at IS NULL
                      ORDER by e.created_at DESC 
-                     LIMIT ?",
+                     LIMIT ? OFFSET ?",
                 )
                 .bind(count)
+                .bind(offset)
                 .fetch_all(db_pool)
                 .await?
+                .into_iter()
+                .map(SearchHit::plain)
+                .collect()
             } else {
-                sqlx::query_as::<_, EventInfo>(
-                    "SELECT id,name,logo,event_type,team_size FROM events_fts WHERE name MATCH  ? || '*' LIMIT ?",
-                )
-                .bind(param)
-                .bind(count)
-                .fetch_all(db_pool)
-                .await?
+                search(&SearchSpec::events(), None, param, offset, count, db_pool).await?
             };
             Ok(events)
         })
@@ -378,11 +438,37 @@ impl Queriable for EventInfo<'_> {
 #[rtype(result = "()")]
 struct FtsQueryRes(String);
 
+impl<'a, T> WsActor for FtsQuery<'a, T>
+where
+    T: Queriable,
+    Self: Actor<Context = ws::WebsocketContext<Self>>,
+{
+    fn conn(&mut self) -> &mut WsConn {
+        &mut self.conn
+    }
+}
+impl<'a, T> WsActor for CurEventFts<'a, T>
+where
+    T: CurEventFtsMarker,
+    Self: Actor<Context = ws::WebsocketContext<Self>>,
+{
+    fn conn(&mut self) -> &mut WsConn {
+        &mut self.conn
+    }
+}
+
 impl<'a> Actor for FtsQuery<'a, TeamInfo<'_>>
 where
     'a: 'static,
 {
     type Context = ws::WebsocketContext<Self>;
+    fn started(&mut self, ctx: &mut Self::Context) {
+        ws_gauge(WS_FTS_QUERY).inc();
+        start_heartbeat(self, ctx);
+    }
+    fn stopped(&mut self, _ctx: &mut Self::Context) {
+        ws_gauge(WS_FTS_QUERY).dec();
+    }
 }
 
 impl<'a> Handler<FtsQueryRes> for FtsQuery<'a, TeamInfo<'_>>
@@ -402,23 +488,33 @@ where
 {
     fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
         use ws::Message::*;
+        self.conn.beat();
         let pool = self.db_pool.clone();
         let addr = ctx.address();
         let count = self.count;
         match msg {
             Ok(Ping(msg)) => ctx.pong(&msg),
+            Ok(Close(reason)) => {
+                ctx.close(reason);
+                ctx.stop();
+            }
+            Err(_) => ctx.stop(),
             Ok(Text(param)) => {
-                async move {
-                    let res = TeamInfo::fts_query(&param, count, &pool)
-                        .await
-                        .and_then(|teams| Ok(serde_json::to_string(&teams)?));
-                    match res {
-                        Ok(teams) => addr.do_send(FtsQueryRes(teams)),
-                        Err(e) => log::debug!("Error Getting Teams Fts : {}", e),
+                let (query, offset) = self.pager.next(&param, count);
+                //new searches wait for typing to pause , "more" runs right away
+                schedule_query(self, ctx, offset == 0, move |act, ctx| {
+                    async move {
+                        let res = TeamInfo::fts_query(&query, offset, count + 1, &pool)
+                            .await
+                            .and_then(|teams| search_page(&query, offset, count, teams));
+                        match res {
+                            Ok(teams) => addr.do_send(FtsQueryRes(teams)),
+                            Err(e) => log::debug!("Error Getting Teams Fts : {}", e),
+                        }
                     }
-                }
-                .into_actor(self)
-                .wait(ctx);
+                    .into_actor(act)
+                    .wait(ctx);
+                });
             }
             _ => (),
         }
@@ -430,6 +526,13 @@ where
     'a: 'static,
 {
     type Context = ws::WebsocketContext<Self>;
+    fn started(&mut self, ctx: &mut Self::Context) {
+        ws_gauge(WS_FTS_QUERY).inc();
+        start_heartbeat(self, ctx);
+    }
+    fn stopped(&mut self, _ctx: &mut Self::Context) {
+        ws_gauge(WS_FTS_QUERY).dec();
+    }
 }
 
 impl<'a> Handler<FtsQueryRes> for FtsQuery<'a, User<'_>>
@@ -449,23 +552,33 @@ where
 {
     fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
         use ws::Message::*;
+        self.conn.beat();
         let pool = self.db_pool.clone();
         let addr = ctx.address();
         let count = self.count;
         match msg {
             Ok(Ping(msg)) => ctx.pong(&msg),
+            Ok(Close(reason)) => {
+                ctx.close(reason);
+                ctx.stop();
+            }
+            Err(_) => ctx.stop(),
             Ok(Text(param)) => {
-                async move {
-                    let res = User::fts_query(&param, count, &pool)
-                        .await
-                        .and_then(|users| Ok(serde_json::to_string(&users)?));
-                    match res {
-                        Ok(users) => addr.do_send(FtsQueryRes(users)),
-                        Err(e) => log::debug!("Error Getting Users Fts : {}", e),
+                let (query, offset) = self.pager.next(&param, count);
+                //new searches wait for typing to pause , "more" runs right away
+                schedule_query(self, ctx, offset == 0, move |act, ctx| {
+                    async move {
+                        let res = User::fts_query(&query, offset, count + 1, &pool)
+                            .await
+                            .and_then(|users| search_page(&query, offset, count, users));
+                        match res {
+                            Ok(users) => addr.do_send(FtsQueryRes(users)),
+                            Err(e) => log::debug!("Error Getting Users Fts : {}", e),
+                        }
                     }
-                }
-                .into_actor(self)
-                .wait(ctx);
+                    .into_actor(act)
+                    .wait(ctx);
+                });
             }
             _ => (),
         }
@@ -477,6 +590,13 @@ where
     'a: 'static,
 {
     type Context = ws::WebsocketContext<Self>;
+    fn started(&mut self, ctx: &mut Self::Context) {
+        ws_gauge(WS_FTS_QUERY).inc();
+        start_heartbeat(self, ctx);
+    }
+    fn stopped(&mut self, _ctx: &mut Self::Context) {
+        ws_gauge(WS_FTS_QUERY).dec();
+    }
 }
 
 impl<'a> Handler<FtsQueryRes> for FtsQuery<'a, EventInfo<'_>>
@@ -496,23 +616,33 @@ where
 {
     fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
         use ws::Message::*;
+        self.conn.beat();
         let pool = self.db_pool.clone();
         let addr = ctx.address();
         let count = self.count;
         match msg {
             Ok(Ping(msg)) => ctx.pong(&msg),
+            Ok(Close(reason)) => {
+                ctx.close(reason);
+                ctx.stop();
+            }
+            Err(_) => ctx.stop(),
             Ok(Text(param)) => {
-                async move {
-                    let res = EventInfo::fts_query(&param, count, &pool)
-                        .await
-                        .and_then(|events| Ok(serde_json::to_string(&events)?));
-                    match res {
-                        Ok(events_str) => addr.do_send(FtsQueryRes(events_str)),
-                        Err(e) => log::debug!("Error Getting Events Fts : {}", e),
+                let (query, offset) = self.pager.next(&param, count);
+                //new searches wait for typing to pause , "more" runs right away
+                schedule_query(self, ctx, offset == 0, move |act, ctx| {
+                    async move {
+                        let res = EventInfo::fts_query(&query, offset, count + 1, &pool)
+                            .await
+                            .and_then(|events| search_page(&query, offset, count, events));
+                        match res {
+                            Ok(events_str) => addr.do_send(FtsQueryRes(events_str)),
+                            Err(e) => log::debug!("Error Getting Events Fts : {}", e),
+                        }
                     }
-                }
-                .into_actor(self)
-                .wait(ctx);
+                    .into_actor(act)
+                    .wait(ctx);
+                });
             }
             _ => (),
         }
@@ -527,12 +657,15 @@ where
     fn started(&mut self, ctx: &mut Self::Context) {
         let addr = ctx.address();
         self.addr = Some(addr.clone());
+        ws_gauge(WS_CUR_EVENT_FTS).inc();
+        start_heartbeat(self, ctx);
         self.srv_addr
             .do_send(CurFtsConnect(CurEventFtsWrapper(actix_web::Either::Left(
                 addr,
             ))))
     }
     fn stopped(&mut self, _ctx: &mut Self::Context) {
+        ws_gauge(WS_CUR_EVENT_FTS).dec();
         if let Some(addr) = &self.addr {
             self.srv_addr.do_send(CurFtsDisconnect(CurEventFtsWrapper(
                 actix_web::Either::Left(addr.clone()),
@@ -546,38 +679,48 @@ where
 {
     fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
         use ws::Message::*;
+        self.conn.beat();
         let pool = self.db_pool.clone();
         let addr = ctx.address();
         match msg {
             Ok(Ping(msg)) => ctx.pong(&msg),
+            Ok(Close(reason)) => {
+                ctx.close(reason);
+                ctx.stop();
+            }
+            Err(_) => ctx.stop(),
             Ok(Text(param)) => {
                 let id = self.event_id;
                 let team_opt = self.team_opt;
                 let count = self.count;
-                async move {
-                    let res = match team_opt {
-                        Some(ref opt) => match opt {
-                            TeamFtsOpt::TeamInfo => {
-                                TeamInfo::event_team_fts(&id, &param, count, &pool)
-                                    .await
-                                    .and_then(|teams| Ok(serde_json::to_string(&teams)?))
-                            }
-                            TeamFtsOpt::RemUserInfo => {
-                                TeamInfo::event_rem_users_fts(&id, &param, count, &pool)
-                                    .await
-                                    .and_then(|users| Ok(serde_json::to_string(&users)?))
-                            }
-                        },
-                        None => unreachable!(),
-                    };
-
-                    match res {
-                        Ok(teams_str) => addr.do_send(FtsQueryRes(teams_str)),
-                        Err(e) => log::debug!("Error Getting Current Event Team Fts : {}", e),
+                let (query, offset) = self.pager.next(&param, count);
+                //new searches wait for typing to pause , "more" runs right away
+                schedule_query(self, ctx, offset == 0, move |act, ctx| {
+                    async move {
+                        let res = match team_opt {
+                            Some(ref opt) => match opt {
+                                TeamFtsOpt::TeamInfo => {
+                                    TeamInfo::event_team_fts(&id, &query, offset, count + 1, &pool)
+                                        .await
+                                        .and_then(|teams| search_page(&query, offset, count, teams))
+                                }
+                                TeamFtsOpt::RemUserInfo => {
+                                    TeamInfo::event_rem_users_fts(&query, offset, count + 1, &pool)
+                                        .await
+                                        .and_then(|users| search_page(&query, offset, count, users))
+                                }
+                            },
+                            None => unreachable!(),
+                        };
+
+                        match res {
+                            Ok(teams_str) => addr.do_send(FtsQueryRes(teams_str)),
+                            Err(e) => log::debug!("Error Getting Current Event Team Fts : {}", e),
+                        }
                     }
-                }
-                .into_actor(self)
-                .wait(ctx);
+                    .into_actor(act)
+                    .wait(ctx);
+                });
             }
             _ => (),
         }
@@ -602,12 +745,15 @@ where
     fn started(&mut self, ctx: &mut Self::Context) {
         let addr = ctx.address();
         self.addr = Some(addr.clone());
+        ws_gauge(WS_CUR_EVENT_FTS).inc();
+        start_heartbeat(self, ctx);
         self.srv_addr
             .do_send(CurFtsConnect(CurEventFtsWrapper(actix_web::Either::Right(
                 addr,
             ))))
     }
     fn stopped(&mut self, _ctx: &mut Self::Context) {
+        ws_gauge(WS_CUR_EVENT_FTS).dec();
         if let Some(addr) = &self.addr {
             self.srv_addr.do_send(CurFtsDisconnect(CurEventFtsWrapper(
                 actix_web::Either::Right(addr.clone()),
@@ -621,6 +767,7 @@ where
 {
     fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
         use ws::Message::*;
+        self.conn.beat();
         let pool = self.db_pool.clone();
         let addr = ctx.address();
         let id = self.event_id;
@@ -628,22 +775,31 @@ where
         let count = self.count;
         match msg {
             Ok(Ping(msg)) => ctx.pong(&msg),
+            Ok(Close(reason)) => {
+                ctx.close(reason);
+                ctx.stop();
+            }
+            Err(_) => ctx.stop(),
             Ok(Text(param)) => {
-                async move {
-                    let res = match team_opt {
-                        None => User::event_user_fts(&id, &param, count, &pool)
-                            .await
-                            .and_then(|users| Ok(serde_json::to_string(&users)?)),
-                        Some(_) => unreachable!(),
-                    };
-
-                    match res {
-                        Ok(teams_str) => addr.do_send(FtsQueryRes(teams_str)),
-                        Err(e) => log::debug!("Error Getting Current Event User Fts : {}", e),
+                let (query, offset) = self.pager.next(&param, count);
+                //new searches wait for typing to pause , "more" runs right away
+                schedule_query(self, ctx, offset == 0, move |act, ctx| {
+                    async move {
+                        let res = match team_opt {
+                            None => User::event_user_fts(&id, &query, offset, count + 1, &pool)
+                                .await
+                                .and_then(|users| search_page(&query, offset, count, users)),
+                            Some(_) => unreachable!(),
+                        };
+
+                        match res {
+                            Ok(teams_str) => addr.do_send(FtsQueryRes(teams_str)),
+                            Err(e) => log::debug!("Error Getting Current Event User Fts : {}", e),
+                        }
                     }
-                }
-                .into_actor(self)
-                .wait(ctx);
+                    .into_actor(act)
+                    .wait(ctx);
+                });
             }
             _ => (),
         }
@@ -679,6 +835,18 @@ impl Handler<CurFtsConnect<'_>> for CurFtsServer<'_> {
     }
 }
 
+impl Handler<CurFtsCount> for CurFtsServer<'_> {
+    type Result = usize;
+    fn handle(&mut self, _msg: CurFtsCount, _ctx: &mut Self::Context) -> Self::Result {
+        self.cfts_addr.len()
+    }
+}
+
+impl Handler<HealthPing> for CurFtsServer<'_> {
+    type Result = ();
+    fn handle(&mut self, _msg: HealthPing, _ctx: &mut Self::Context) -> Self::Result {}
+}
+
 impl Handler<CurFtsDisconnect<'_>> for CurFtsServer<'_> {
     type Result = ();
     fn handle(&mut self, msg: CurFtsDisconnect, _ctx: &mut Self::Context) -> Self::Result {
@@ -722,6 +890,8 @@ impl<'a, U: EventState> From<&Event<'a, Team<'a>, U>> for EventQueryBuilder<'a>
             event_type: EventType::TeamEvent {
                 team_size: e.team_size.unwrap(),
             },
+            score_limits: e.score_limits,
+            exclude_unchecked: e.exclude_unchecked,
         }
     }
 }
@@ -733,6 +903,8 @@ impl<'a, U: EventState> From<&Event<'a, User<'a>, U>> for EventQueryBuilder<'a>
             name: e.name.clone(),
             logo: e.logo.clone(),
             event_type: EventType::UserEvent,
+            score_limits: e.score_limits,
+            exclude_unchecked: e.exclude_unchecked,
         }
     }
 }
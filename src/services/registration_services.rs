@@ -0,0 +1,404 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use actix_web::HttpRequest;
+use chrono::{DateTime, Utc};
+use sqlx::error::ErrorKind;
+use sqlx::{Row, SqliteConnection, SqlitePool};
+use uuid::Uuid;
+
+use crate::models::config_models::{RegistrationConfig, WsConfig};
+use crate::models::error_models::VaderError;
+use crate::models::query_models::EventQueryState;
+use crate::models::registration_models::{
+    Registration, RegistrationGate, RegistrationRes, RegistrationStatus,
+};
+use crate::models::v_models::{AsyncDbRes, Event, Player, Team, User, VaderEvent};
+use crate::services::ws_services::client_ip;
+
+impl RegistrationGate {
+    // the client address is read the same way as for the websockets
+    pub fn new(config: &RegistrationConfig, ws_config: &WsConfig) -> Self {
+        RegistrationGate {
+            config: config.clone(),
+            trust_forwarded: ws_config.trust_forwarded,
+            attempts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // every attempt counts , failed ones too , so invite codes can't be guessed
+    pub fn admit(&self, req: &HttpRequest) -> Result<(), VaderError<'static>> {
+        let Some(ip) = client_ip(req, self.trust_forwarded) else {
+            return Ok(());
+        };
+        let window = Duration::from_secs(self.config.window_secs);
+        let now = Instant::now();
+        let mut attempts = self.attempts.lock().unwrap_or_else(|e| e.into_inner());
+        attempts.retain(|_, (start, _)| now.duration_since(*start) < window);
+        let (_, count) = attempts.entry(ip).or_insert((now, 0));
+        if *count >= self.config.max_per_ip {
+            return Err(VaderError::TooManyRequests(
+                "Too many registrations from this address , try again later",
+            ));
+        }
+        *count += 1;
+        Ok(())
+    }
+}
+
+// a public sign up gets a readable error instead of the sqlite constraint : )
+fn name_taken(e: VaderError<'_>) -> VaderError<'_> {
+    let VaderError::SqlxError(sqlx::Error::Database(db_err)) = &e else {
+        return e;
+    };
+    if db_err.kind() != ErrorKind::UniqueViolation {
+        return e;
+    }
+    match db_err.message() {
+        m if m.contains("teams.name") => VaderError::NameTaken("Team name is already taken"),
+        m if m.contains("users.name") => VaderError::NameTaken("Member name is already taken"),
+        _ => e,
+    }
+}
+
+impl RegistrationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Approved => "approved",
+            Self::Rejected => "rejected",
+        }
+    }
+    fn from_str(status: &str) -> Result<Self, VaderError<'static>> {
+        match status {
+            "pending" => Ok(Self::Pending),
+            "approved" => Ok(Self::Approved),
+            "rejected" => Ok(Self::Rejected),
+            _ => Err(VaderError::SqlxFieldError(
+                "Error decoding Registration status",
+            )),
+        }
+    }
+}
+
+// short enough to read out to a teammate , the unique index catches a clash
+fn invite_code() -> String {
+    Uuid::new_v4().simple().to_string()[..10].to_uppercase()
+}
+
+// the stored state is checked too , the cli may have started or stopped the event
+// behind the back of a running server
+pub fn registration_open<'c>(
+    event_id: &'c Uuid,
+    conn: &'c mut SqliteConnection,
+) -> AsyncDbRes<'c, bool> {
+    Box::pin(async move {
+        let open: Option<bool> = sqlx::query_scalar(
+            "SELECT self_registration AND state = ? FROM events WHERE id = ? AND deleted_at IS NULL",
+        )
+        .bind(EventQueryState::Added.as_str())
+        .bind(event_id.to_string())
+        .fetch_optional(conn)
+        .await?;
+        Ok(open.unwrap_or(false))
+    })
+}
+
+pub fn set_registration<'b>(
+    event_id: &Uuid,
+    open: bool,
+    db_pool: &'b SqlitePool,
+) -> AsyncDbRes<'b, ()> {
+    let id = event_id.to_string();
+    Box::pin(async move {
+        let res = sqlx::query(
+            "UPDATE events SET self_registration = ? WHERE id = ? AND deleted_at IS NULL",
+        )
+        .bind(open)
+        .bind(id)
+        .execute(db_pool)
+        .await?;
+        if res.rows_affected().eq(&0) {
+            return Err(VaderError::EventNotFound("No event found"));
+        }
+        Ok(())
+    })
+}
+
+// oldest first , the order they came in
+pub fn get_registrations<'b>(
+    event_id: &Uuid,
+    status: Option<RegistrationStatus>,
+    db_pool: &'b SqlitePool,
+) -> AsyncDbRes<'b, Vec<Registration>> {
+    let id = event_id.to_string();
+    Box::pin(async move {
+        let rows = sqlx::query(
+            "SELECT r.id AS id,r.team_id AS team_id,t.name AS team_name,
+             (SELECT COUNT(*) FROM team_members tm WHERE tm.team_id = r.team_id) AS members,
+             r.invite_code AS invite_code,r.status AS status,
+             r.created_at AS created_at,r.decided_at AS decided_at
+             FROM registrations r JOIN teams t ON t.id = r.team_id
+             WHERE r.event_id = ?1 AND (?2 IS NULL OR r.status = ?2) ORDER BY r.created_at",
+        )
+        .bind(id)
+        .bind(status.map(|s| s.as_str()))
+        .fetch_all(db_pool)
+        .await?;
+        rows.iter()
+            .map(|row| {
+                let id = Uuid::parse_str(row.get("id"))
+                    .map_err(|_e| VaderError::SqlxFieldError("Error decoding Registration Id"))?;
+                let team_id = Uuid::parse_str(row.get("team_id"))
+                    .map_err(|_e| VaderError::SqlxFieldError("Error decoding Team Id"))?;
+                Ok(Registration {
+                    id,
+                    team_id,
+                    team_name: row.get("team_name"),
+                    members: row.get("members"),
+                    invite_code: row.get("invite_code"),
+                    status: RegistrationStatus::from_str(row.get("status"))?,
+                    created_at: row.get::<DateTime<Utc>, _>("created_at"),
+                    decided_at: row.get::<Option<DateTime<Utc>>, _>("decided_at"),
+                })
+            })
+            .collect()
+    })
+}
+
+// registered teams stay out of event_teams till an admin approves them : )
+impl<'a> Event<'a, Team<'a>> {
+    pub fn register_team(
+        &'a self,
+        team: &'a Team<'a>,
+        members: &'a [User<'a>],
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, RegistrationRes> {
+        Box::pin(async move {
+            let team_size = self
+                .team_size
+                .ok_or(VaderError::EventTypeMismatch("Team size not specified"))?;
+            let mut transaction = db_pool.begin().await?;
+            if !registration_open(&self.id, &mut transaction).await? {
+                return Err(VaderError::RegistrationClosed(
+                    "Event is not taking registrations",
+                ));
+            }
+            team.with_members(members, team_size as usize, &mut transaction)
+                .await
+                .map_err(name_taken)?;
+            let res = RegistrationRes {
+                id: Uuid::new_v4(),
+                team_id: team.id,
+                invite_code: invite_code(),
+                status: RegistrationStatus::Pending,
+            };
+            sqlx::query(
+                "INSERT INTO registrations (id,event_id,team_id,invite_code,status,created_at)
+                 VALUES (?,?,?,?,?,?)",
+            )
+            .bind(res.id.to_string())
+            .bind(self.id.to_string())
+            .bind(team.id.to_string())
+            .bind(&res.invite_code)
+            .bind(res.status.as_str())
+            .bind(Utc::now())
+            .execute(&mut *transaction)
+            .await?;
+            transaction.commit().await?;
+            Ok(res)
+        })
+    }
+
+    // the user joins the team of the invite code , returns the team id
+    pub fn join_registration(
+        &'a self,
+        invite_code: &'a str,
+        user: &'a User<'a>,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, Uuid> {
+        Box::pin(async move {
+            let mut transaction = db_pool.begin().await?;
+            if !registration_open(&self.id, &mut transaction).await? {
+                return Err(VaderError::RegistrationClosed(
+                    "Event is not taking registrations",
+                ));
+            }
+            let row = sqlx::query(
+                "SELECT team_id,status FROM registrations WHERE invite_code = ? AND event_id = ?",
+            )
+            .bind(invite_code.trim().to_uppercase())
+            .bind(self.id.to_string())
+            .fetch_optional(&mut *transaction)
+            .await?
+            .ok_or(VaderError::RegistrationNotFound(
+                "No registration found for the invite code",
+            ))?;
+            if RegistrationStatus::from_str(row.get("status"))? == RegistrationStatus::Rejected {
+                return Err(VaderError::RegistrationClosed("Registration was rejected"));
+            }
+            let team_id = Uuid::parse_str(row.get("team_id"))
+                .map_err(|_e| VaderError::SqlxFieldError("Error decoding Team Id"))?;
+            user.add_player(&mut transaction)
+                .await
+                .map_err(name_taken)?;
+            Team::add_members_from_id(&team_id, std::slice::from_ref(&user.id), &mut transaction)
+                .await?;
+            transaction.commit().await?;
+            Ok(team_id)
+        })
+    }
+
+    // approving links the team to the event , rejecting moves it and its members
+    // to the trash so their names are free again , returns the team id
+    pub fn decide_registration(
+        &'a self,
+        id: &'a Uuid,
+        status: RegistrationStatus,
+        db_pool: &'a SqlitePool,
+    ) -> AsyncDbRes<'a, Uuid> {
+        Box::pin(async move {
+            let mut transaction = db_pool.begin().await?;
+            let team_id: Option<String> = sqlx::query_scalar(
+                "UPDATE registrations SET status = ?, decided_at = ?
+                 WHERE id = ? AND event_id = ? AND status = 'pending' RETURNING team_id",
+            )
+            .bind(status.as_str())
+            .bind(Utc::now())
+            .bind(id.to_string())
+            .bind(self.id.to_string())
+            .fetch_optional(&mut *transaction)
+            .await?;
+            let team_id = team_id.ok_or(VaderError::RegistrationNotFound(
+                "No pending registration found",
+            ))?;
+            let team_id = Uuid::parse_str(&team_id)
+                .map_err(|_e| VaderError::SqlxFieldError("Error decoding Team Id"))?;
+            match status {
+                RegistrationStatus::Approved => {
+                    self.add_participant_from_id(team_id, &mut transaction)
+                        .await?;
+                }
+                _ => {
+                    let now = Utc::now();
+                    sqlx::query(
+                        "UPDATE users SET deleted_at = ? WHERE deleted_at IS NULL
+                         AND id IN (SELECT user_id FROM team_members WHERE team_id = ?)",
+                    )
+                    .bind(now)
+                    .bind(team_id.to_string())
+                    .execute(&mut *transaction)
+                    .await?;
+                    sqlx::query(
+                        "UPDATE teams SET deleted_at = ? WHERE id = ? AND deleted_at IS NULL",
+                    )
+                    .bind(now)
+                    .bind(team_id.to_string())
+                    .execute(&mut *transaction)
+                    .await?;
+                }
+            }
+            transaction.commit().await?;
+            Ok(team_id)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::*;
+    use crate::models::v_models::NewEvent;
+    use crate::services::db_services::test_pool;
+
+    // a team event of 2 taking registrations
+    async fn open_event(db_pool: &SqlitePool) -> Event<'static, Team<'static>, NewEvent> {
+        let event = Event::new(Cow::Borrowed("event"), None, Some(2));
+        sqlx::query(
+            "INSERT INTO events (id,name,event_type,team_size,state,self_registration,created_at)
+             VALUES (?,'event','team_event',2,'added',1,'2024-01-01')",
+        )
+        .bind(event.id.to_string())
+        .execute(db_pool)
+        .await
+        .unwrap();
+        event
+    }
+
+    fn player(name: &str) -> User<'static> {
+        User {
+            id: Uuid::new_v4(),
+            name: Cow::Owned(name.to_string()),
+            score: 0,
+            logo: None,
+        }
+    }
+
+    fn team(name: &str) -> Team<'static> {
+        Team {
+            id: Uuid::new_v4(),
+            name: Cow::Owned(name.to_string()),
+            score: 0,
+            logo: None,
+            members: Vec::new(),
+            captain: None,
+        }
+    }
+
+    #[actix_web::test]
+    async fn rejected_names_are_free_again() {
+        let db_pool = test_pool().await;
+        let event = open_event(&db_pool).await;
+        let (rejected, members) = (team("team"), [player("member")]);
+        let res = event
+            .register_team(&rejected, &members, &db_pool)
+            .await
+            .unwrap();
+        let joiner = player("joiner");
+        event
+            .join_registration(&res.invite_code, &joiner, &db_pool)
+            .await
+            .unwrap();
+        event
+            .decide_registration(&res.id, RegistrationStatus::Rejected, &db_pool)
+            .await
+            .unwrap();
+        let live: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE deleted_at IS NULL")
+            .fetch_one(&db_pool)
+            .await
+            .unwrap();
+        assert_eq!(live, 0);
+        // the same team can sign up again
+        let (retry, members) = (team("team"), [player("member"), player("joiner")]);
+        assert!(event
+            .register_team(&retry, &members, &db_pool)
+            .await
+            .is_ok());
+    }
+
+    #[actix_web::test]
+    async fn stopped_event_takes_no_registrations() {
+        let db_pool = test_pool().await;
+        let event = open_event(&db_pool).await;
+        let (first, members) = (team("first"), [player("first member")]);
+        let res = event
+            .register_team(&first, &members, &db_pool)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE events SET state = 'stopped' WHERE id = ?")
+            .bind(event.id.to_string())
+            .execute(&db_pool)
+            .await
+            .unwrap();
+        let (second, members) = (team("second"), [player("second member")]);
+        let reg = event.register_team(&second, &members, &db_pool).await;
+        assert!(matches!(reg, Err(VaderError::RegistrationClosed(_))));
+        let joiner = player("joiner");
+        let join = event
+            .join_registration(&res.invite_code, &joiner, &db_pool)
+            .await;
+        assert!(matches!(join, Err(VaderError::RegistrationClosed(_))));
+    }
+}
@@ -0,0 +1,340 @@
+use std::collections::HashSet;
+use std::time::Instant;
+
+use sqlx::sqlite::SqliteRow;
+use sqlx::{FromRow, Row, SqlitePool};
+use uuid::Uuid;
+
+use crate::models::error_models::VaderError;
+use crate::models::query_models::{EventInfo, TeamInfo};
+use crate::models::search_models::{
+    MatchKind, SearchHit, SearchSpec, FUZZY_CANDIDATES, FUZZY_MIN_SIMILARITY,
+};
+use crate::models::v_models::User;
+use crate::models::validation_models::{
+    FieldError, PAGE_LIMIT_MAX, SEARCH_OFFSET_MAX, SEARCH_WINDOW_MAX,
+};
+use crate::services::metrics_services::metrics;
+
+// highlight() markers , names can't hold control chars so these never clash
+const MARK_START: char = '\u{2}';
+const MARK_END: char = '\u{3}';
+
+pub trait Searchable: for<'r> FromRow<'r, SqliteRow> + Send + Unpin {
+    fn search_id(&self) -> Uuid;
+    fn search_name(&self) -> &str;
+}
+impl Searchable for TeamInfo<'_> {
+    fn search_id(&self) -> Uuid {
+        self.id
+    }
+    fn search_name(&self) -> &str {
+        &self.name
+    }
+}
+impl Searchable for User<'_> {
+    fn search_id(&self) -> Uuid {
+        self.id
+    }
+    fn search_name(&self) -> &str {
+        &self.name
+    }
+}
+impl Searchable for EventInfo<'_> {
+    fn search_id(&self) -> Uuid {
+        self.id
+    }
+    fn search_name(&self) -> &str {
+        &self.name
+    }
+}
+
+// every whitespace separated term becomes a quoted prefix phrase , so fts5 syntax
+// in the input (quotes , hyphens , AND/OR/NEAR , column filters) is matched literally : )
+pub fn match_expr(param: &str) -> Option<String> {
+    let terms = param
+        .split_whitespace()
+        .map(|term| format!("\"{}\"*", term.replace('"', "\"\"")))
+        .collect::<Vec<String>>();
+    match terms.is_empty() {
+        true => None,
+        false => Some(format!("name : ({})", terms.join(" "))),
+    }
+}
+
+// trigrams inside each word , what the trigram index can look up
+fn word_trigrams(text: &str) -> HashSet<String> {
+    text.to_lowercase()
+        .split_whitespace()
+        .flat_map(|word| {
+            let chars = word.chars().collect::<Vec<char>>();
+            chars
+                .windows(3)
+                .map(|w| w.iter().collect::<String>())
+                .collect::<Vec<String>>()
+        })
+        .collect()
+}
+
+// words padded like pg_trgm , so a typo in a short word still leaves the
+// start/end trigrams to compare : )
+fn padded_trigrams(text: &str) -> HashSet<String> {
+    word_trigrams(
+        &text
+            .split_whitespace()
+            .map(|word| format!("\u{1}\u{1}{}\u{1}", word))
+            .collect::<Vec<String>>()
+            .join(" "),
+    )
+}
+
+fn trigram_expr(trigrams: &HashSet<String>) -> Option<String> {
+    let terms = trigrams
+        .iter()
+        .map(|t| format!("\"{}\"", t.replace('"', "\"\"")))
+        .collect::<Vec<String>>();
+    match terms.is_empty() {
+        true => None,
+        false => Some(terms.join(" OR ")),
+    }
+}
+
+// share of the query trigrams found in the name
+fn similarity(query: &HashSet<String>, name: &str) -> f64 {
+    let name = padded_trigrams(name);
+    query.intersection(&name).count() as f64 / query.len() as f64
+}
+
+// escape for html and swap the highlight() markers for <mark> tags
+fn mark(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len() + 16);
+    for c in raw.chars() {
+        match c {
+            MARK_START => out.push_str("<mark>"),
+            MARK_END => out.push_str("</mark>"),
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+async fn fetch_hits<T: Searchable>(
+    spec: &SearchSpec,
+    index: &str,
+    column: u8,
+    expr: &str,
+    limit: u32,
+    matched: MatchKind,
+    db_pool: &SqlitePool,
+) -> Result<Vec<SearchHit<T>>, VaderError<'static>> {
+    let sql = format!(
+        "SELECT {select},highlight({index},{column},char(2),char(3)) AS highlight
+         FROM {index} JOIN {table} {alias} ON {alias}.rowid={index}.rowid {joins}
+         WHERE {index} MATCH ?1 AND {alias}.deleted_at IS NULL {filter}
+         ORDER BY bm25({index})
+         LIMIT ?2",
+        select = spec.select,
+        table = spec.table,
+        alias = spec.alias,
+        joins = spec.joins,
+        filter = spec.filter,
+    );
+    let mut query = sqlx::query(&sql).bind(expr).bind(limit);
+    if let Some(event_id) = &spec.event_id {
+        query = query.bind(event_id);
+    }
+    let start = Instant::now();
+    let rows = query.fetch_all(db_pool).await?;
+    metrics().observe_query("search", start);
+    rows.iter()
+        .map(|row| {
+            let highlight: Option<String> = row.try_get("highlight")?;
+            Ok(SearchHit {
+                item: T::from_row(row)?,
+                matched: Some(matched),
+                highlight: highlight.as_deref().map(mark),
+            })
+        })
+        .collect()
+}
+
+// ranked name matches , then teams matched by a member name (`members`) , then
+// typo tolerant trigram matches until `count` results , trashed rows never match : )
+pub async fn search<T: Searchable>(
+    spec: &SearchSpec,
+    members: Option<&SearchSpec>,
+    param: &str,
+    offset: u32,
+    count: u32,
+    db_pool: &SqlitePool,
+) -> Result<Vec<SearchHit<T>>, VaderError<'static>> {
+    let Some(expr) = match_expr(param) else {
+        return Ok(Vec::new());
+    };
+    // hits come from three merged queries , so later pages rerun them and skip
+    let count = offset
+        .checked_add(count)
+        .filter(|window| *window <= SEARCH_WINDOW_MAX)
+        .ok_or_else(window_exceeded)? as usize;
+    let mut hits = fetch_hits::<T>(
+        spec,
+        spec.fts,
+        1,
+        &expr,
+        count as u32,
+        MatchKind::Name,
+        db_pool,
+    )
+    .await?;
+    let mut seen = hits
+        .iter()
+        .map(|hit| hit.item.search_id())
+        .collect::<HashSet<Uuid>>();
+    if let Some(members) = members.filter(|_| hits.len() < count) {
+        let member_hits = fetch_hits::<T>(
+            members,
+            members.fts,
+            1,
+            &expr,
+            count as u32,
+            MatchKind::Member,
+            db_pool,
+        )
+        .await?;
+        for hit in member_hits {
+            if hits.len() < count && seen.insert(hit.item.search_id()) {
+                hits.push(hit);
+            }
+        }
+    }
+    if hits.len() < count {
+        if let Some(trgm_expr) = trigram_expr(&word_trigrams(param)) {
+            let query = padded_trigrams(param);
+            let limit = (count as u32).saturating_mul(FUZZY_CANDIDATES);
+            let mut fuzzy = fetch_hits::<T>(
+                spec,
+                spec.trgm,
+                0,
+                &trgm_expr,
+                limit,
+                MatchKind::Fuzzy,
+                db_pool,
+            )
+            .await?
+            .into_iter()
+            .filter(|hit| !seen.contains(&hit.item.search_id()))
+            .map(|hit| (similarity(&query, hit.item.search_name()), hit))
+            .filter(|(score, _)| *score >= FUZZY_MIN_SIMILARITY)
+            .collect::<Vec<(f64, SearchHit<T>)>>();
+            // stable , ties keep the bm25 order
+            fuzzy.sort_by(|a, b| b.0.total_cmp(&a.0));
+            let wanted = count - hits.len();
+            hits.extend(fuzzy.into_iter().take(wanted).map(|(_, hit)| hit));
+        }
+    }
+    Ok(hits.into_iter().skip(offset as usize).collect())
+}
+
+fn window_exceeded() -> VaderError<'static> {
+    VaderError::ValidationError(vec![FieldError {
+        field: "offset".to_string(),
+        code: "range".to_string(),
+        message: format!(
+            "offset must be at most {} , with count up to {}",
+            SEARCH_OFFSET_MAX, PAGE_LIMIT_MAX
+        ),
+    }])
+}
+
+const TEAM_SELECT: &str = "t.id,t.name,t.score,t.logo";
+const USER_SELECT: &str = "u.id,u.name,u.score,u.logo";
+const EVENT_FILTER: &str = "AND et.event_id = ?3";
+pub const REM_USER_FILTER: &str = "AND NOT EXISTS (SELECT 1 FROM team_members tm
+     JOIN teams t ON t.id=tm.team_id WHERE tm.user_id=u.id AND t.deleted_at IS NULL)";
+
+impl SearchSpec {
+    // all teams , or the teams of an event
+    pub fn teams(event_id: Option<&Uuid>) -> Self {
+        Self {
+            select: TEAM_SELECT,
+            table: "teams",
+            alias: "t",
+            fts: "teams_fts",
+            trgm: "teams_trgm",
+            joins: match event_id {
+                Some(_) => "JOIN event_teams et ON et.team_id=t.id",
+                None => "",
+            },
+            filter: match event_id {
+                Some(_) => EVENT_FILTER,
+                None => "",
+            },
+            event_id: event_id.map(Uuid::to_string),
+        }
+    }
+    // teams found through the names of their members
+    pub fn team_members(event_id: Option<&Uuid>) -> Self {
+        Self {
+            select: TEAM_SELECT,
+            table: "users",
+            alias: "u",
+            fts: "users_fts",
+            trgm: "users_trgm",
+            joins: match event_id {
+                Some(_) => {
+                    "JOIN team_members tm ON tm.user_id=u.id JOIN teams t ON t.id=tm.team_id
+                     JOIN event_teams et ON et.team_id=t.id"
+                }
+                None => "JOIN team_members tm ON tm.user_id=u.id JOIN teams t ON t.id=tm.team_id",
+            },
+            filter: match event_id {
+                Some(_) => "AND et.event_id = ?3 AND t.deleted_at IS NULL",
+                None => "AND t.deleted_at IS NULL",
+            },
+            event_id: event_id.map(Uuid::to_string),
+        }
+    }
+    // all users , or the users of a user event
+    pub fn users(event_id: Option<&Uuid>) -> Self {
+        Self {
+            select: USER_SELECT,
+            table: "users",
+            alias: "u",
+            fts: "users_fts",
+            trgm: "users_trgm",
+            joins: match event_id {
+                Some(_) => "JOIN event_users ut ON ut.user_id=u.id",
+                None => "",
+            },
+            filter: match event_id {
+                Some(_) => "AND ut.event_id = ?3",
+                None => "",
+            },
+            event_id: event_id.map(Uuid::to_string),
+        }
+    }
+    // users without a team , members of a trashed team are free to join another
+    pub fn rem_users() -> Self {
+        Self {
+            filter: REM_USER_FILTER,
+            ..Self::users(None)
+        }
+    }
+    pub fn events() -> Self {
+        Self {
+            select: "e.id,e.name,e.logo,e.event_type,e.team_size",
+            table: "events",
+            alias: "e",
+            fts: "events_fts",
+            trgm: "events_trgm",
+            joins: "",
+            filter: "",
+            event_id: None,
+        }
+    }
+}
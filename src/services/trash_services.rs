@@ -0,0 +1,131 @@
+use std::cmp::Reverse;
+
+use chrono::{DateTime, Duration, Utc};
+use sqlx::error::ErrorKind;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::models::error_models::VaderError;
+use crate::models::trash_models::{PurgeReport, TrashItem, TrashKind};
+
+const TRASH_KINDS: [TrashKind; 3] = [TrashKind::Event, TrashKind::Team, TrashKind::User];
+
+impl TrashKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TrashKind::Event => "event",
+            TrashKind::Team => "team",
+            TrashKind::User => "user",
+        }
+    }
+    // fixed names , fine to format into sql
+    fn table(&self) -> &'static str {
+        match self {
+            TrashKind::Event => "events",
+            TrashKind::Team => "teams",
+            TrashKind::User => "users",
+        }
+    }
+    fn not_found(&self) -> VaderError<'static> {
+        match self {
+            TrashKind::Event => VaderError::EventNotFound("No deleted event found"),
+            TrashKind::Team => VaderError::TeamNotFound("No deleted team found"),
+            TrashKind::User => VaderError::UserNotFound("No deleted user found"),
+        }
+    }
+    fn name_taken(&self) -> VaderError<'static> {
+        VaderError::NameTaken(match self {
+            TrashKind::Event => "Another event took this name , rename it before restoring",
+            TrashKind::Team => "Another team took this name , rename it before restoring",
+            TrashKind::User => "Another user took this name , rename it before restoring",
+        })
+    }
+}
+
+// newest deletions first
+pub async fn list_trash(
+    kind: Option<TrashKind>,
+    retention_days: u32,
+    db_pool: &SqlitePool,
+) -> Result<Vec<TrashItem>, VaderError<'static>> {
+    let retention = Duration::days(retention_days.into());
+    let mut items = Vec::new();
+    for kind in TRASH_KINDS
+        .into_iter()
+        .filter(|k| kind.is_none_or(|f| f.eq(k)))
+    {
+        let rows: Vec<(String, String, DateTime<Utc>)> = sqlx::query_as(&format!(
+            "SELECT id,name,deleted_at FROM {} WHERE deleted_at IS NOT NULL",
+            kind.table()
+        ))
+        .fetch_all(db_pool)
+        .await?;
+        for (id, name, deleted_at) in rows {
+            let id = Uuid::parse_str(&id)
+                .map_err(|_| VaderError::SqlxFieldError("Error decoding Id"))?;
+            items.push(TrashItem {
+                kind,
+                id,
+                name,
+                deleted_at,
+                purge_after: deleted_at + retention,
+            });
+        }
+    }
+    items.sort_by_key(|i| Reverse(i.deleted_at));
+    Ok(items)
+}
+
+// links were never removed , so clearing deleted_at brings back the event
+// teams/users or team members as they were : )
+pub async fn restore_trashed(
+    kind: TrashKind,
+    id: &Uuid,
+    db_pool: &SqlitePool,
+) -> Result<(), VaderError<'static>> {
+    // trashed names aren't reserved , a live row may have taken it meanwhile
+    let res = sqlx::query(&format!(
+        "UPDATE {} SET deleted_at = NULL WHERE id = ? AND deleted_at IS NOT NULL",
+        kind.table()
+    ))
+    .bind(id.to_string())
+    .execute(db_pool)
+    .await
+    .map_err(|e| match e.as_database_error().map(|e| e.kind()) {
+        Some(ErrorKind::UniqueViolation) => kind.name_taken(),
+        _ => e.into(),
+    })?;
+    if res.rows_affected().eq(&0) {
+        return Err(kind.not_found());
+    }
+    Ok(())
+}
+
+// removes rows deleted more than `older_than_days` ago for good , the
+// ON DELETE CASCADE links and fts rows go with them
+pub async fn purge_trash(
+    older_than_days: u32,
+    db_pool: &SqlitePool,
+) -> Result<PurgeReport, VaderError<'static>> {
+    let cutoff = Utc::now() - Duration::days(older_than_days.into());
+    let mut transaction = db_pool.begin().await?;
+    let mut purged = [0; 3];
+    for (kind, count) in TRASH_KINDS.iter().zip(purged.iter_mut()) {
+        *count = sqlx::query(&format!(
+            "DELETE FROM {} WHERE deleted_at IS NOT NULL AND deleted_at <= ?",
+            kind.table()
+        ))
+        .bind(cutoff)
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+    }
+    transaction.commit().await?;
+    let [events, teams, users] = purged;
+    Ok(PurgeReport {
+        cutoff,
+        events,
+        teams,
+        users,
+    })
+}
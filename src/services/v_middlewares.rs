@@ -1,10 +1,26 @@
+use std::cell::RefCell;
 use std::future::{ready, Ready};
+use std::rc::Rc;
+use std::time::Instant;
 
+use actix::Addr;
 use actix_session::SessionExt;
 use actix_web::body::{EitherBody, MessageBody};
 use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
-use actix_web::HttpResponse;
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::http::Method;
+use actix_web::ResponseError;
+use actix_web::{web, HttpMessage};
 use futures::future::LocalBoxFuture;
+use sqlx::SqlitePool;
+
+use crate::models::audit_models::{Audit, AuditAppend, AuditEntry, AuditSrv};
+use crate::models::error_models::VaderError;
+use crate::models::log_models::{LogContext, REQUEST_ID_HEADER};
+use crate::services::log_services::{
+    new_request_id, request_id, set_admin, valid_request_id, with_context, with_context_sync,
+};
+use crate::services::metrics_services::metrics;
 
 //Admin only guard middleware
 // Use Actix Identity for production
@@ -51,6 +67,10 @@ where
     fn call(&self, req: ServiceRequest) -> Self::Future {
         let session = req.get_session();
         if let Some(true) = session.get::<bool>("admin").ok().flatten() {
+            //sessions from before usernames were stored just go without
+            if let Some(username) = session.get::<String>("admin_user").ok().flatten() {
+                set_admin(username);
+            }
             let fut = self.service.call(req);
             Box::pin(async move {
                 let res = fut.await?;
@@ -61,11 +81,249 @@ where
             Box::pin(async move {
                 Ok(ServiceResponse::<EitherBody<B>>::new(
                     req.into_parts().0,
-                    HttpResponse::Unauthorized()
-                        .body("Unauthorized Access request")
+                    VaderError::Unauthorized("Unauthorized Access request")
+                        .error_response()
                         .map_into_right_body(),
                 ))
             })
         }
     }
 }
+
+// request count and latency per route for /metrics , labelled with the route
+// pattern so ids in paths don't blow up the label count : )
+pub struct RequestMetrics;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestMetrics
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type InitError = ();
+    type Transform = RequestMetricsService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestMetricsService { service }))
+    }
+}
+
+pub struct RequestMetricsService<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestMetricsService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+    type Error = actix_web::Error;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let start = Instant::now();
+        let method = req.method().to_string();
+        let route = req
+            .match_pattern()
+            .unwrap_or_else(|| "unmatched".to_string());
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let res = fut.await?;
+            let status = res.status().as_u16().to_string();
+            let metrics = metrics();
+            metrics
+                .http_requests
+                .with_label_values(&[&method, &route, &status])
+                .inc();
+            metrics
+                .http_duration
+                .with_label_values(&[&method, &route])
+                .observe(start.elapsed().as_secs_f64());
+            Ok(res)
+        })
+    }
+}
+
+// tags each request with an id , kept for every line logged while serving it
+// and sent back as X-Request-Id , then logs the request once it's answered : )
+pub struct RequestLogger;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestLogger
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type InitError = ();
+    type Transform = RequestLoggerService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestLoggerService { service }))
+    }
+}
+
+pub struct RequestLoggerService<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestLoggerService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+    type Error = actix_web::Error;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let start = Instant::now();
+        let request_id = req
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|id| id.to_str().ok())
+            .filter(|id| valid_request_id(id))
+            .map(String::from)
+            .unwrap_or_else(new_request_id);
+        let method = req.method().to_string();
+        let path = req.path().to_string();
+        let ctx = Rc::new(RefCell::new(LogContext {
+            request_id: request_id.clone(),
+            admin: None,
+        }));
+        let fut = with_context_sync(ctx.clone(), || self.service.call(req));
+        Box::pin(with_context(ctx, async move {
+            let mut res = fut.await?;
+            if let Ok(id) = HeaderValue::from_str(&request_id) {
+                res.headers_mut()
+                    .insert(HeaderName::from_static(REQUEST_ID_HEADER), id);
+            }
+            let status = res.status().as_u16();
+            //probes hit these every few seconds
+            let level = match path.as_str() {
+                "/healthz" | "/readyz" => log::Level::Debug,
+                _ => log::Level::Info,
+            };
+            log::log!(
+                target: "vader_admin::access",
+                level,
+                method = method.as_str(),
+                path = path.as_str(),
+                status = status,
+                duration_ms = start.elapsed().as_millis() as u64;
+                "{} {} {}",
+                method,
+                path,
+                status
+            );
+            Ok(res)
+        }))
+    }
+}
+
+// writes an audit entry for every mutating request , failed and unauthorized
+// ones included , handlers fill in the details through the Audit extractor : )
+pub struct AuditLog;
+
+impl<S, B> Transform<S, ServiceRequest> for AuditLog
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type InitError = ();
+    type Transform = AuditLogService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(AuditLogService { service }))
+    }
+}
+
+pub struct AuditLogService<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for AuditLogService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+    type Error = actix_web::Error;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        // admin commands and logins only , public registration isn't an admin action
+        let audited =
+            req.path() == "/login" || req.path() == "/admin" || req.path().starts_with("/admin/");
+        if !audited || matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS) {
+            return Box::pin(self.service.call(req));
+        }
+        let audit = Audit::default();
+        req.extensions_mut().insert(audit.clone());
+        let action = format!(
+            "{} {}",
+            req.method(),
+            req.match_pattern()
+                .unwrap_or_else(|| req.path().to_string())
+        );
+        let session = req.get_session();
+        let actor = match session.get::<bool>("admin").ok().flatten() {
+            Some(true) => session.get::<String>("admin_user").ok().flatten(),
+            _ => None,
+        };
+        let request_id = request_id();
+        let db_pool = req.app_data::<web::Data<SqlitePool>>().cloned();
+        let audit_srv = req.app_data::<web::Data<Addr<AuditSrv>>>().cloned();
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let res = fut.await?;
+            let status = res.status();
+            let error = match res.response().error() {
+                Some(e) => Some(
+                    e.as_error::<VaderError<'static>>()
+                        .map(|e| e.code().to_string())
+                        .unwrap_or_else(|| status.as_str().to_string()),
+                ),
+                None if status.as_u16() == 401 => Some("unauthorized".to_string()),
+                None if status.as_u16() >= 400 => Some(status.as_str().to_string()),
+                None => None,
+            };
+            let mut note = audit.take();
+            if note.actor.is_none() {
+                note.actor = actor;
+            }
+            if let Some(db_pool) = db_pool {
+                match AuditEntry::record(note, action, request_id, status.as_u16(), error, &db_pool)
+                    .await
+                {
+                    Ok(entry) => {
+                        if let Some(audit_srv) = audit_srv {
+                            audit_srv.do_send(AuditAppend(entry));
+                        }
+                    }
+                    Err(e) => log::error!("Unable to write audit entry : {}", e),
+                }
+            }
+            Ok(res)
+        })
+    }
+}
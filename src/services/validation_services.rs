@@ -0,0 +1,186 @@
+use std::borrow::Cow;
+
+use validator::{Validate, ValidationError, ValidationErrors, ValidationErrorsKind};
+
+use crate::models::command_models::PlayerPatch;
+use crate::models::error_models::VaderError;
+use crate::models::query_models::EventType;
+use crate::models::v_models::ScoreLimits;
+use crate::models::validation_models::{
+    FieldError, LOGO_MAX_LEN, NAME_MAX_LEN, SCORE_DELTA_MAX, TEAM_SIZE_MAX,
+};
+
+fn invalid(code: &'static str, message: String) -> ValidationError {
+    let mut err = ValidationError::new(code);
+    err.message = Some(Cow::Owned(message));
+    err
+}
+
+// 1..=64 chars , no control chars and no surrounding whitespace : )
+pub fn validate_name(name: &str) -> Result<(), ValidationError> {
+    let len = name.chars().count();
+    if len == 0 || len > NAME_MAX_LEN {
+        return Err(invalid(
+            "length",
+            format!("must be 1 to {} characters", NAME_MAX_LEN),
+        ));
+    }
+    if name.trim() != name {
+        return Err(invalid(
+            "whitespace",
+            "must not start or end with whitespace".to_string(),
+        ));
+    }
+    if name.chars().any(char::is_control) {
+        return Err(invalid(
+            "characters",
+            "must not contain control characters".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// empty logo is stored as no logo , anything else must be a http(s) url
+pub fn validate_logo(logo: &str) -> Result<(), ValidationError> {
+    if logo.is_empty() {
+        return Ok(());
+    }
+    if logo.len() > LOGO_MAX_LEN {
+        return Err(invalid(
+            "length",
+            format!("must be at most {} bytes", LOGO_MAX_LEN),
+        ));
+    }
+    let is_http = logo.starts_with("http://") || logo.starts_with("https://");
+    if !is_http || !validator::validate_url(logo) {
+        return Err(invalid("url", "must be a http(s) url".to_string()));
+    }
+    Ok(())
+}
+
+pub fn validate_event_type(event_type: &EventType) -> Result<(), ValidationError> {
+    match event_type {
+        EventType::TeamEvent { team_size } if !(1..=TEAM_SIZE_MAX).contains(team_size) => {
+            Err(invalid(
+                "team_size",
+                format!("team_size must be between 1 and {}", TEAM_SIZE_MAX),
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+pub fn validate_score_delta(score: i64) -> Result<(), ValidationError> {
+    if score.abs() > SCORE_DELTA_MAX {
+        return Err(invalid(
+            "range",
+            format!("must be between -{0} and {0}", SCORE_DELTA_MAX),
+        ));
+    }
+    Ok(())
+}
+
+pub fn validate_score_limits(limits: &ScoreLimits) -> Result<(), ValidationError> {
+    if let (Some(min), Some(max)) = (limits.min_score, limits.max_score) {
+        if min > max {
+            return Err(invalid(
+                "range",
+                "min_score must not be greater than max_score".to_string(),
+            ));
+        }
+    }
+    if let Some(max_delta) = limits.max_delta {
+        if !(1..=SCORE_DELTA_MAX).contains(&max_delta) {
+            return Err(invalid(
+                "range",
+                format!("max_delta must be between 1 and {}", SCORE_DELTA_MAX),
+            ));
+        }
+    }
+    Ok(())
+}
+
+pub fn validate_patch(patch: &PlayerPatch) -> Result<(), ValidationError> {
+    if patch.name.is_none() && patch.logo.is_none() {
+        return Err(invalid(
+            "empty",
+            "nothing to update , set name and/or logo".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+impl ScoreLimits {
+    // checked against the event before the update is applied
+    pub fn check_delta(&self, delta: i64) -> Result<(), VaderError<'static>> {
+        match self.max_delta {
+            Some(max_delta) if delta.abs() > max_delta => {
+                Err(VaderError::ValidationError(vec![FieldError {
+                    field: "score".to_string(),
+                    code: "max_delta".to_string(),
+                    message: format!("score update must be between -{0} and {0}", max_delta),
+                }]))
+            }
+            _ => Ok(()),
+        }
+    }
+    pub fn out_of_bounds(&self) -> VaderError<'static> {
+        let bound = |b: Option<i64>| b.map_or("unbounded".to_string(), |b| b.to_string());
+        VaderError::ValidationError(vec![FieldError {
+            field: "score".to_string(),
+            code: "score_bounds".to_string(),
+            message: format!(
+                "resulting score must be between {} and {}",
+                bound(self.min_score),
+                bound(self.max_score)
+            ),
+        }])
+    }
+}
+
+fn flatten_errors(prefix: &str, errors: &ValidationErrors, out: &mut Vec<FieldError>) {
+    for (field, kind) in errors.errors() {
+        // schema level errors are reported on the struct itself
+        let path = match (prefix.is_empty(), *field == "__all__") {
+            (true, true) => String::new(),
+            (true, false) => field.to_string(),
+            (false, true) => prefix.to_string(),
+            (false, false) => format!("{}.{}", prefix, field),
+        };
+        match kind {
+            ValidationErrorsKind::Field(errs) => out.extend(errs.iter().map(|e| {
+                FieldError {
+                    field: path.clone(),
+                    code: e.code.to_string(),
+                    message: e
+                        .message
+                        .as_ref()
+                        .map_or_else(|| format!("failed {} check", e.code), |m| m.to_string()),
+                }
+            })),
+            ValidationErrorsKind::Struct(errs) => flatten_errors(&path, errs, out),
+            ValidationErrorsKind::List(errs) => {
+                for (i, errs) in errs {
+                    flatten_errors(&format!("{}[{}]", path, i), errs, out);
+                }
+            }
+        }
+    }
+}
+
+impl<'a> From<ValidationErrors> for VaderError<'a> {
+    fn from(errors: ValidationErrors) -> Self {
+        let mut fields = Vec::new();
+        flatten_errors("", &errors, &mut fields);
+        // hashmap order is random , keep responses stable
+        fields.sort_by(|a, b| a.field.cmp(&b.field));
+        VaderError::ValidationError(fields)
+    }
+}
+
+// validate and flatten into one message , for the cli and import rows : )
+pub fn validation_message<T: Validate>(value: &T) -> Result<(), String> {
+    value
+        .validate()
+        .map_err(|e| VaderError::from(e).to_string())
+}
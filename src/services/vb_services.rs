@@ -1,15 +1,35 @@
-use actix::{AsyncContext, ContextFutureSpawner, Handler, StreamHandler, WrapFuture};
+use std::borrow::Cow;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+use actix::{
+    ActorContext, AsyncContext, ContextFutureSpawner, Handler, MessageResult, StreamHandler,
+    WrapFuture,
+};
+use actix_web::web::Bytes;
 use actix_web_actors::ws;
 
+use crate::models::health_models::HealthPing;
 use crate::models::query_models::{
-    TransferType, VbConnect, VbDisconnect, VboardClient, VboardGet, VboardRes, VboardSrv,
+    LifecycleData, SseConnect, SseEvent, TransferType, VbConnect, VbCount, VbDisconnect,
+    VboardClient, VboardGet, VboardLifecycle, VboardRes, VboardSrv, SSE_HISTORY, SSE_RETRY_MS,
 };
+use crate::models::ws_models::{WsActor, WsConn, WsLimit};
+use crate::services::metrics_services::metrics;
+use crate::services::ws_services::close_ws;
 
 impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for VboardClient {
     fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
         use ws::Message::*;
+        self.conn.beat();
         match msg {
             Ok(Ping(msg)) => ctx.pong(&msg),
+            Ok(Close(reason)) => {
+                ctx.close(reason);
+                ctx.stop();
+            }
+            Err(_) => ctx.stop(),
+            Ok(Text(_)) if !self.conn.allow_query() => close_ws(ctx, &WsLimit::Rate),
             Ok(Text(_)) => self
                 .srv_addr
                 .do_send(VboardGet(TransferType::Unicast(self.addr.clone().unwrap()))),
@@ -18,6 +38,12 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for VboardClient {
     }
 }
 
+impl WsActor for VboardClient {
+    fn conn(&mut self) -> &mut WsConn {
+        &mut self.conn
+    }
+}
+
 impl Handler<VboardRes<'_>> for VboardClient {
     type Result = ();
     fn handle(&mut self, msg: VboardRes, ctx: &mut Self::Context) -> Self::Result {
@@ -38,6 +64,18 @@ impl Handler<VbConnect> for VboardSrv {
         addr.do_send(VboardGet(TransferType::Unicast(msg.0)));
     }
 }
+impl Handler<VbCount> for VboardSrv {
+    type Result = MessageResult<VbCount>;
+    fn handle(&mut self, _msg: VbCount, _ctx: &mut Self::Context) -> Self::Result {
+        //closed streams are otherwise only noticed on the next send
+        self.sse_clients.retain(|tx| !tx.is_closed());
+        MessageResult((self.vb_addr.len(), self.sse_clients.len()))
+    }
+}
+impl Handler<HealthPing> for VboardSrv {
+    type Result = ();
+    fn handle(&mut self, _msg: HealthPing, _ctx: &mut Self::Context) -> Self::Result {}
+}
 impl Handler<VbDisconnect> for VboardSrv {
     type Result = ();
     fn handle(&mut self, msg: VbDisconnect, _ctx: &mut Self::Context) -> Self::Result {
@@ -59,6 +97,16 @@ impl Handler<VboardRes<'static>> for VboardSrv {
                     self.vb_addr
                         .iter()
                         .for_each(|addr| addr.do_send(VboardRes(vb_str.clone(), None)));
+                    self.sse_publish("vaderboard", vb_str);
+                }
+                //a snapshot is as new as the last event , it doesn't get an id of its own
+                TransferType::SseUnicast(tx) => {
+                    let event = SseEvent {
+                        id: self.sse_seq,
+                        kind: "vaderboard",
+                        data: vb_str,
+                    };
+                    let _ = tx.try_send(event.frame());
                 }
             }
         }
@@ -67,14 +115,27 @@ impl Handler<VboardRes<'static>> for VboardSrv {
 impl Handler<VboardGet> for VboardSrv {
     type Result = ();
     fn handle(&mut self, msg: VboardGet, ctx: &mut Self::Context) -> Self::Result {
+        //a broadcast means the scores changed , stale the http etags
+        if let TransferType::Broadcast = msg.0 {
+            self.app_state.vb_version.fetch_add(1, Ordering::SeqCst);
+        }
         let addr = ctx.address();
         let event_lock = self.app_state.clone();
         let db_pool = self.db_pool.clone();
         let vb_count = self.app_state.vb_count;
+        let start = Instant::now();
         async move {
             let event = event_lock.as_ref().current_event.lock().await;
             if let Some(e) = event.as_ref() {
+                let query_start = Instant::now();
                 let vb_res = e.get_vboard(&db_pool, vb_count).await;
+                metrics().observe_query("vaderboard", query_start);
+                if let (Ok(_), TransferType::Broadcast) = (&vb_res, &msg.0) {
+                    metrics().vb_broadcasts.inc();
+                    metrics()
+                        .vb_broadcast_duration
+                        .observe(start.elapsed().as_secs_f64());
+                }
                 match vb_res {
                     Ok(vb_str) => addr.do_send(VboardRes(vb_str, Some(msg.0))),
                     Err(e) => log::debug!("Error sending Vaderboard : {}", e),
@@ -85,3 +146,86 @@ impl Handler<VboardGet> for VboardSrv {
         .wait(ctx)
     }
 }
+
+impl Handler<SseConnect> for VboardSrv {
+    type Result = ();
+    fn handle(&mut self, msg: SseConnect, ctx: &mut Self::Context) -> Self::Result {
+        let SseConnect { tx, last_event_id } = msg;
+        let _ = tx.try_send(Bytes::from(format!("retry: {}\n\n", SSE_RETRY_MS)));
+        //replay what was missed if it is all still in the history , else start over
+        let missed = last_event_id.filter(|&id| {
+            id <= self.sse_seq
+                && self
+                    .sse_history
+                    .front()
+                    .map_or(id == self.sse_seq, |first| first.id <= id + 1)
+        });
+        match missed {
+            // SSE_BUFFER fits the whole history , a gap would be a bug
+            Some(id) => self
+                .sse_history
+                .iter()
+                .filter(|event| event.id > id)
+                .for_each(|event| {
+                    if let Err(e) = tx.try_send(event.frame()) {
+                        log::warn!("Event stream replay dropped a frame : {}", e);
+                    }
+                }),
+            None => ctx
+                .address()
+                .do_send(VboardGet(TransferType::SseUnicast(tx.clone()))),
+        }
+        self.sse_clients.push(tx);
+        log::debug!(
+            "New event stream.Total event stream count : {}",
+            self.sse_clients.len()
+        );
+    }
+}
+impl Handler<VboardLifecycle> for VboardSrv {
+    type Result = ();
+    fn handle(&mut self, msg: VboardLifecycle, _ctx: &mut Self::Context) -> Self::Result {
+        let VboardLifecycle(state, event_id) = msg;
+        match serde_json::to_string(&LifecycleData { event_id, state }) {
+            Ok(data) => self.sse_publish(state.as_str(), data.into()),
+            Err(e) => log::debug!("Error sending event lifecycle : {}", e),
+        }
+    }
+}
+
+impl VboardSrv {
+    fn sse_publish(&mut self, kind: &'static str, data: Cow<'static, str>) {
+        self.sse_seq += 1;
+        let event = SseEvent {
+            id: self.sse_seq,
+            kind,
+            data,
+        };
+        self.sse_send(event.frame());
+        if self.sse_history.len() == SSE_HISTORY {
+            self.sse_history.pop_front();
+        }
+        self.sse_history.push_back(event);
+    }
+    // closed streams are dropped , so are lagging ones , they resume with Last-Event-ID : )
+    pub fn sse_send(&mut self, frame: Bytes) {
+        let count = self.sse_clients.len();
+        self.sse_clients
+            .retain(|tx| tx.try_send(frame.clone()).is_ok());
+        if self.sse_clients.len() != count {
+            log::debug!(
+                "Event stream closed.Total event stream count : {}",
+                self.sse_clients.len()
+            );
+        }
+    }
+}
+
+impl SseEvent {
+    pub fn frame(&self) -> Bytes {
+        Bytes::from(format!(
+            "id: {}\nevent: {}\ndata: {}\n\n",
+            self.id, self.kind, self.data
+        ))
+    }
+}
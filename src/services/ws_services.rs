@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use actix::{Actor, ActorContext, AsyncContext, StreamHandler};
+use actix_web::{web, HttpRequest, HttpResponse};
+use actix_web_actors::ws;
+
+use crate::models::config_models::WsConfig;
+use crate::models::ws_models::{WsActor, WsConn, WsGate, WsLimit, WsReject};
+use crate::services::log_services::{new_request_id, request_id};
+
+impl WsGate {
+    pub fn new(config: &WsConfig) -> Self {
+        WsGate {
+            config: config.clone(),
+            live: Arc::new(AtomicUsize::new(0)),
+            per_ip: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn live(&self) -> usize {
+        self.live.load(Ordering::SeqCst)
+    }
+
+    // `count` is the page size asked for in the path , if the route has one
+    pub fn admit(&self, req: &HttpRequest, count: Option<u32>) -> Result<WsConn, WsLimit> {
+        let max_count = self.config.max_count;
+        if count.is_some_and(|count| count == 0 || count > max_count) {
+            return Err(WsLimit::Count(max_count));
+        }
+        if self.live.fetch_add(1, Ordering::SeqCst) >= self.config.max_connections {
+            self.live.fetch_sub(1, Ordering::SeqCst);
+            return Err(WsLimit::Connections);
+        }
+        let ip = client_ip(req, self.config.trust_forwarded);
+        if let Some(ip) = ip {
+            let mut per_ip = self.per_ip.lock().unwrap_or_else(|e| e.into_inner());
+            let conns = per_ip.entry(ip).or_default();
+            if *conns >= self.config.max_per_ip {
+                drop(per_ip);
+                self.live.fetch_sub(1, Ordering::SeqCst);
+                return Err(WsLimit::PerIp);
+            }
+            *conns += 1;
+        }
+        let now = Instant::now();
+        let id = request_id().unwrap_or_else(new_request_id);
+        log::debug!(request_id = id.as_str(); "Connection opened");
+        Ok(WsConn {
+            id,
+            opened: now,
+            config: self.config.clone(),
+            last_seen: now,
+            tokens: self.config.queries_per_sec as f64,
+            refilled: now,
+            pending: None,
+            ip,
+            live: self.live.clone(),
+            per_ip: self.per_ip.clone(),
+        })
+    }
+}
+
+// X-Forwarded-For/Forwarded can be set by anyone , only trusted behind a proxy
+pub fn client_ip(req: &HttpRequest, trust_forwarded: bool) -> Option<IpAddr> {
+    if trust_forwarded {
+        let conn_info = req.connection_info();
+        let addr = conn_info.realip_remote_addr()?;
+        addr.parse::<SocketAddr>()
+            .map(|addr| addr.ip())
+            .or_else(|_| addr.parse::<IpAddr>())
+            .ok()
+    } else {
+        req.peer_addr().map(|addr| addr.ip())
+    }
+}
+
+impl WsConn {
+    // any frame from the client counts , not just pongs
+    pub fn beat(&mut self) {
+        self.last_seen = Instant::now();
+    }
+
+    pub fn allow_query(&mut self) -> bool {
+        let rate = self.config.queries_per_sec as f64;
+        let now = Instant::now();
+        let refill = now.duration_since(self.refilled).as_secs_f64() * rate;
+        self.tokens = (self.tokens + refill).min(rate);
+        self.refilled = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+impl Drop for WsConn {
+    fn drop(&mut self) {
+        log::debug!(
+            request_id = self.id.as_str(),
+            duration_ms = self.opened.elapsed().as_millis() as u64;
+            "Connection closed"
+        );
+        self.live.fetch_sub(1, Ordering::SeqCst);
+        if let Some(ip) = self.ip {
+            let mut per_ip = self.per_ip.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(conns) = per_ip.get_mut(&ip) {
+                *conns -= 1;
+                if *conns == 0 {
+                    per_ip.remove(&ip);
+                }
+            }
+        }
+    }
+}
+
+impl WsLimit {
+    pub fn close_reason(&self) -> ws::CloseReason {
+        let (code, description) = match self {
+            WsLimit::Connections => (
+                ws::CloseCode::Again,
+                "too many connections , try again later".to_string(),
+            ),
+            WsLimit::PerIp => (
+                ws::CloseCode::Policy,
+                "too many connections from this address".to_string(),
+            ),
+            WsLimit::Count(max) => (
+                ws::CloseCode::Policy,
+                format!("count must be between 1 and {}", max),
+            ),
+            WsLimit::Rate => (ws::CloseCode::Policy, "rate limit exceeded".to_string()),
+        };
+        ws::CloseReason {
+            code,
+            description: Some(description),
+        }
+    }
+}
+
+impl Actor for WsReject {
+    type Context = ws::WebsocketContext<Self>;
+    fn started(&mut self, ctx: &mut Self::Context) {
+        close_ws(ctx, &self.0);
+    }
+}
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for WsReject {
+    fn handle(&mut self, _msg: Result<ws::Message, ws::ProtocolError>, _ctx: &mut Self::Context) {}
+}
+
+// starts the websocket actor made by `actor` , or closes the socket with the
+// limit it ran into : )
+pub fn start_ws<A, F>(
+    gate: &WsGate,
+    req: &HttpRequest,
+    stream: web::Payload,
+    count: Option<u32>,
+    actor: F,
+) -> Result<HttpResponse, actix_web::Error>
+where
+    A: WsActor + StreamHandler<Result<ws::Message, ws::ProtocolError>>,
+    F: FnOnce(WsConn) -> A,
+{
+    match gate.admit(req, count) {
+        Ok(conn) => ws::start(actor(conn), req, stream),
+        Err(limit) => {
+            log::debug!("Websocket refused : {:?}", limit.close_reason().description);
+            ws::start(WsReject(limit), req, stream)
+        }
+    }
+}
+
+pub fn close_ws<A>(ctx: &mut ws::WebsocketContext<A>, limit: &WsLimit)
+where
+    A: Actor<Context = ws::WebsocketContext<A>>,
+{
+    ctx.close(Some(limit.close_reason()));
+    ctx.stop();
+}
+
+// ping every interval , close the socket once the client misses the timeout.
+// stopping runs the actor's `stopped` , which deregisters it : )
+pub fn start_heartbeat<A: WsActor>(act: &mut A, ctx: &mut ws::WebsocketContext<A>) {
+    let interval = Duration::from_secs(act.conn().config.heartbeat_secs);
+    ctx.run_interval(interval, |act, ctx| {
+        let conn = act.conn();
+        let timeout = Duration::from_secs(conn.config.timeout_secs);
+        if conn.last_seen.elapsed() > timeout {
+            log::debug!(
+                request_id = conn.id.as_str(),
+                silent_ms = conn.last_seen.elapsed().as_millis() as u64;
+                "Websocket missed heartbeats , closing"
+            );
+            ctx.close(Some(ws::CloseReason {
+                code: ws::CloseCode::Away,
+                description: Some("heartbeat timeout".to_string()),
+            }));
+            ctx.stop();
+        } else {
+            ctx.ping(b"");
+        }
+    });
+}
+
+// runs a query once the client stops typing for `debounce_ms` , a newer search
+// replaces the pending one. Queries past the rate limit close the socket : )
+pub fn schedule_query<A, F>(act: &mut A, ctx: &mut ws::WebsocketContext<A>, debounce: bool, run: F)
+where
+    A: WsActor,
+    F: FnOnce(&mut A, &mut ws::WebsocketContext<A>) + 'static,
+{
+    if !debounce {
+        return run_query(act, ctx, run);
+    }
+    if let Some(pending) = act.conn().pending.take() {
+        ctx.cancel_future(pending);
+    }
+    let delay = Duration::from_millis(act.conn().config.debounce_ms);
+    let pending = ctx.run_later(delay, move |act, ctx| {
+        act.conn().pending = None;
+        run_query(act, ctx, run)
+    });
+    act.conn().pending = Some(pending);
+}
+
+fn run_query<A, F>(act: &mut A, ctx: &mut ws::WebsocketContext<A>, run: F)
+where
+    A: WsActor,
+    F: FnOnce(&mut A, &mut ws::WebsocketContext<A>),
+{
+    if act.conn().allow_query() {
+        run(act, ctx)
+    } else {
+        log::debug!(request_id = act.conn().id.as_str(); "Websocket over the rate limit , closing");
+        close_ws(ctx, &WsLimit::Rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use super::*;
+
+    fn gate(max_connections: usize, max_per_ip: usize) -> WsGate {
+        WsGate::new(&WsConfig {
+            max_connections,
+            max_per_ip,
+            max_count: 10,
+            queries_per_sec: 3,
+            ..Default::default()
+        })
+    }
+
+    fn from(ip: &str) -> HttpRequest {
+        TestRequest::default()
+            .peer_addr(format!("{}:4000", ip).parse().unwrap())
+            .to_http_request()
+    }
+
+    #[test]
+    fn admit_counts_per_ip() {
+        let gate = gate(10, 2);
+        let first = gate.admit(&from("10.0.0.1"), None).ok().unwrap();
+        let _second = gate.admit(&from("10.0.0.1"), None).ok().unwrap();
+        assert!(matches!(
+            gate.admit(&from("10.0.0.1"), None),
+            Err(WsLimit::PerIp)
+        ));
+        // a refused connection holds no slot
+        assert_eq!(gate.live(), 2);
+        let _other = gate.admit(&from("10.0.0.2"), None).ok().unwrap();
+        drop(first);
+        assert!(gate.admit(&from("10.0.0.1"), None).is_ok());
+    }
+
+    #[test]
+    fn admit_counts_in_total() {
+        let gate = gate(2, 5);
+        let conns = [
+            gate.admit(&from("10.0.0.1"), None).ok().unwrap(),
+            gate.admit(&from("10.0.0.2"), None).ok().unwrap(),
+        ];
+        assert!(matches!(
+            gate.admit(&from("10.0.0.3"), None),
+            Err(WsLimit::Connections)
+        ));
+        assert_eq!(gate.live(), 2);
+        drop(conns);
+        assert_eq!(gate.live(), 0);
+        assert!(gate.per_ip.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn admit_checks_count() {
+        let gate = gate(10, 10);
+        assert!(matches!(
+            gate.admit(&from("10.0.0.1"), Some(0)),
+            Err(WsLimit::Count(10))
+        ));
+        assert!(matches!(
+            gate.admit(&from("10.0.0.1"), Some(11)),
+            Err(WsLimit::Count(10))
+        ));
+        assert_eq!(gate.live(), 0);
+        assert!(gate.admit(&from("10.0.0.1"), Some(10)).is_ok());
+    }
+
+    #[test]
+    fn queries_spend_and_refill_tokens() {
+        let gate = gate(10, 10);
+        let mut conn = gate.admit(&from("10.0.0.1"), None).ok().unwrap();
+        // a full bucket is one second of queries
+        assert!((0..3).all(|_| conn.allow_query()));
+        assert!(!conn.allow_query());
+        // a third of a second buys one more
+        conn.refilled -= Duration::from_millis(340);
+        assert!(conn.allow_query());
+        assert!(!conn.allow_query());
+        // idle time doesn't bank more than a full bucket
+        conn.refilled -= Duration::from_secs(60);
+        assert_eq!((0..10).filter(|_| conn.allow_query()).count(), 3);
+    }
+}
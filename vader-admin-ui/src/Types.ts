@@ -1,12 +1,27 @@
 export type EventType = "UserEvent" | { TeamEvent: { team_size: number } };
 
-export interface EventInfo {
+// only set on search results , see SearchName
+export interface SearchMatch {
+  matched?: "name" | "member" | "fuzzy";
+  // html escaped , matched parts wrapped in <mark>
+  highlight?: string;
+}
+
+export interface EventInfo extends SearchMatch {
   id: string;
   name: string;
   logo: string;
   event_type: EventType;
+  score_limits?: ScoreLimits;
   state: EventState;
 }
+
+// null is unbounded
+export interface ScoreLimits {
+  min_score: number | null;
+  max_score: number | null;
+  max_delta: number | null;
+}
 export enum EventState {
   Added,
   Start,
@@ -22,7 +37,7 @@ export enum TeamEventOpts {
   TeamList = "Team List",
 }
 
-export interface PlayerInfo {
+export interface PlayerInfo extends SearchMatch {
   id: string;
   name: string;
   logo: string;
@@ -36,3 +51,36 @@ export interface ScoreUpdate {
 }
 export type UserInfo = PlayerInfo;
 export type TeamInfo = PlayerInfo;
+
+// json body of every non 2xx api response
+export interface ApiError {
+  code: string;
+  message: string;
+  details: string;
+  // only on validation_error , one entry per failed field ie: members[1].name
+  fields?: Array<{ field: string; code: string; message: string }>;
+}
+
+export type SortKey = "name" | "score" | "created_at";
+
+export interface PageParams {
+  sort?: SortKey;
+  order?: "asc" | "desc";
+  limit?: number;
+  // next_cursor of the previous page
+  cursor?: string;
+}
+
+// rest list endpoints , next_cursor is null on the last page
+export interface Page<T> {
+  items: Array<T>;
+  next_cursor: string | null;
+}
+
+// websocket search replies , send {"op":"more"} for the next page
+export interface SearchPage<T> {
+  query: string;
+  offset: number;
+  items: Array<T>;
+  more: boolean;
+}
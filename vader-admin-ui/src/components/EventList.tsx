@@ -5,10 +5,13 @@ import {
   IconButton,
   Container,
   TextField,
+  Button,
 } from "@mui/material";
 import FileCopyIcon from "@mui/icons-material/FileCopy";
 import { useEffect, useState } from "react";
-import { EventInfo, EventType } from "../Types";
+import { EventInfo, EventType, SearchPage } from "../Types";
+import { loadMore } from "../utils/ApiUtils";
+import SearchName from "./SearchName";
 
 interface EventListProps {
   url: string;
@@ -17,6 +20,7 @@ interface EventListProps {
 const EventList = (props: EventListProps): JSX.Element => {
   const [events, setEvents] = useState<Array<EventInfo>>([]);
   const [socket, setSocket] = useState<WebSocket>();
+  const [more, setMore] = useState<boolean>(false);
 
   const getEventType = (event_type: EventType): string => {
     if (event_type == "UserEvent") {
@@ -34,8 +38,12 @@ const EventList = (props: EventListProps): JSX.Element => {
     });
 
     ws.addEventListener("message", (event) => {
-      const data: Array<EventInfo> = JSON.parse(event.data);
-      setEvents(data);
+      const page: SearchPage<EventInfo> = JSON.parse(event.data);
+      // offset 0 is a new search , anything else is the next page
+      setEvents((prev) =>
+        page.offset === 0 ? page.items : [...prev, ...page.items]
+      );
+      setMore(page.more);
     });
 
     setSocket(ws);
@@ -71,7 +79,7 @@ const EventList = (props: EventListProps): JSX.Element => {
         {events.map((event) => (
           <ListItem key={event.id}>
             <ListItemText
-              primary={event.name}
+              primary={<SearchName name={event.name} hit={event} />}
               secondary={`${getEventType(event.event_type)} - ${event.id
                 }`}
             />
@@ -84,6 +92,11 @@ const EventList = (props: EventListProps): JSX.Element => {
           </ListItem>
         ))}
       </List>
+      {more && socket && (
+        <Button onClick={() => loadMore(socket)} fullWidth>
+          Load more
+        </Button>
+      )}
     </Container>
   );
 };
@@ -0,0 +1,27 @@
+import { SearchMatch } from "../Types";
+
+interface SearchNameProps {
+    name: string;
+    hit: SearchMatch;
+}
+
+// the server escapes highlights , only the <mark> tags are html
+const SearchName = (props: SearchNameProps): JSX.Element => {
+    const { name, hit } = props;
+    if (!hit.highlight) {
+        return <>{name}</>;
+    }
+    if (hit.matched === "member") {
+        return (
+            <>
+                {name}{" "}
+                <small>
+                    (<span dangerouslySetInnerHTML={{ __html: hit.highlight }} />)
+                </small>
+            </>
+        );
+    }
+    return <span dangerouslySetInnerHTML={{ __html: hit.highlight }} />;
+};
+
+export default SearchName;
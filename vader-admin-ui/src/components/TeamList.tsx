@@ -13,8 +13,9 @@ import {
 } from "@mui/material";
 import FileCopyIcon from "@mui/icons-material/FileCopy";
 import { useEffect, useState } from "react";
-import { TeamInfo } from "../Types";
-import { apiUrl } from "../utils/ApiUtils";
+import { SearchPage, TeamInfo } from "../Types";
+import { apiUrl, loadMore } from "../utils/ApiUtils";
+import SearchName from "./SearchName";
 
 interface TeamListProps {
     url: string;
@@ -24,6 +25,7 @@ interface TeamListProps {
 const TeamList = (props: TeamListProps): JSX.Element => {
     const [teams, setTeams] = useState<Array<TeamInfo>>([]);
     const [socket, setSocket] = useState<WebSocket>();
+    const [more, setMore] = useState<boolean>(false);
     const [selectedTeamId, setSelectedTeamId] = useState<string>("");
     const [isDialogOpen, setIsDialogOpen] = useState<boolean>(false);
     const [newScore, setNewScore] = useState<number>(0);
@@ -36,8 +38,12 @@ const TeamList = (props: TeamListProps): JSX.Element => {
         });
 
         ws.addEventListener("message", (event) => {
-            const data: Array<TeamInfo> = JSON.parse(event.data);
-            setTeams(data);
+            const page: SearchPage<TeamInfo> = JSON.parse(event.data);
+            // offset 0 is a new search , anything else is the next page
+            setTeams((prev) =>
+                page.offset === 0 ? page.items : [...prev, ...page.items]
+            );
+            setMore(page.more);
         });
 
         setSocket(ws);
@@ -115,7 +121,7 @@ const TeamList = (props: TeamListProps): JSX.Element => {
                 {teams.map((team) => (
                     <ListItem key={team.id}>
                         <ListItemText
-                            primary={team.name}
+                            primary={<SearchName name={team.name} hit={team} />}
                             secondary={`${team.score} - ${team.id}`}
                         />
                         {props.updateScore && (
@@ -138,6 +144,11 @@ const TeamList = (props: TeamListProps): JSX.Element => {
                     </ListItem>
                 ))}
             </List>
+            {more && socket && (
+                <Button onClick={() => loadMore(socket)} fullWidth>
+                    Load more
+                </Button>
+            )}
             <Dialog open={isDialogOpen} onClose={() => setIsDialogOpen(false)}>
                 <DialogTitle>Update Score</DialogTitle>
                 <DialogContent>
@@ -13,8 +13,9 @@ import {
 } from "@mui/material";
 import FileCopyIcon from "@mui/icons-material/FileCopy";
 import React, { useEffect, useState } from "react";
-import { UserInfo } from "../Types";
-import { apiUrl } from "../utils/ApiUtils";
+import { SearchPage, UserInfo } from "../Types";
+import { apiUrl, loadMore } from "../utils/ApiUtils";
+import SearchName from "./SearchName";
 
 interface UserListProps {
     url: string;
@@ -24,6 +25,7 @@ interface UserListProps {
 const UserList = (props: UserListProps): JSX.Element => {
     const [users, setUsers] = useState<Array<UserInfo>>([]);
     const [socket, setSocket] = useState<WebSocket>();
+    const [more, setMore] = useState<boolean>(false);
     const [selectedUserId, setSelectedUserId] = useState<string>("");
     const [isDialogOpen, setIsDialogOpen] = useState<boolean>(false);
     const [newScore, setNewScore] = useState<number>(0);
@@ -36,8 +38,12 @@ const UserList = (props: UserListProps): JSX.Element => {
         });
 
         ws.addEventListener("message", (event) => {
-            const data: Array<UserInfo> = JSON.parse(event.data);
-            setUsers(data);
+            const page: SearchPage<UserInfo> = JSON.parse(event.data);
+            // offset 0 is a new search , anything else is the next page
+            setUsers((prev) =>
+                page.offset === 0 ? page.items : [...prev, ...page.items]
+            );
+            setMore(page.more);
         });
 
         setSocket(ws);
@@ -115,7 +121,7 @@ const UserList = (props: UserListProps): JSX.Element => {
                 {users.map((user) => (
                     <ListItem key={user.id}>
                         <ListItemText
-                            primary={user.name}
+                            primary={<SearchName name={user.name} hit={user} />}
                             secondary={`${user.score} - ${user.id}`}
                         />
                         {props.updateScore && (
@@ -138,6 +144,11 @@ const UserList = (props: UserListProps): JSX.Element => {
                     </ListItem>
                 ))}
             </List>
+            {more && socket && (
+                <Button onClick={() => loadMore(socket)} fullWidth>
+                    Load more
+                </Button>
+            )}
             <Dialog open={isDialogOpen} onClose={() => setIsDialogOpen(false)}>
                 <DialogTitle>Update Score</DialogTitle>
                 <DialogContent>
@@ -7,7 +7,7 @@ import {
     Snackbar,
     IconButton,
 } from "@mui/material";
-import { apiUrl } from "../utils/ApiUtils";
+import { apiUrl, getApiError } from "../utils/ApiUtils";
 import { useNavigate, useLocation } from "react-router-dom";
 import AddCircleIcon from "@mui/icons-material/AddCircle";
 import { TeamEventOpts } from "../Types";
@@ -58,8 +58,9 @@ export const AddTeam = () => {
                     },
                 });
             } else {
+                const err = await getApiError(response);
                 setShowSnackbar(true);
-                setSnackbarMessage("Failed to add team");
+                setSnackbarMessage(`Failed to add team : ${err.details}`);
             }
         } catch (error: any) {
             setShowSnackbar(true);
@@ -1,6 +1,6 @@
 import { useState } from "react";
 import { Button, Container, TextField, Typography } from "@mui/material";
-import { apiUrl } from "../utils/ApiUtils";
+import { apiUrl, getApiError } from "../utils/ApiUtils";
 import { useNavigate } from "react-router-dom";
 import { UserEventOpts } from "../Types";
 
@@ -28,7 +28,8 @@ export const AddUser: React.FC = () => {
                     state: { opt: UserEventOpts.User },
                 });
             } else {
-                console.log("Failed to add user");
+                const err = await getApiError(response);
+                console.log("Failed to add user :", err.code, err.details);
             }
         } catch (error: any) {
             console.log("Error:", error.message);
@@ -1,5 +1,5 @@
 import { Container, TextField, Button, Typography } from "@mui/material";
-import { apiUrl } from "../utils/ApiUtils";
+import { apiUrl, getApiError } from "../utils/ApiUtils";
 import { useState } from "react";
 import "./Login.css";
 import { useNavigate } from "react-router-dom";
@@ -27,7 +27,10 @@ const Login = (): JSX.Element => {
         if (res.ok) {
             navigate("/home");
         } else {
-            setInvalid("Incorrect Username/Password");
+            const err = await getApiError(res);
+            setInvalid(
+                err.code === "unauthorized" ? "Incorrect Username/Password" : err.message
+            );
         }
     };
     return (
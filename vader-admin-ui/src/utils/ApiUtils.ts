@@ -1,32 +1,46 @@
-import { EventInfo, TeamInfo, UserInfo } from "../Types";
+import { ApiError, EventInfo, Page, PageParams, TeamInfo, UserInfo } from "../Types";
 
 const VITE_API_URL=import.meta.env.VITE_API_URL;
 export const apiUrl = `https://${VITE_API_URL}`;
 export const apiUrlWs = `wss://${VITE_API_URL}`;
 
-export const getEvents = async (): Promise<Array<EventInfo>> => {
-  let events: Array<EventInfo> = [];
-  const url = `${apiUrl}/event/info/all`;
-  const res = await fetch(url, { method: "GET" });
-  events = await res.json();
-  return events;
+export const getApiError = async (res: Response): Promise<ApiError> => {
+  try {
+    return await res.json();
+  } catch {
+    return { code: "unknown", message: res.statusText, details: "" };
+  }
 }
-export const getUsers = async (): Promise<Array<UserInfo>> => {
-  let users: Array<UserInfo> = [];
-  const url = `${apiUrl}/user/info/all`;
-  const res = await fetch(url, { method: "GET" });
-  users = await res.json();
-  return users;
+
+const pageQuery = (params: PageParams): string => {
+  const query = new URLSearchParams();
+  Object.entries(params).forEach(([key, value]) => {
+    if (value !== undefined) {
+      query.set(key, String(value));
+    }
+  });
+  return query.toString();
 }
 
-export const getTeams = async (): Promise<Array<TeamInfo>> => {
-  let teams: Array<TeamInfo> = [];
-  const url = `${apiUrl}/team/info/all`;
-  const res = await fetch(url, { method: "GET" });
-  teams = await res.json();
-  return teams;
+const getPage = async <T>(path: string, params: PageParams): Promise<Page<T>> => {
+  const res = await fetch(`${apiUrl}${path}?${pageQuery(params)}`, { method: "GET" });
+  if (!res.ok) {
+    throw await getApiError(res);
+  }
+  return await res.json();
 }
 
+export const getEvents = (params: PageParams = {}): Promise<Page<EventInfo>> =>
+  getPage<EventInfo>("/event/info/all", params);
+
+export const getUsers = (params: PageParams = {}): Promise<Page<UserInfo>> =>
+  getPage<UserInfo>("/user/info/all", params);
+
+export const getTeams = (params: PageParams = {}): Promise<Page<TeamInfo>> =>
+  getPage<TeamInfo>("/team/info/all", params);
+
+// asks a search websocket for the next page of the last search
+export const loadMore = (ws: WebSocket) => ws.send(JSON.stringify({ op: "more" }));
 
 export const eventFtsUrl = `${apiUrlWs}/event/fts/20`;
 